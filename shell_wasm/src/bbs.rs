@@ -1,4 +1,6 @@
-use crate::state::SystemState;
+use crate::commands::ls;
+use crate::fs::{self, Source};
+use crate::state::{LoginState, Post, SystemState};
 use crate::WasmLine;
 
 const BBS_WIDTH: usize = 80;
@@ -20,12 +22,12 @@ fn get_line(text: &str, align: &str) -> String {
     let text_len = text.chars().count();
     
     let line = if align == "center" {
-        let total_padding = if content_width > text_len { content_width - text_len } else { 0 };
+        let total_padding = content_width.saturating_sub(text_len);
         let pad_left = total_padding / 2;
         let pad_right = total_padding - pad_left;
         format!("{}{}{}", " ".repeat(pad_left), text, " ".repeat(pad_right))
     } else {
-        format!("{}{}", text, " ".repeat(if content_width > text_len { content_width - text_len } else { 0 }))
+        format!("{}{}", text, " ".repeat(content_width.saturating_sub(text_len)))
     };
 
     format!("║ {} ║", line)
@@ -98,6 +100,47 @@ pub fn render_main_menu(state: &SystemState) -> Vec<WasmLine> {
     out
 }
 
+/// The posts of the message area the BBS is in: those in the category or
+/// tag directory it was entered from, or all of them. Posts with a weight
+/// come first, lightest first, as Hugo orders them.
+pub fn area_posts(state: &SystemState) -> Vec<&Post> {
+    let mut posts: Vec<&Post> = if !state.cwd.starts_with("/categories/") && !state.cwd.starts_with("/tags/") {
+        state.posts.iter().collect()
+    } else {
+        fs::tree(state).entries(&state.cwd).unwrap_or_default().into_iter()
+            .filter_map(|(_, inode)| match inode.source {
+                Source::Post(i) => state.posts.get(i),
+                _ => None,
+            })
+            .collect()
+    };
+    posts.sort_by_key(|post| (post.meta.weight == 0, post.meta.weight));
+    posts
+}
+
+/// The categories that have posts, one message area each.
+pub fn areas(state: &SystemState) -> Vec<String> {
+    let tree = fs::tree(state);
+    tree.list("/categories").unwrap_or_default().into_iter()
+        .filter(|category| {
+            tree.entries(&format!("/categories/{}", category)).unwrap_or_default().iter()
+                .any(|(_, inode)| matches!(inode.source, Source::Post(_)))
+        })
+        .collect()
+}
+
+/// One post of the list: its number, when it last changed, its title, its
+/// size and its reading time.
+fn post_column(id: usize, post: &Post) -> String {
+    let title = if post.title.chars().count() > 12 {
+        format!("{}...", post.title.chars().take(9).collect::<String>())
+    } else {
+        post.title.clone()
+    };
+    let reading = if post.meta.reading_time > 0 { format!("{}m", post.meta.reading_time) } else { String::new() };
+    format!("{:2} {:10} {:12} {:>4} {:>3}", id, post.meta.modified(), title, ls::human_size(post.meta.size), reading)
+}
+
 pub fn render_post_list(state: &SystemState) -> Vec<WasmLine> {
     let mut out = Vec::new();
     out.push(wrap(get_border("top"), "bbs-border"));
@@ -113,34 +156,16 @@ pub fn render_post_list(state: &SystemState) -> Vec<WasmLine> {
     out.push(wrap(get_line(&title, "center"), "bbs-title"));
     out.push(wrap(get_border("mid"), "bbs-border"));
     
-    let filtered_posts: Vec<_> = if state.cwd.starts_with("/categories/") {
-        let cat = &state.cwd[12..];
-        state.posts.iter().filter(|p| p.categories.contains(&cat.to_string())).collect()
-    } else if state.cwd.starts_with("/tags/") {
-        let tag = &state.cwd[6..];
-        state.posts.iter().filter(|p| p.tags.contains(&tag.to_string())).collect()
-    } else {
-        state.posts.iter().collect()
-    };
+    let filtered_posts = area_posts(state);
 
     if filtered_posts.is_empty() {
         out.push(wrap(get_line("No posts found in this area.", "center"), "regular"));
     } else {
-        let half = (filtered_posts.len() + 1) / 2;
+        let half = filtered_posts.len().div_ceil(2);
         for i in 0..half {
-            let p1 = filtered_posts[i];
-            let id1 = i + 1;
-            let title1 = if p1.title.len() > 18 { format!("{}...", &p1.title[0..15]) } else { p1.title.clone() };
-            let col1 = format!("{:2} {:8} {:18}", id1, p1.date, title1);
-
-            let mut row = col1;
-
-            if i + half < filtered_posts.len() {
-                let p2 = filtered_posts[i + half];
-                let id2 = i + half + 1;
-                let title2 = if p2.title.len() > 18 { format!("{}...", &p2.title[0..15]) } else { p2.title.clone() };
-                let col2 = format!("{:2} {:8} {:18}", id2, p2.date, title2);
-                row = format!("{}  │  {}", row, col2);
+            let mut row = post_column(i + 1, filtered_posts[i]);
+            if let Some(p2) = filtered_posts.get(i + half) {
+                row = format!("{}  │  {}", row, post_column(i + half + 1, p2));
             }
 
             out.push(wrap(get_line(&row, "left"), &format!("bbs-posts-row-{}", i)));
@@ -172,35 +197,35 @@ pub fn render_system_stats(state: &crate::state::SystemState) -> Vec<WasmLine> {
 }
 
 pub fn render_user_list() -> Vec<WasmLine> {
-    let mut out = Vec::new();
-    out.push(wrap(get_border("top"), "bbs-border"));
-    out.push(wrap(get_line(" CHANNEL 5: CURRENTLY ONLINE USERS ", "center"), "bbs-title"));
-    out.push(wrap(get_border("mid"), "bbs-border"));
-    out.push(wrap(get_line(" NODE │ USERNAME     │ LOCATION       │ ACTION", "left"), "bbs-header"));
-    out.push(wrap(get_border("sep"), "bbs-border"));
-    out.push(wrap(get_line("  01  │ guest        │ Local          │ Reading Bulletins", "left"), "regular"));
-    out.push(wrap(get_line("  02  │ sysop        │ Remote         │ Maintenance", "left"), "regular"));
-    out.push(wrap(get_line("  03  │ wizard       │ Unknown        │ matrix", "left"), "regular"));
-    out.push(wrap(get_line("  04  │ cyber_pioneer│ Seattle, WA    │ Composing Mail", "left"), "regular"));
-    out.push(wrap(get_border("mid"), "bbs-border"));
-    out.push(wrap(get_line("Press any key to return...", "center"), "bbs-footer"));
-    out.push(wrap(get_border("bot"), "bbs-border"));
-    out
+    vec![
+        wrap(get_border("top"), "bbs-border"),
+        wrap(get_line(" CHANNEL 5: CURRENTLY ONLINE USERS ", "center"), "bbs-title"),
+        wrap(get_border("mid"), "bbs-border"),
+        wrap(get_line(" NODE │ USERNAME     │ LOCATION       │ ACTION", "left"), "bbs-header"),
+        wrap(get_border("sep"), "bbs-border"),
+        wrap(get_line("  01  │ guest        │ Local          │ Reading Bulletins", "left"), "regular"),
+        wrap(get_line("  02  │ sysop        │ Remote         │ Maintenance", "left"), "regular"),
+        wrap(get_line("  03  │ wizard       │ Unknown        │ matrix", "left"), "regular"),
+        wrap(get_line("  04  │ cyber_pioneer│ Seattle, WA    │ Composing Mail", "left"), "regular"),
+        wrap(get_border("mid"), "bbs-border"),
+        wrap(get_line("Press any key to return...", "center"), "bbs-footer"),
+        wrap(get_border("bot"), "bbs-border"),
+    ]
 }
 
 pub fn render_bulletins() -> Vec<WasmLine> {
-    let mut out = Vec::new();
-    out.push(wrap(get_border("top"), "bbs-border"));
-    out.push(wrap(get_line(" CHANNEL 2: SYSTEM BULLETINS ", "center"), "bbs-title"));
-    out.push(wrap(get_border("mid"), "bbs-border"));
-    out.push(wrap(get_line(" 1. 2026-01-01: Welcome to the New Year on tecnoter.io!", "left"), "regular"));
-    out.push(wrap(get_line(" 2. 2026-01-02: System memory upgraded to 128GB.", "left"), "regular"));
-    out.push(wrap(get_line(" 3. 2026-01-03: New ANSI art collection added.", "left"), "regular"));
-    out.push(wrap(get_line(" 4. 2026-01-03: Mail routing issues resolved.", "left"), "regular"));
-    out.push(wrap(get_border("mid"), "bbs-border"));
-    out.push(wrap(get_line("Press any key to return...", "center"), "bbs-footer"));
-    out.push(wrap(get_border("bot"), "bbs-border"));
-    out
+    vec![
+        wrap(get_border("top"), "bbs-border"),
+        wrap(get_line(" CHANNEL 2: SYSTEM BULLETINS ", "center"), "bbs-title"),
+        wrap(get_border("mid"), "bbs-border"),
+        wrap(get_line(" 1. 2026-01-01: Welcome to the New Year on tecnoter.io!", "left"), "regular"),
+        wrap(get_line(" 2. 2026-01-02: System memory upgraded to 128GB.", "left"), "regular"),
+        wrap(get_line(" 3. 2026-01-03: New ANSI art collection added.", "left"), "regular"),
+        wrap(get_line(" 4. 2026-01-03: Mail routing issues resolved.", "left"), "regular"),
+        wrap(get_border("mid"), "bbs-border"),
+        wrap(get_line("Press any key to return...", "center"), "bbs-footer"),
+        wrap(get_border("bot"), "bbs-border"),
+    ]
 }
 
 pub fn render_category_list(state: &SystemState) -> Vec<WasmLine> {
@@ -209,9 +234,7 @@ pub fn render_category_list(state: &SystemState) -> Vec<WasmLine> {
     out.push(wrap(get_line(" CHANNEL 3: MESSAGE AREAS (CATEGORIES) ", "center"), "bbs-title"));
     out.push(wrap(get_border("mid"), "bbs-border"));
     
-    let mut cats: Vec<String> = state.posts.iter().flat_map(|p| p.categories.clone()).collect();
-    cats.sort();
-    cats.dedup();
+    let cats = areas(state);
 
     if cats.is_empty() {
         out.push(wrap(get_line("No categories found.", "center"), "regular"));
@@ -228,18 +251,29 @@ pub fn render_category_list(state: &SystemState) -> Vec<WasmLine> {
     out
 }
 
+/// Redraws the screen the session is on, e.g. after returning from a pause.
+pub fn render_screen(state: &SystemState) -> Vec<WasmLine> {
+    match state.login_state {
+        LoginState::BbsMain => render_main_menu(state),
+        LoginState::BbsPosts => render_post_list(state),
+        LoginState::BbsCategories => render_category_list(state),
+        _ => vec![],
+    }
+}
+
 pub fn render_help() -> Vec<WasmLine> {
-    let mut out = Vec::new();
-    out.push(wrap(get_border("top"), "bbs-border"));
-    out.push(wrap(get_line("--- BBS COMMAND LIST ---", "center"), "bbs-header"));
-    out.push(wrap(get_border("sep"), "bbs-border"));
-    out.push(wrap(get_line("1-99 : Select a post by its ID", "left"), "regular"));
-    out.push(wrap(get_line("N    : Read next post", "left"), "regular"));
-    out.push(wrap(get_line("P    : Read previous post", "left"), "regular"));
-    out.push(wrap(get_line("M    : Refresh/Show the main post menu", "left"), "regular"));
-    out.push(wrap(get_line("Q    : Exit BBS and return to system prompt", "left"), "regular"));
-    out.push(wrap(get_line("H / ? : Show this help message", "left"), "regular"));
-    out.push(wrap(get_line("--- System commands work here too! ---", "center"), "bbs-footer"));
-    out.push(wrap(get_border("bot"), "bbs-border"));
-    out
+    vec![
+        wrap(get_border("top"), "bbs-border"),
+        wrap(get_line("--- BBS COMMAND LIST ---", "center"), "bbs-header"),
+        wrap(get_border("sep"), "bbs-border"),
+        wrap(get_line("1-99 : Select a post by its ID", "left"), "regular"),
+        wrap(get_line("N    : Read next post", "left"), "regular"),
+        wrap(get_line("P    : Read previous post", "left"), "regular"),
+        wrap(get_line("M    : Refresh/Show the main post menu", "left"), "regular"),
+        wrap(get_line("B    : Go back to the previous screen", "left"), "regular"),
+        wrap(get_line("Q    : Exit BBS and return to system prompt", "left"), "regular"),
+        wrap(get_line("H / ? : Show this help message", "left"), "regular"),
+        wrap(get_line("--- System commands work here too! ---", "center"), "bbs-footer"),
+        wrap(get_border("bot"), "bbs-border"),
+    ]
 }
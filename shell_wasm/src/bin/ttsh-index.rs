@@ -0,0 +1,64 @@
+//! `ttsh-index`: builds the full-text search index of a Hugo build. Hugo
+//! publishes the plain text of every page as `search/corpus.json`; this
+//! writes the inverted index the `search` command loads next to it, as
+//! `search/index.json`. Run it after every `hugo`.
+//!
+//!     hugo && ttsh-index --site public
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tecnoter_shell::search::{Corpus, Index};
+
+const USAGE: &str = "usage: ttsh-index [--site DIR]
+
+  --site DIR    Hugo output holding search/corpus.json (default: public)";
+
+/// `Ok(None)` when only the usage was asked for.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<PathBuf>, String> {
+    let mut args = args;
+    let mut site = PathBuf::from("public");
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--site" => site = PathBuf::from(args.next().ok_or("ttsh-index: --site needs a directory")?),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("ttsh-index: unknown option {}\n{}", other, USAGE)),
+        }
+    }
+    Ok(Some(site))
+}
+
+fn run(site: PathBuf) -> Result<(), String> {
+    let corpus_path = site.join("search/corpus.json");
+    let index_path = site.join("search/index.json");
+    let text = std::fs::read_to_string(&corpus_path)
+        .map_err(|e| format!("ttsh-index: {}: {}", corpus_path.display(), e))?;
+    let corpus: Corpus = serde_json::from_str(&text)
+        .map_err(|e| format!("ttsh-index: {}: invalid corpus: {}", corpus_path.display(), e))?;
+
+    let index = Index::build(corpus.documents);
+    std::fs::write(&index_path, index.to_json())
+        .map_err(|e| format!("ttsh-index: {}: {}", index_path.display(), e))?;
+    println!("ttsh-index: {} documents, {} terms in {}", index.docs.len(), index.terms.len(), index_path.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let site = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(site)) => site,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    match run(site) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! `ttsh-sshd`: the tecnoter node over SSH. The user name picks the account,
+//! as at the login prompt: `guest` gets the shell, `bbs` lands in the BBS and
+//! `admin` is asked for the password. Each connection runs on its own thread.
+//!
+//!     ttsh-sshd --site public
+//!     ssh -p 2222 guest@localhost
+//!     ssh -p 2222 guest@localhost ls /posts
+
+use std::io::{self, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tecnoter_shell::host::{self, session::Session, ssh::{self, HostKey}, Connections, Slot};
+use tecnoter_shell::platform::native::NativePlatform;
+
+const USAGE: &str = "usage: ttsh-sshd [--site DIR] [--listen ADDR] [--host-key FILE] [--max-per-ip N] [--idle-timeout SECS] [--verbose]
+
+  --site DIR            Hugo output holding index.json (default: public)
+  --listen ADDR         address to listen on (default: 127.0.0.1:2222)
+  --host-key FILE       ed25519 host key, created if missing (default: ttsh_host_ed25519_key)
+  --max-per-ip N        connections allowed from one address (default: 3)
+  --idle-timeout SECS   hang up after this long without input (default: 600)
+  --verbose             show debug logs on stderr";
+
+struct Options {
+    site: PathBuf,
+    listen: String,
+    host_key: PathBuf,
+    max_per_ip: usize,
+    idle_timeout: Duration,
+    verbose: bool,
+}
+
+/// `Ok(None)` when only the usage was asked for.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args;
+    let mut options = Options {
+        site: PathBuf::from("public"),
+        listen: "127.0.0.1:2222".to_string(),
+        host_key: PathBuf::from("ttsh_host_ed25519_key"),
+        max_per_ip: 3,
+        idle_timeout: Duration::from_secs(600),
+        verbose: false,
+    };
+    let number = |flag: &str, value: Option<String>| -> Result<u64, String> {
+        value.and_then(|v| v.parse().ok()).ok_or(format!("ttsh-sshd: {} needs a number", flag))
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--site" => options.site = PathBuf::from(args.next().ok_or("ttsh-sshd: --site needs a directory")?),
+            "--listen" => options.listen = args.next().ok_or("ttsh-sshd: --listen needs an address")?,
+            "--host-key" => options.host_key = PathBuf::from(args.next().ok_or("ttsh-sshd: --host-key needs a file")?),
+            "--max-per-ip" => options.max_per_ip = number(&arg, args.next())? as usize,
+            "--idle-timeout" => options.idle_timeout = Duration::from_secs(number(&arg, args.next())?.max(1)),
+            "-v" | "--verbose" => options.verbose = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("ttsh-sshd: unknown option {}\n{}", other, USAGE)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn serve(stream: TcpStream, site: &Path, host_key: &HostKey, idle_timeout: Duration, verbose: bool) -> io::Result<()> {
+    let mut platform = NativePlatform::new(Some(site.to_path_buf()), verbose);
+    platform.public_only = true;
+    let platform = Rc::new(platform);
+    // Unlike telnet there is no way to tell the user before the handshake
+    let state = host::load_site(site, &*platform).map_err(io::Error::other)?;
+    let mut session = Session::new(state, platform);
+    ssh::serve(stream, host_key, idle_timeout, &mut session)
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let (host_key, created) = HostKey::load_or_generate(&options.host_key)
+        .map_err(|e| format!("ttsh-sshd: {}: {}", options.host_key.display(), e))?;
+    if created {
+        eprintln!("ttsh-sshd: created a host key in {}", options.host_key.display());
+    }
+    let listener = TcpListener::bind(&options.listen).map_err(|e| format!("ttsh-sshd: {}: {}", options.listen, e))?;
+    eprintln!("ttsh-sshd: serving {} on {}, host key {}", options.site.display(), options.listen, host_key.fingerprint());
+    let connections: Connections = Arc::default();
+    let host_key = Arc::new(host_key);
+    let options = Arc::new(options);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("ttsh-sshd: accept: {}", e);
+                continue;
+            }
+        };
+        let Ok(peer) = stream.peer_addr() else { continue };
+        // Dropping the stream is all we can do before the handshake
+        let Some(slot) = Slot::acquire(&connections, peer.ip(), options.max_per_ip) else { continue };
+
+        let options = options.clone();
+        let host_key = host_key.clone();
+        std::thread::spawn(move || {
+            let _slot = slot;
+            if options.verbose {
+                eprintln!("ttsh-sshd: {} connected", peer);
+            }
+            match serve(stream, &options.site, &host_key, options.idle_timeout, options.verbose) {
+                // Clients that just go away are no error
+                Err(e) if matches!(e.kind(), ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset | ErrorKind::BrokenPipe) => {}
+                Err(e) => eprintln!("ttsh-sshd: {}: {}", peer, e),
+                Ok(()) => {}
+            }
+            if options.verbose {
+                eprintln!("ttsh-sshd: {} disconnected", peer);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! `ttsh-telnetd`: the tecnoter node over telnet. Every connection gets a
+//! session of its own, boot banner and login included, on its own thread.
+//! The site is read again for each connection, so new posts show up without
+//! a restart.
+//!
+//!     ttsh-telnetd --site public
+//!     telnet localhost 2323
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tecnoter_shell::host::{self, session::Session, telnet::{self, Decoder, Event}, Connections, Slot};
+use tecnoter_shell::platform::native::NativePlatform;
+
+const USAGE: &str = "usage: ttsh-telnetd [--site DIR] [--listen ADDR] [--max-per-ip N] [--idle-timeout SECS] [--verbose]
+
+  --site DIR            Hugo output holding index.json (default: public)
+  --listen ADDR         address to listen on (default: 127.0.0.1:2323)
+  --max-per-ip N        connections allowed from one address (default: 3)
+  --idle-timeout SECS   hang up after this long without input, or with output unread (default: 600)
+  --verbose             show debug logs on stderr";
+
+struct Options {
+    site: PathBuf,
+    listen: String,
+    max_per_ip: usize,
+    idle_timeout: Duration,
+    verbose: bool,
+}
+
+/// `Ok(None)` when only the usage was asked for.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args;
+    let mut options = Options {
+        site: PathBuf::from("public"),
+        listen: "127.0.0.1:2323".to_string(),
+        max_per_ip: 3,
+        idle_timeout: Duration::from_secs(600),
+        verbose: false,
+    };
+    let number = |flag: &str, value: Option<String>| -> Result<u64, String> {
+        value.and_then(|v| v.parse().ok()).ok_or(format!("ttsh-telnetd: {} needs a number", flag))
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--site" => options.site = PathBuf::from(args.next().ok_or("ttsh-telnetd: --site needs a directory")?),
+            "--listen" => options.listen = args.next().ok_or("ttsh-telnetd: --listen needs an address")?,
+            "--max-per-ip" => options.max_per_ip = number(&arg, args.next())? as usize,
+            "--idle-timeout" => options.idle_timeout = Duration::from_secs(number(&arg, args.next())?.max(1)),
+            "-v" | "--verbose" => options.verbose = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("ttsh-telnetd: unknown option {}\n{}", other, USAGE)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn serve(mut stream: TcpStream, site: &Path, idle_timeout: Duration, verbose: bool) -> io::Result<()> {
+    stream.set_read_timeout(Some(idle_timeout))?;
+    // A client that stops reading is dropped as well, instead of holding its thread
+    stream.set_write_timeout(Some(idle_timeout))?;
+    let mut platform = NativePlatform::new(Some(site.to_path_buf()), verbose);
+    platform.public_only = true;
+    let platform = Rc::new(platform);
+    let state = match host::load_site(site, &*platform) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("ttsh-telnetd: {}", e);
+            return stream.write_all(b"The node is down for maintenance. Try again later.\r\n");
+        }
+    };
+    let mut session = Session::new(state, platform);
+
+    stream.write_all(&telnet::negotiation())?;
+    stream.write_all(&telnet::escape(&session.start()))?;
+
+    let mut decoder = Decoder::default();
+    let mut buf = [0; 1024];
+    while !session.closed() {
+        let n = match stream.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            // The client went away without saying goodbye
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => return Ok(()),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return stream.write_all(b"\r\nIdle timeout, closing connection.\r\n");
+            }
+            Err(e) => return Err(e),
+        };
+        let (events, reply) = decoder.feed(&buf[..n]);
+        stream.write_all(&reply)?;
+        for event in events {
+            match event {
+                Event::Data(keys) => stream.write_all(&telnet::escape(&session.input(&keys)))?,
+                Event::Resize(width, height) => session.resize(width, height),
+            }
+        }
+    }
+    stream.write_all(b"Connection closed by foreign host.\r\n")
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let listener = TcpListener::bind(&options.listen).map_err(|e| format!("ttsh-telnetd: {}: {}", options.listen, e))?;
+    eprintln!("ttsh-telnetd: serving {} on {}", options.site.display(), options.listen);
+    let connections: Connections = Arc::default();
+    let options = Arc::new(options);
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("ttsh-telnetd: accept: {}", e);
+                continue;
+            }
+        };
+        let Ok(peer) = stream.peer_addr() else { continue };
+        let Some(slot) = Slot::acquire(&connections, peer.ip(), options.max_per_ip) else {
+            let _ = stream.write_all(b"Too many connections from your address.\r\n");
+            continue;
+        };
+
+        let options = options.clone();
+        std::thread::spawn(move || {
+            let _slot = slot;
+            if options.verbose {
+                eprintln!("ttsh-telnetd: {} connected", peer);
+            }
+            if let Err(e) = serve(stream, &options.site, options.idle_timeout, options.verbose) {
+                eprintln!("ttsh-telnetd: {}: {}", peer, e);
+            }
+            if options.verbose {
+                eprintln!("ttsh-telnetd: {} disconnected", peer);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(1)
+        }
+    }
+}
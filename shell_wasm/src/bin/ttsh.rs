@@ -0,0 +1,145 @@
+//! `ttsh`: the tecnoter shell in a native terminal. It reads the catalog and
+//! the per-page `index.json` files from a Hugo build, so writers can preview
+//! the terminal side of new posts without `hugo server` and a browser.
+//!
+//! With `--batch`, command lines are read from stdin without prompts and the
+//! exit status is that of the last one, e.g. `echo 'ls /posts' | ttsh --batch`.
+//! With `--home FILE`, the files under `/home` are read from FILE and saved
+//! back to it on exit.
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use tecnoter_shell::host::{self, ansi};
+use tecnoter_shell::platform::{self, native::NativePlatform};
+use tecnoter_shell::state::{Home, LoginState, SystemState};
+
+const USAGE: &str = "usage: ttsh [--site DIR] [--user NAME] [--home FILE] [--batch] [--verbose]
+
+  --site DIR    Hugo output holding index.json (default: public/ if built, else .)
+  --user NAME   log in as NAME (default: guest)
+  --home FILE   keep the files under /home in FILE between runs
+  --batch       run command lines from stdin without prompts
+  --verbose     show debug logs on stderr";
+
+struct Options {
+    site: PathBuf,
+    user: String,
+    home: Option<PathBuf>,
+    batch: bool,
+    verbose: bool,
+}
+
+/// `Ok(None)` when only the usage was asked for.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args;
+    let mut site = None;
+    let mut user = "guest".to_string();
+    let mut home = None;
+    let mut batch = false;
+    let mut verbose = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--site" => site = Some(PathBuf::from(args.next().ok_or("ttsh: --site needs a directory")?)),
+            "--user" => user = args.next().ok_or("ttsh: --user needs a name")?,
+            "--home" => home = Some(PathBuf::from(args.next().ok_or("ttsh: --home needs a file")?)),
+            "--batch" => batch = true,
+            "-v" | "--verbose" => verbose = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("ttsh: unknown option {}\n{}", other, USAGE)),
+        }
+    }
+
+    // Hugo builds into public/; any directory with an index.json will do
+    let site = site.unwrap_or_else(|| {
+        if Path::new("public/index.json").exists() { PathBuf::from("public") } else { PathBuf::from(".") }
+    });
+    Ok(Some(Options { site, user, home, batch, verbose }))
+}
+
+/// A logged-in session with the site's catalog loaded.
+fn session(options: &Options, platform: &NativePlatform) -> Result<SystemState, String> {
+    let mut state = host::load_site(&options.site, platform).map_err(|e| format!("ttsh: {}", e))?;
+    state.current_user = options.user.clone();
+    state.login_state = LoginState::Prompt;
+    state.booted = true;
+    state.is_authenticated = true;
+    state.debug_mode = options.verbose;
+    if let Some(path) = options.home.as_ref().filter(|path| path.exists()) {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("ttsh: {}: {}", path.display(), e))?;
+        let home: Home = serde_json::from_str(&text).map_err(|e| format!("ttsh: {}: invalid home: {}", path.display(), e))?;
+        state.restore_home(home);
+    }
+    Ok(state)
+}
+
+fn save_home(path: &Path, state: &SystemState) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(&state.home()).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("ttsh: {}: {}", path.display(), e))
+}
+
+fn run(options: Options) -> Result<i32, String> {
+    let platform = Rc::new(NativePlatform::new(Some(options.site.clone()), options.verbose));
+    platform::install(platform.clone());
+    let mut state = session(&options, &platform)?;
+    let interactive = !options.batch;
+
+    if interactive {
+        println!("{}", tecnoter_shell::commands::motd::handle(&state));
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("{}", host::prompt(&state));
+            io::stdout().flush().map_err(|e| e.to_string())?;
+        }
+        let Some(line) = lines.next() else {
+            if interactive {
+                println!();
+            }
+            break;
+        };
+        let line = line.map_err(|e| format!("ttsh: {}", e))?;
+
+        let reply = host::execute(&mut state, &line);
+        for output in &reply.lines {
+            match output.line_type.as_str() {
+                "clearScreen" if interactive => print!("{}", ansi::CLEAR_SCREEN),
+                "clearScreen" => {}
+                _ => println!("{}", output.text),
+            }
+        }
+        if reply.closed {
+            break;
+        }
+    }
+    if let Some(path) = &options.home {
+        save_home(path, &state)?;
+    }
+    Ok(state.last_status)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(status) => ExitCode::from(status.clamp(0, 255) as u8),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! The site catalog Hugo publishes as `/index.json`
+//! (`themes/tecnoterio/layouts/index.json`): posts, pages of every other
+//! section, social links, fortunes and node information.
+
+use serde::Deserialize;
+use crate::state::{Page, Post, Social, SystemInfo, SystemState};
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    #[serde(default)]
+    pub posts: Vec<Post>,
+    #[serde(default)]
+    pub pages: Vec<Page>,
+    // Site params that are not set come out as `null`
+    #[serde(default)]
+    pub socials: Option<Vec<Social>>,
+    #[serde(default)]
+    pub fortunes: Option<Vec<String>>,
+    #[serde(default)]
+    pub system_info: Option<SystemInfo>,
+}
+
+impl Catalog {
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| format!("invalid catalog: {}", e))
+    }
+
+    /// Loads the catalog into a session, as the page does once `/index.json` arrives.
+    pub fn apply(self, state: &mut SystemState) {
+        state.posts = self.posts;
+        state.pages = self.pages;
+        state.socials = self.socials.unwrap_or_default();
+        state.fortunes = self.fortunes.unwrap_or_default();
+        if let Some(info) = self.system_info {
+            state.system_info = info;
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Ansi;
+
+impl Command for Ansi {
+    fn name(&self) -> &'static str { "ansi" }
+    fn synopsis(&self) -> &'static str { "ansi" }
+    fn summary(&self) -> &'static str { "Display random ANSI art" }
+    fn description(&self) -> &'static str {
+        "Draws a random piece of ANSI art."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult {
+            lines: vec![WasmLine { text: "ansi".to_string(), line_type: "internalInstruction".to_string() }],
+            next_state: state,
+            handled: true,
+            pending: None,
+            status: 0,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::bbs;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+use crate::WasmLine;
+
+pub struct Bbs;
+
+impl Command for Bbs {
+    fn name(&self) -> &'static str { "bbs" }
+    fn synopsis(&self) -> &'static str { "bbs [r|c|s|u]" }
+    fn summary(&self) -> &'static str { "Launch the BBS interface" }
+    fn description(&self) -> &'static str {
+        "Enters the main tecnoter.io interactive node. r opens the posts, c the categories, s the system stats and u the user list."
+    }
+
+    fn complete(&self, _state: &SystemState, args: &[String], partial: &str) -> Vec<String> {
+        if !args.is_empty() {
+            return Vec::new();
+        }
+        ["r", "c", "s", "u"].iter().filter(|s| s.starts_with(partial)).map(|s| s.to_string()).collect()
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let sub_cmd = args.first().map(|a| a.to_lowercase()).unwrap_or_default();
+        // Screens opened from the shell sit on top of the main menu
+        if !state.login_state.is_bbs() {
+            if let Err(e) = state.go_to(LoginState::BbsMain) {
+                return ProcessResult::failure(e.to_string(), state);
+            }
+        }
+        let lines = match sub_cmd.as_str() {
+            "r" | "l" => {
+                if let Err(e) = state.open(LoginState::BbsPosts) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                bbs::render_post_list(&state)
+            },
+            "c" => {
+                if let Err(e) = state.open(LoginState::BbsCategories) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                bbs::render_category_list(&state)
+            },
+            "s" => {
+                state.pause();
+                bbs::render_system_stats(&state)
+            },
+            "u" => {
+                state.pause();
+                bbs::render_user_list()
+            },
+            _ => {
+                state.nav_stack.clear();
+                if let Err(e) = state.go_to(LoginState::BbsMain) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                bbs::render_main_menu(&state)
+            }
+        };
+        ProcessResult { lines, next_state: state, handled: true, pending: None, status: 0 }
+    }
+}
+
+/// Title banner the BBS shows while it fetches a post for reading.
+pub struct ReadInternal;
+
+impl Command for ReadInternal {
+    fn name(&self) -> &'static str { "_read_internal" }
+    fn synopsis(&self) -> &'static str { "_read_internal slug" }
+    fn summary(&self) -> &'static str { "Show the banner of a post opened from the BBS" }
+    fn description(&self) -> &'static str {
+        "Internal command used by the BBS screens."
+    }
+
+    fn allowed(&self, _login_state: LoginState) -> bool {
+        true
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let slug = args.first().map(String::as_str).unwrap_or("");
+        let mut lines = vec![];
+        if let Some(post) = state.posts.iter().find(|p| p.slug == slug) {
+            lines.push(WasmLine { text: format!("Reading: {}", post.title.to_uppercase()), line_type: "bbs-title".to_string() });
+            lines.push(WasmLine { text: "-".repeat(40), line_type: "bbs-border".to_string() });
+        }
+        ProcessResult { lines, next_state: state, handled: true, pending: None, status: 0 }
+    }
+}
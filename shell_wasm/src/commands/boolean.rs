@@ -0,0 +1,33 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct True;
+
+impl Command for True {
+    fn name(&self) -> &'static str { "true" }
+    fn synopsis(&self) -> &'static str { "true" }
+    fn summary(&self) -> &'static str { "Succeed, for && and || chains" }
+    fn description(&self) -> &'static str {
+        "Exits with status 0, e.g. true && echo always."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(String::new(), state)
+    }
+}
+
+pub struct False;
+
+impl Command for False {
+    fn name(&self) -> &'static str { "false" }
+    fn synopsis(&self) -> &'static str { "false" }
+    fn summary(&self) -> &'static str { "Fail, for && and || chains" }
+    fn description(&self) -> &'static str {
+        "Exits with status 1, e.g. false || echo fallback."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::failure(String::new(), state)
+    }
+}
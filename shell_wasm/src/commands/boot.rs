@@ -0,0 +1,49 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+use crate::WasmLine;
+
+/// Prints the boot banner; the host runs it before showing the login prompt.
+pub struct Boot;
+
+impl Command for Boot {
+    fn name(&self) -> &'static str { "_boot" }
+    fn synopsis(&self) -> &'static str { "_boot" }
+    fn summary(&self) -> &'static str { "Print the boot banner" }
+    fn description(&self) -> &'static str {
+        "Internal command run by the terminal when it powers on."
+    }
+
+    fn allowed(&self, _login_state: LoginState) -> bool {
+        true
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        if let Err(e) = state.go_to(LoginState::Boot) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
+        ProcessResult {
+            lines: vec![
+                WasmLine { text: "TECNOTER.IO(TM) CORE SYSTEM".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "LOADING SYSTEM MODULES...".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "NET_STACK: TCP/IP v6 READY".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "SSH_DAEMON: LISTENING ON PORT 22".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "HTTP_DAEMON: READY".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "CONNECTING TO TECNOTER NETWORK...".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "CARRIER 14400 / ARQ / V.32bis".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "CONNECT 14400/REL - CD 1".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "PROTOCOL: LAP-M".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "COMPRESSION: V.42bis".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "*** WELCOME TO THE TECNOTER.IO NODE ***".to_string(), line_type: "regular".to_string() },
+                WasmLine { text: "".to_string(), line_type: "regular".to_string() },
+            ],
+            next_state: state,
+            handled: true,
+            pending: None,
+            status: 0,
+        }
+    }
+}
@@ -1,14 +1,11 @@
-use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{Request, RequestInit, RequestMode, Response};
 use serde::Deserialize;
+use crate::commands::{Command, Deferred, Stdio, Stdout};
 use crate::state::SystemState;
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "terminalUI"])]
-    fn print(text: &str, line_type: &str);
-}
+use crate::completer;
+use crate::fs::{self, Source};
+use crate::shell::ProcessResult;
+use crate::http::{self, HttpError};
+use crate::platform;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,89 +14,166 @@ struct HugoContent {
     pub content: String,
 }
 
-pub fn handle(state: &SystemState, args: Vec<&str>) -> String {
+pub struct Cat;
+
+impl Command for Cat {
+    fn name(&self) -> &'static str { "cat" }
+    fn synopsis(&self) -> &'static str { "cat [file...]" }
+    fn summary(&self) -> &'static str { "Show file content" }
+    fn description(&self) -> &'static str {
+        "Prints pages, posts or user files, one after the other. Pages and posts can be named by slug from any directory, e.g. cat bio. Without a file, copies its input through, as in ... | cat."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        let matches = completer::complete_path(state, partial);
+        if !matches.is_empty() || partial.contains('/') {
+            return matches;
+        }
+        // Slugs work from anywhere
+        fs::items(state)
+            .filter(|item| item.slug.starts_with(partial))
+            .map(|item| item.slug.to_string())
+            .collect()
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_stdout(handle(&state, args, &stdio), state)
+    }
+}
+
+pub fn handle(state: &SystemState, args: &[String], stdio: &Stdio) -> Result<Stdout, String> {
     if args.is_empty() {
-        return "Usage: cat [filename]".to_string();
+        // Plain `cat` copies its input through, as in `... | cat`
+        return match &stdio.stdin {
+            Some(text) if stdio.piped => Ok(Stdout::Text(text.clone())),
+            // The terminal ends the last line itself
+            Some(text) => Ok(Stdout::Text(text.strip_suffix('\n').unwrap_or(text).to_string())),
+            None => Err("Usage: cat [filename]".to_string()),
+        };
     }
 
-    let input_path = args[0];
-    let resolved_path = crate::fs::resolve_path(&state.cwd, input_path);
+    let mut outputs = Vec::new();
+    for arg in args {
+        outputs.push(open(state, arg, stdio.piped)?);
+    }
+    if outputs.len() == 1 {
+        return Ok(outputs.remove(0));
+    }
+
+    // Piped files are concatenated as they are; on the terminal each ends its own line
+    let separator = if stdio.piped { "" } else { "\n" };
+    if outputs.iter().all(|output| matches!(output, Stdout::Text(_))) {
+        let texts: Vec<String> = outputs.into_iter()
+            .filter_map(|output| match output {
+                Stdout::Text(text) => Some(text),
+                Stdout::Deferred { .. } => None,
+            })
+            .collect();
+        return Ok(Stdout::Text(texts.join(separator)));
+    }
+
+    // Pages are fetched one after the other so they print in order
+    let mut notices = Vec::new();
+    let mut parts: Vec<Deferred> = Vec::new();
+    for output in outputs {
+        match output {
+            Stdout::Text(text) => parts.push(Box::pin(async move { Ok(text) })),
+            Stdout::Deferred { notice, body } => {
+                notices.push(notice);
+                parts.push(body);
+            }
+        }
+    }
+    let body = async move {
+        let mut texts = Vec::new();
+        for part in parts {
+            texts.push(part.await?);
+        }
+        Ok(texts.join(separator))
+    };
+    Ok(Stdout::Deferred { notice: notices.join("\n"), body: Box::pin(body) })
+}
+
+/// Where the page or post at `url` publishes its content.
+pub fn content_url(url: &str) -> String {
+    if url.ends_with('/') {
+        format!("{}index.json", url)
+    } else {
+        format!("{}/index.json", url)
+    }
+}
+
+/// The body of a page from its `index.json`, or the text itself when it is
+/// not one.
+pub fn content(text: &str) -> String {
+    serde_json::from_str::<HugoContent>(text).map(|hugo| hugo.content).unwrap_or_else(|_| text.to_string())
+}
+
+/// A single operand: a user file, or a page or post fetched by path or slug.
+fn open(state: &SystemState, input_path: &str, piped: bool) -> Result<Stdout, String> {
+    let resolved_path = fs::resolve_path(&state.cwd, input_path);
+
+    // Files the user wrote with `>` are local and need no fetch
+    if let Some(text) = fs::read_file(state, &resolved_path) {
+        let text = if piped { text } else { text.strip_suffix('\n').unwrap_or(text) };
+        return Ok(Stdout::Text(text.to_string()));
+    }
     
-    // Determine the search key (the name of the file without leading slashes or dir prefixes)
-    // We try to be permissive: "bio", "/pages/bio", "pages/bio" should all work.
-    let search_slug = resolved_path.split('/').last().unwrap_or(input_path);
-
-    // Search both pages and posts for a matching slug
-    let target_item = state.pages.iter().find(|p| p.slug == search_slug || p.slug == input_path)
-        .map(|p| (p.title.clone(), p.url.clone()))
-        .or_else(|| {
-            state.posts.iter().find(|p| p.slug == search_slug || p.slug == input_path)
-                .map(|p| (p.title.clone(), p.url.clone()))
-        });
+    let target_item = match fs::tree(state).lookup(&resolved_path) {
+        Some(inode) if inode.is_dir() => return Err(format!("cat: {}: Is a directory", input_path)),
+        Some(inode) => fs::item(state, &inode.source),
+        None => {
+            // Not here: "bio", "/pages/bio" and "pages/bio" all name the page by its slug
+            #[allow(clippy::double_ended_iterator_last)]
+            let search_slug = resolved_path.split('/').last().unwrap_or(input_path);
+            fs::items(state)
+                .filter(|item| item.slug == search_slug || item.slug == input_path)
+                // Pages win over posts with the same slug
+                .min_by_key(|item| matches!(item.source, Source::Post(_)))
+        }
+    }.map(|item| (item.title.to_string(), item.url.to_string()));
 
     if let Some((title, item_url)) = target_item {
-        let url = if item_url.ends_with('/') {
-            format!("{}index.json", item_url)
-        } else {
-            format!("{}/index.json", item_url)
-        };
+        let url = content_url(&item_url);
         
         let display_title: String = title.clone();
         let debug_mode = state.debug_mode;
+        let platform = platform::current();
 
-        spawn_local(async move {
-            let opts = RequestInit::new();
-            opts.set_method("GET");
-            opts.set_mode(RequestMode::Cors);
-
-            let window = web_sys::window().unwrap();
+        let body = async move {
             if debug_mode {
-                web_sys::console::log_1(&format!("cat: fetching {}", url).into());
+                platform.log(&format!("cat: fetching {}", url));
             }
-            
-            let request = match Request::new_with_str_and_init(&url, &opts) {
-                Ok(r) => r,
-                Err(_) => {
-                    print("cat: invalid request sequence", "regular");
-                    return;
-                }
-            };
-            
-            let resp_value = match wasm_bindgen_futures::JsFuture::from(window.fetch_with_request(&request)).await {
-                Ok(v) => v,
-                Err(_) => {
-                    print(&format!("cat: network error fetching {}", url), "regular");
-                    return;
-                }
-            };
 
-            let resp: Response = resp_value.dyn_into().unwrap();
-            if !resp.ok() {
-                web_sys::console::error_1(&format!("cat: error {} loading {}", resp.status(), url).into());
-                print(&format!("cat: error {} loading {}", resp.status(), url), "regular");
-                return;
-            }
+            let text = http::client().get(&url).await.map_err(|e| match e {
+                HttpError::InvalidUrl => "cat: invalid request sequence".to_string(),
+                HttpError::Network(_) => format!("cat: network error fetching {}", url),
+                HttpError::Timeout => format!("cat: timed out fetching {}", url),
+                HttpError::Status(status) => {
+                    platform.error(&format!("cat: error {} loading {}", status, url));
+                    format!("cat: error {} loading {}", status, url)
+                }
+            })?;
 
-            let text_vec = wasm_bindgen_futures::JsFuture::from(resp.text().unwrap()).await;
-            let text = text_vec.unwrap_or_default().as_string().unwrap_or_default();
-            
             match serde_json::from_str::<HugoContent>(&text) {
-                Ok(hugo) => {
-                    print(&format!("\n# {}\n", hugo.title), "regular");
-                    print(&hugo.content, "regular");
-                },
+                // Piped output is the bare body so that `cat bio | wc -w` counts words, not headers
+                Ok(hugo) if piped => Ok(hugo.content),
+                Ok(hugo) => Ok(format!("\n# {}\n\n{}", hugo.title, hugo.content)),
                 Err(e) => {
                     if debug_mode {
-                        web_sys::console::error_1(&format!("cat: JSON parse error: {}", e).into());
+                        platform.error(&format!("cat: JSON parse error: {}", e));
                     }
                     // Fallback: print raw body if it looks like plain text
-                    print(&text, "regular");
+                    Ok(text)
                 }
             }
-        });
+        };
 
-        return format!("Reading {}...", display_title);
+        return Ok(Stdout::Deferred {
+            notice: format!("Reading {}...", display_title),
+            body: Box::pin(body),
+        });
     }
 
-    format!("cat: {}: No such file or directory", input_path)
+    Err(format!("cat: {}: No such file or directory", input_path))
 }
@@ -1,21 +1,63 @@
 use crate::state::SystemState;
+use crate::expand;
 use crate::fs;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::completer;
 
-pub struct CdResult {
-    pub message: String,
-    pub new_path: String,
-}
+pub struct Cd;
 
-pub fn handle(state: &SystemState, args: Vec<&str>) -> CdResult {
-    if args.is_empty() {
-        return CdResult { message: String::new(), new_path: "/".to_string() };
+impl Command for Cd {
+    fn name(&self) -> &'static str { "cd" }
+    fn synopsis(&self) -> &'static str { "cd [dir | -]" }
+    fn summary(&self) -> &'static str { "Change directory" }
+    fn description(&self) -> &'static str {
+        "Changes the working directory, e.g. cd ../tags or cd ~/notes. Without a directory, goes to $HOME. cd - goes back to the previous one, $OLDPWD, and prints it."
     }
 
-    let target = fs::resolve_path(&state.cwd, args[0]);
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
 
-    if fs::get_directory_contents(&target).is_some() {
-        CdResult { message: String::new(), new_path: target }
-    } else {
-        CdResult { message: format!("cd: no such directory: {}", args[0]), new_path: state.cwd.clone() }
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&state, args) {
+            Ok(path) => {
+                // `cd -` shows where it went, since it was not typed
+                let output = if args.first().is_some_and(|arg| arg == "-") { path.clone() } else { String::new() };
+                change_dir(&mut state, path);
+                ProcessResult::simple(output, state)
+            }
+            Err(e) => ProcessResult::failure(e, state),
+        }
     }
 }
+
+/// Returns the new working directory.
+pub fn handle(state: &SystemState, args: &[String]) -> Result<String, String> {
+    let (target, operand) = match args {
+        [] => (fs::home_dir(state), "~"),
+        [dir] if dir == "-" => {
+            let old = expand::lookup(state, "OLDPWD").filter(|dir| !dir.is_empty());
+            (old.ok_or("cd: OLDPWD not set")?, "-")
+        }
+        [dir] => (fs::resolve_path(&state.cwd, dir), dir.as_str()),
+        _ => return Err("cd: too many arguments".to_string()),
+    };
+    check_dir("cd", state, &target, operand)?;
+    Ok(target)
+}
+
+/// Whether `command` can change to the absolute `path`, written as `operand`.
+pub fn check_dir(command: &str, state: &SystemState, path: &str, operand: &str) -> Result<(), String> {
+    match fs::tree(state).lookup(path) {
+        Some(inode) if inode.is_dir() => Ok(()),
+        Some(_) => Err(format!("{}: not a directory: {}", command, operand)),
+        None => Err(format!("{}: no such directory: {}", command, operand)),
+    }
+}
+
+/// Makes `dir` the working directory, keeping the previous one as `$OLDPWD`.
+pub fn change_dir(state: &mut SystemState, dir: String) {
+    let old = std::mem::replace(&mut state.cwd, dir);
+    let _ = expand::assign(state, "OLDPWD", Some(old), true);
+}
@@ -0,0 +1,25 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str { "clear" }
+    fn synopsis(&self) -> &'static str { "clear" }
+    fn summary(&self) -> &'static str { "Clear terminal screen" }
+    fn description(&self) -> &'static str {
+        "Clears the terminal screen."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult {
+            lines: vec![WasmLine { text: "".to_string(), line_type: "clearScreen".to_string() }],
+            next_state: state,
+            handled: true,
+            pending: None,
+            status: 0,
+        }
+    }
+}
@@ -1,3 +1,24 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Cowsay;
+
+impl Command for Cowsay {
+    fn name(&self) -> &'static str { "cowsay" }
+    fn synopsis(&self) -> &'static str { "cowsay" }
+    fn summary(&self) -> &'static str { "Digital mascot ASCII art" }
+    fn description(&self) -> &'static str {
+        "Draws the node mascot."
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        // Screenfuls in the BBS wait for a key before the menu returns
+        state.pause();
+        ProcessResult::simple(handle(), state)
+    }
+}
+
 pub fn handle() -> String {
     "<pre style='color: #5ff;'>  ^__^\n  (oo)\\_______\n  (__)\\       )\\/\\\n      ||----w |\n      ||     ||</pre>".to_string()
 }
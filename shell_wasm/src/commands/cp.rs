@@ -0,0 +1,132 @@
+use crate::commands::{self, cat, mv, Command, Stdio};
+use crate::completer;
+use crate::events;
+use crate::fs::{self, Inode, Tree};
+use crate::http;
+use crate::shell::{self, Completion, Outcome, ProcessResult, STDERR};
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Cp;
+
+impl Command for Cp {
+    fn name(&self) -> &'static str { "cp" }
+    fn synopsis(&self) -> &'static str { "cp [-r] source... dest" }
+    fn summary(&self) -> &'static str { "Copy files" }
+    fn description(&self) -> &'static str {
+        "Copies source to dest, or every source into dest when it is a directory. Copies go to /tmp or your home directory; posts and pages are fetched and saved as text. -r copies directories and everything in them. e.g. cp -r /tags/rust ~/rust"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let (fetches, errors) = match handle(&mut state, args) {
+            Ok(copied) => copied,
+            Err(e) => return ProcessResult::failure(e, state),
+        };
+        if fetches.is_empty() {
+            return if errors.is_empty() {
+                ProcessResult::simple(String::new(), state)
+            } else {
+                ProcessResult::failure(errors.join("\n"), state)
+            };
+        }
+
+        let total = fetches.len();
+        let failed = !errors.is_empty();
+        let mut lines: Vec<WasmLine> = errors.into_iter().map(|text| WasmLine { text, line_type: STDERR.to_string() }).collect();
+        lines.push(WasmLine {
+            text: format!("Copying {} file{}...", total, if total == 1 { "" } else { "s" }),
+            line_type: "regular".to_string(),
+        });
+        let pending = async move {
+            let urls: Vec<String> = fetches.iter().map(|fetch| fetch.url.clone()).collect();
+            let bodies = http::client()
+                .get_all(&urls, |done| events::progress(format!("cp: copied {} of {} files", done, total)))
+                .await;
+            let mut completions = Vec::new();
+            let mut status = if failed { 1 } else { 0 };
+            for (fetch, body) in fetches.into_iter().zip(bodies) {
+                match body {
+                    Ok(body) => completions.push(Completion::Write { path: fetch.path, text: shell::as_file_text(&cat::content(&body)) }),
+                    Err(e) => {
+                        completions.push(Completion::Stderr(format!("cp: {}: {} fetching {}", fetch.operand, e, fetch.url)));
+                        status = 1;
+                    }
+                }
+            }
+            Outcome { completions, status }
+        };
+        ProcessResult { lines, pending: Some(Box::pin(pending)), ..ProcessResult::simple(String::new(), state) }
+    }
+}
+
+/// A post or page being copied, written to `path` once fetched.
+pub struct Fetch {
+    operand: String,
+    url: String,
+    path: String,
+}
+
+/// Copies what is local right away. Returns the posts and pages still to
+/// fetch, and the errors for what could not be copied.
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<(Vec<Fetch>, Vec<String>), String> {
+    let (flags, operands) = commands::split_flags("cp", args, "rR")?;
+    let recursive = flags.contains(&'r') || flags.contains(&'R');
+    let (sources, dests) = mv::targets("cp", state, &operands)?;
+
+    let mut tree = fs::tree_for_writing(state);
+    let mut fetches = Vec::new();
+    let mut errors = Vec::new();
+    for (operand, dest) in sources.into_iter().zip(dests) {
+        let from = fs::resolve_path(&state.cwd, operand);
+        let Some(inode) = tree.lookup(&from) else {
+            errors.push(format!("cp: cannot stat '{}': No such file or directory", operand));
+            continue;
+        };
+        let inside = from == "/" || dest.strip_prefix(&from).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+        if inode.is_dir() && !recursive {
+            errors.push(format!("cp: -r not specified; omitting directory '{}'", operand));
+            continue;
+        } else if inside && inode.is_dir() {
+            errors.push(format!("cp: cannot copy a directory, '{}', into itself, '{}'", operand, dest));
+            continue;
+        } else if inside {
+            errors.push(format!("cp: '{}' and '{}' are the same file", operand, dest));
+            continue;
+        }
+
+        let copies: Vec<(String, Inode)> = tree.walk(&from, usize::MAX).into_iter()
+            .map(|(path, _, inode)| (path, inode.clone()))
+            .collect();
+        for (path, inode) in &copies {
+            let to = fs::resolve_path(&dest, path[from.len()..].trim_start_matches('/'));
+            if let Err(reason) = copy(state, &mut tree, path, &to, inode, &mut fetches) {
+                let kind = if inode.is_dir() { "directory" } else { "regular file" };
+                errors.push(format!("cp: cannot create {} '{}': {}", kind, to, reason));
+            }
+        }
+    }
+    Ok((fetches, errors))
+}
+
+/// Copies the inode at `from` to `to`, or adds it to `fetches` when it is a
+/// post or page.
+fn copy(state: &mut SystemState, tree: &mut Tree, from: &str, to: &str, inode: &Inode, fetches: &mut Vec<Fetch>) -> Result<(), &'static str> {
+    if inode.is_dir() {
+        return match fs::make_dir(state, tree, to) {
+            Err("File exists") if tree.is_dir(to) => Ok(()),
+            made => made,
+        };
+    }
+    fs::check_writable(state, tree, to)?;
+    if let Some(text) = fs::read_file(state, from) {
+        let text = text.to_string();
+        fs::save_file(state, tree, to, text)?;
+    } else if let Some(item) = fs::item(state, &inode.source) {
+        fetches.push(Fetch { operand: from.to_string(), url: cat::content_url(item.url), path: to.to_string() });
+    }
+    Ok(())
+}
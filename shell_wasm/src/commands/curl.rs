@@ -1,76 +1,64 @@
-use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{Request, RequestInit, RequestMode, Response};
+use crate::commands::{Command, Stdio, Stdout};
 use crate::state::SystemState;
+use crate::shell::ProcessResult;
+use crate::http::{self, HttpError};
+use crate::platform;
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "terminalUI"])]
-    fn print(text: &str, line_type: &str);
+pub struct Curl;
+
+impl Command for Curl {
+    fn name(&self) -> &'static str { "curl" }
+    fn synopsis(&self) -> &'static str { "curl [url]" }
+    fn summary(&self) -> &'static str { "Download content from URL" }
+    fn description(&self) -> &'static str {
+        "Fetches url in the background and prints the response. Output to the terminal is clipped to 2000 characters; pipes and redirections get all of it."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_stdout(handle(&state, args, &stdio), state)
+    }
 }
 
-pub fn handle(_state: &SystemState, args: Vec<&str>) -> String {
+pub fn handle(_state: &SystemState, args: &[String], stdio: &Stdio) -> Result<Stdout, String> {
     if args.is_empty() {
-        return "Usage: curl [url]".to_string();
+        return Err("Usage: curl [url]".to_string());
     }
 
-    let url = args[0].to_string();
+    let url = args[0].clone();
     let debug_mode = _state.debug_mode;
+    // Only clip what goes to the screen; pipes get the whole document
+    let piped = stdio.piped;
+    let platform = platform::current();
     
-    // We launch an async task in the background
-    spawn_local(async move {
+    // The fetch runs in the background so the terminal isn't blocked
+    let body = async move {
         if debug_mode {
-            web_sys::console::log_1(&format!("curl: fetching {}", url).into());
+            platform.log(&format!("curl: fetching {}", url));
         }
-        print(&format!("fetching {}...", url), "regular");
-        
-        let opts = RequestInit::new();
-        opts.set_method("GET");
-        opts.set_mode(RequestMode::Cors);
 
-        let request = match Request::new_with_str_and_init(&url, &opts) {
-            Ok(r) => r,
-            Err(_) => {
-                print("curl: invalid URL", "regular");
-                return;
+        let text = http::client().get(&url).await.map_err(|e| match e {
+            HttpError::InvalidUrl => "curl: invalid URL".to_string(),
+            HttpError::Network(_) => "curl: network error (CORS?)".to_string(),
+            HttpError::Timeout => "curl: operation timed out".to_string(),
+            HttpError::Status(status) => {
+                platform.error(&format!("curl: error {} fetching {}", status, url));
+                format!("curl: error {}", status)
             }
-        };
+        })?;
 
-        let window = web_sys::window().unwrap();
-        let resp_value = match wasm_bindgen_futures::JsFuture::from(window.fetch_with_request(&request)).await {
-            Ok(v) => v,
-            Err(_) => {
-                print("curl: network error (CORS?)", "regular");
-                return;
+        // Clip long output
+        if !piped && text.len() > 2000 {
+            let mut end = 2000;
+            while !text.is_char_boundary(end) {
+                end -= 1;
             }
-        };
-
-        let resp: Response = resp_value.dyn_into().unwrap();
-        
-        if !resp.ok() {
-            web_sys::console::error_1(&format!("curl: error {} fetching {}", resp.status(), url).into());
-            print(&format!("curl: error {}", resp.status()), "regular");
-            return;
+            return Ok(format!("{}... [TRUNCATED]", &text[..end]));
         }
+        Ok(text)
+    };
 
-        let text = match wasm_bindgen_futures::JsFuture::from(resp.text().unwrap()).await {
-            Ok(t) => t.as_string().unwrap_or_default(),
-            Err(_) => {
-                print("curl: failed to parse response", "regular");
-                return;
-            }
-        };
-
-        // Clip long output
-        let display_text = if text.len() > 2000 {
-            format!("{}... [TRUNCATED]", &text[0..2000])
-        } else {
-            text
-        };
-
-        print(&display_text, "regular");
-    });
-
-    // Return immediately to the shell so the terminal isn't blocked
-    "Establishing uplink...".to_string()
+    Ok(Stdout::Deferred {
+        notice: format!("Establishing uplink...\nfetching {}...", args[0]),
+        body: Box::pin(body),
+    })
 }
@@ -1,7 +1,23 @@
 use crate::state::SystemState;
-use js_sys::Date;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::platform;
+
+pub struct Date;
+
+impl Command for Date {
+    fn name(&self) -> &'static str { "date" }
+    fn synopsis(&self) -> &'static str { "date" }
+    fn summary(&self) -> &'static str { "Show system date" }
+    fn description(&self) -> &'static str {
+        "Displays the current node system time."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(_state: &SystemState) -> String {
-    let d = Date::new_0();
-    d.to_string().as_string().unwrap_or_else(|| "Error getting date".to_string())
+    platform::current().now()
 }
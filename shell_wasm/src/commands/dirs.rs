@@ -0,0 +1,164 @@
+use crate::commands::cd::{change_dir, check_dir};
+use crate::commands::{Command, Stdio};
+use crate::completer;
+use crate::fs;
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+/// The directory stack, the working directory first.
+fn stack(state: &SystemState) -> Vec<String> {
+    std::iter::once(state.cwd.clone()).chain(state.dir_stack.iter().cloned()).collect()
+}
+
+/// `dir` with the home directory written as `~`.
+fn shown(state: &SystemState, dir: &str) -> String {
+    let home = fs::home_dir(state);
+    match dir.strip_prefix(&home) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+        _ => dir.to_string(),
+    }
+}
+
+/// The stack on one line, as `pushd` and `popd` print it after a change.
+fn line(state: &SystemState) -> String {
+    stack(state).iter().map(|dir| shown(state, dir)).collect::<Vec<_>>().join(" ")
+}
+
+/// The position in the stack `+N` (from the left, 0 being the working
+/// directory) or `-N` (from the right) names; `None` when `spec` is not one.
+fn position(command: &str, spec: &str, len: usize) -> Option<Result<usize, String>> {
+    let (from_right, n) = match spec.as_bytes().first()? {
+        b'+' => (false, &spec[1..]),
+        b'-' => (true, &spec[1..]),
+        _ => return None,
+    };
+    let n: usize = n.parse().ok()?;
+    if n >= len {
+        return Some(Err(format!("{}: {}: directory stack index out of range", command, spec)));
+    }
+    Some(Ok(if from_right { len - 1 - n } else { n }))
+}
+
+/// Replaces the stack with `stack`, changing to its first directory.
+fn set_stack(command: &str, state: &mut SystemState, mut stack: Vec<String>) -> Result<(), String> {
+    let top = stack.remove(0);
+    if top != state.cwd {
+        check_dir(command, state, &top, &shown(state, &top))?;
+        change_dir(state, top);
+    }
+    state.dir_stack = stack;
+    Ok(())
+}
+
+pub struct Pushd;
+
+impl Command for Pushd {
+    fn name(&self) -> &'static str { "pushd" }
+    fn synopsis(&self) -> &'static str { "pushd [dir | +N | -N]" }
+    fn summary(&self) -> &'static str { "Save the directory and change to another" }
+    fn description(&self) -> &'static str {
+        "Puts dir on top of the directory stack and changes to it, keeping the working directory below it. Without dir, swaps the top two directories; +N and -N rotate the stack so that the Nth directory, counted from the left or the right of dirs, is on top. Prints the stack."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle_pushd(&mut state, args) {
+            Ok(()) => ProcessResult::simple(line(&state), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+fn handle_pushd(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let mut stack = stack(state);
+    match args {
+        [] => {
+            if stack.len() < 2 {
+                return Err("pushd: no other directory".to_string());
+            }
+            stack.swap(0, 1);
+        }
+        [arg] => match position("pushd", arg, stack.len()) {
+            Some(n) => stack.rotate_left(n?),
+            None => {
+                let path = fs::resolve_path(&state.cwd, arg);
+                check_dir("pushd", state, &path, arg)?;
+                stack.insert(0, path);
+            }
+        },
+        _ => return Err("pushd: too many arguments".to_string()),
+    }
+    set_stack("pushd", state, stack)
+}
+
+pub struct Popd;
+
+impl Command for Popd {
+    fn name(&self) -> &'static str { "popd" }
+    fn synopsis(&self) -> &'static str { "popd [+N | -N]" }
+    fn summary(&self) -> &'static str { "Return to the directory saved by pushd" }
+    fn description(&self) -> &'static str {
+        "Removes the top of the directory stack and changes to the directory below it. +N and -N remove the Nth directory instead, counted from the left or the right of dirs. Prints the stack."
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle_popd(&mut state, args) {
+            Ok(()) => ProcessResult::simple(line(&state), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+fn handle_popd(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let mut stack = stack(state);
+    if stack.len() < 2 {
+        return Err("popd: directory stack empty".to_string());
+    }
+    let n = match args {
+        [] => 0,
+        [spec] => position("popd", spec, stack.len()).ok_or_else(|| format!("popd: {}: invalid argument", spec))??,
+        _ => return Err("popd: too many arguments".to_string()),
+    };
+    stack.remove(n);
+    set_stack("popd", state, stack)
+}
+
+pub struct Dirs;
+
+impl Command for Dirs {
+    fn name(&self) -> &'static str { "dirs" }
+    fn synopsis(&self) -> &'static str { "dirs [-clpv]" }
+    fn summary(&self) -> &'static str { "Show the directory stack" }
+    fn description(&self) -> &'static str {
+        "Prints the directory stack, the working directory first, with the home directory as ~. -l prints full paths, -p one directory per line, -v one per line with its position, and -c clears the stack."
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let mut flags = String::new();
+        for arg in args {
+            match arg.strip_prefix('-') {
+                Some(f) if !f.is_empty() && f.chars().all(|c| "clpv".contains(c)) => flags.push_str(f),
+                _ => return ProcessResult::failure(format!("dirs: {}: invalid option\nUsage: {}", arg, self.synopsis()), state),
+            }
+        }
+        if flags.contains('c') {
+            state.dir_stack.clear();
+            return ProcessResult::simple(String::new(), state);
+        }
+
+        let dirs: Vec<String> = stack(&state).iter()
+            .map(|dir| if flags.contains('l') { dir.clone() } else { shown(&state, dir) })
+            .collect();
+        let output = if flags.contains('v') {
+            dirs.iter().enumerate().map(|(i, dir)| format!("{:2}  {}", i, dir)).collect::<Vec<_>>().join("\n")
+        } else if flags.contains('p') {
+            dirs.join("\n")
+        } else {
+            dirs.join(" ")
+        };
+        ProcessResult::simple(output, state)
+    }
+}
@@ -0,0 +1,22 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Echo;
+
+impl Command for Echo {
+    fn name(&self) -> &'static str { "echo" }
+    fn synopsis(&self) -> &'static str { "echo [string...]" }
+    fn summary(&self) -> &'static str { "Print arguments, e.g. echo $USER" }
+    fn description(&self) -> &'static str {
+        "Prints its arguments separated by spaces, after variable expansion."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(args), state)
+    }
+}
+
+pub fn handle(args: &[String]) -> String {
+    args.join(" ")
+}
@@ -0,0 +1,27 @@
+use crate::expand;
+use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Env;
+
+impl Command for Env {
+    fn name(&self) -> &'static str { "env" }
+    fn synopsis(&self) -> &'static str { "env" }
+    fn summary(&self) -> &'static str { "List exported variables" }
+    fn description(&self) -> &'static str {
+        "Lists exported variables, including USER, HOME, PWD, HOSTNAME and SHELL."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
+
+pub fn handle(state: &SystemState) -> String {
+    expand::all(state).into_iter()
+        .filter(|(_, _, exported)| *exported)
+        .map(|(name, value, _)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
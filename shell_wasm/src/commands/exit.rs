@@ -0,0 +1,26 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Exit;
+
+impl Command for Exit {
+    fn name(&self) -> &'static str { "exit" }
+    fn aliases(&self) -> &'static [&'static str] { &["logout"] }
+    fn synopsis(&self) -> &'static str { "exit" }
+    fn summary(&self) -> &'static str { "Terminate session" }
+    fn description(&self) -> &'static str {
+        "Closes the connection to the node."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult {
+            lines: vec![WasmLine { text: "exit".to_string(), line_type: "internalInstruction".to_string() }],
+            next_state: state,
+            handled: true,
+            pending: None,
+            status: 0,
+        }
+    }
+}
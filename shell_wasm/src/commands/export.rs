@@ -0,0 +1,46 @@
+use crate::expand;
+use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Export;
+
+impl Command for Export {
+    fn name(&self) -> &'static str { "export" }
+    fn synopsis(&self) -> &'static str { "export [NAME[=value]...]" }
+    fn summary(&self) -> &'static str { "Set and export variables (NAME=value)" }
+    fn description(&self) -> &'static str {
+        "Marks each NAME for export, assigning value if given. Without arguments, lists exported variables."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        expand::complete_name(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let output = handle(&mut state, args);
+        ProcessResult::from_result(output, state)
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<String, String> {
+    if args.is_empty() {
+        return Ok(expand::all(state).into_iter()
+            .filter(|(_, _, exported)| *exported)
+            .map(|(name, value, _)| format!("export {}=\"{}\"", name, value))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let mut errors = Vec::new();
+    for arg in args {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        if let Err(e) = expand::assign(state, name, value, true) {
+            errors.push(format!("export: {}", e));
+        }
+    }
+    if errors.is_empty() { Ok(String::new()) } else { Err(errors.join("\n")) }
+}
@@ -0,0 +1,91 @@
+use crate::commands::{Command, Stdio};
+use crate::completer;
+use crate::fs::{self, Inode, Source, Tree};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct File;
+
+impl Command for File {
+    fn name(&self) -> &'static str { "file" }
+    fn synopsis(&self) -> &'static str { "file path..." }
+    fn summary(&self) -> &'static str { "Tell what kind of file a path is" }
+    fn description(&self) -> &'static str {
+        "Prints what each path holds: a post or a page (and whether it is a draft or the index of a page bundle), a section, a page bundle, the tags or categories taxonomy, one of their terms, a plain directory, or a file of your own. e.g. file /posts/* /tags"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&state, args) {
+            Ok((output, errors)) => {
+                let status = if errors.is_empty() { 0 } else { 1 };
+                ProcessResult::with_errors(output, errors, status, state)
+            }
+            Err(usage) => ProcessResult::failure(usage, state),
+        }
+    }
+}
+
+/// The output for the paths that exist, and the errors for those that do
+/// not; fails only without a path.
+pub fn handle(state: &SystemState, args: &[String]) -> Result<(String, Vec<String>), String> {
+    if args.is_empty() {
+        return Err("Usage: file path...".to_string());
+    }
+    let tree = fs::tree(state);
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+    for operand in args {
+        let path = fs::resolve_path(&state.cwd, operand);
+        match tree.lookup(&path) {
+            Some(inode) => output.push(format!("{}: {}", operand, kind(state, &tree, &path, inode))),
+            None => errors.push(format!("file: cannot open '{}': No such file or directory", operand)),
+        }
+    }
+    Ok((output.join("\n"), errors))
+}
+
+/// What the inode at the absolute `path` is, e.g. `post`, `tag directory`
+/// or `page bundle`.
+pub fn kind(state: &SystemState, tree: &Tree, path: &str, inode: &Inode) -> String {
+    if let Some(item) = fs::item(state, &inode.source) {
+        let mut kind = if matches!(item.source, Source::Post(_)) { "post" } else { "page" }.to_string();
+        if item.meta.draft {
+            kind.push_str(", draft");
+        }
+        if path.ends_with("/index") && fs::taxonomy_depth(path).is_none() {
+            kind.push_str(&format!(", index of the bundle {}", fs::parent_dir(path)));
+        }
+        return kind;
+    }
+    if let Source::User(_) = inode.source {
+        let text = fs::read_file(state, path).unwrap_or_default();
+        return if text.is_empty() {
+            "empty".to_string()
+        } else if text.is_ascii() {
+            "ASCII text".to_string()
+        } else {
+            "UTF-8 text".to_string()
+        };
+    }
+
+    let root = path.split('/').nth(1).unwrap_or("");
+    match fs::taxonomy_depth(path) {
+        Some(0) => return "taxonomy directory".to_string(),
+        Some(_) if root == "tags" => return "tag directory".to_string(),
+        Some(_) => return "category directory".to_string(),
+        None => {}
+    }
+    let entries = tree.entries(path).unwrap_or_default();
+    let holds_items = entries.iter().any(|(_, inode)| fs::item(state, &inode.source).is_some());
+    if entries.iter().any(|(name, inode)| *name == "index" && fs::item(state, &inode.source).is_some()) {
+        "page bundle".to_string()
+    } else if holds_items && fs::parent_dir(path) == "/" {
+        "section directory".to_string()
+    } else {
+        "directory".to_string()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::commands::{Command, Stdio};
+use crate::completer;
+use crate::fs::{self, Inode, Kind};
+use crate::glob;
+use crate::shell::{ProcessResult, STDERR};
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Find;
+
+impl Command for Find {
+    fn name(&self) -> &'static str { "find" }
+    fn synopsis(&self) -> &'static str {
+        "find [path...] [-name PATTERN] [-type f|d] [-newer DATE] [-tag TAG] [-category CAT] [-maxdepth N]"
+    }
+    fn summary(&self) -> &'static str { "Search for files" }
+    fn description(&self) -> &'static str {
+        "Lists what is under each path, the working directory by default, that passes every test: -name matches the name against a wildcard pattern, -type keeps files (f) or directories (d), -newer keeps what changed on or after DATE (2026, 2026-03 or 2026-03-01), -tag and -category keep the posts and pages filed under them, and -maxdepth stops N levels down. A search from outside /tags and /categories does not list the posts linked in them again. e.g. find / -tag rust -newer 2026"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let found = match handle(&state, args) {
+            Ok(found) => found,
+            Err(e) => return ProcessResult::failure(e, state),
+        };
+        let status = if found.iter().any(Result::is_err) { 1 } else { 0 };
+        let lines = found.into_iter()
+            .map(|line| match line {
+                Ok(text) => WasmLine { text, line_type: "regular".to_string() },
+                Err(text) => WasmLine { text, line_type: STDERR.to_string() },
+            })
+            .collect();
+        ProcessResult { lines, status, ..ProcessResult::simple(String::new(), state) }
+    }
+}
+
+/// The tests `find` knows, each taking one argument.
+const PREDICATES: [&str; 6] = ["-name", "-type", "-newer", "-tag", "-category", "-maxdepth"];
+
+/// The tests an entry must pass to be listed.
+#[derive(Default)]
+struct Filter {
+    name: Option<String>,
+    kind: Option<Kind>,
+    /// The first day of `-newer`, e.g. `2026-01-01`.
+    newer: Option<String>,
+    tag: Option<String>,
+    category: Option<String>,
+    max_depth: Option<usize>,
+}
+
+/// `2026`, `2026-03` or `2026-03-01` as the first day it covers.
+fn parse_date(date: &str) -> Option<String> {
+    let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    match date.split('-').collect::<Vec<_>>()[..] {
+        [year] if digits(year, 4) => Some(format!("{}-01-01", year)),
+        [year, month] if digits(year, 4) && digits(month, 2) => Some(format!("{}-{}-01", year, month)),
+        [year, month, day] if digits(year, 4) && digits(month, 2) && digits(day, 2) => Some(date.to_string()),
+        _ => None,
+    }
+}
+
+impl Filter {
+    /// Splits `args` into the paths and the tests that follow them.
+    fn parse(args: &[String]) -> Result<(Vec<&str>, Filter), String> {
+        let mut paths = Vec::new();
+        let mut filter = Filter::default();
+        let mut i = 0;
+        while i < args.len() {
+            let arg = args[i].as_str();
+            if !arg.starts_with('-') {
+                if i > paths.len() {
+                    return Err(format!("find: paths must precede expression: {}", arg));
+                }
+                paths.push(arg);
+                i += 1;
+                continue;
+            }
+            if !PREDICATES.contains(&arg) {
+                return Err(format!("find: unknown predicate `{}'", arg));
+            }
+            let value = args.get(i + 1).ok_or_else(|| format!("find: missing argument to `{}'", arg))?;
+            match arg {
+                "-name" => filter.name = Some(value.clone()),
+                "-type" => filter.kind = Some(match value.as_str() {
+                    "f" => Kind::File,
+                    "d" => Kind::Directory,
+                    _ => return Err(format!("find: Unknown argument to -type: {}", value)),
+                }),
+                "-newer" => filter.newer = Some(parse_date(value).ok_or_else(|| format!("find: invalid date `{}'", value))?),
+                "-tag" => filter.tag = Some(value.clone()),
+                "-category" => filter.category = Some(value.clone()),
+                "-maxdepth" => filter.max_depth = Some(value.parse().map_err(|_| format!("find: invalid -maxdepth argument '{}'", value))?),
+                _ => unreachable!("predicates are checked before their argument"),
+            }
+            i += 2;
+        }
+        Ok((paths, filter))
+    }
+
+    fn accepts(&self, state: &SystemState, name: &str, inode: &Inode) -> bool {
+        let item = fs::item(state, &inode.source);
+        let filed_under = |terms: Option<&[String]>, wanted: &str| {
+            terms.is_some_and(|terms| terms.iter().any(|term| term.eq_ignore_ascii_case(wanted)))
+        };
+        self.name.as_ref().is_none_or(|pattern| glob::matches(pattern, name))
+            && self.kind.is_none_or(|kind| inode.kind == kind)
+            && self.newer.as_ref().is_none_or(|date| inode.mtime >= *date)
+            && self.tag.as_ref().is_none_or(|tag| filed_under(item.as_ref().map(|item| item.tags), tag))
+            && self.category.as_ref().is_none_or(|category| filed_under(item.as_ref().map(|item| item.categories), category))
+    }
+}
+
+/// The paths found, printed the way their starting point was written, with
+/// the error for a missing starting point where its paths would have been.
+pub fn handle(state: &SystemState, args: &[String]) -> Result<Vec<Result<String, String>>, String> {
+    let (mut paths, filter) = Filter::parse(args)?;
+    if paths.is_empty() {
+        paths.push(&state.cwd);
+    }
+
+    let tree = fs::tree(state);
+    let mut found = Vec::new();
+    for operand in paths {
+        let start = fs::resolve_path(&state.cwd, operand);
+        if tree.lookup(&start).is_none() {
+            found.push(Err(format!("find: '{}': No such file or directory", operand)));
+            continue;
+        }
+        for (path, _, inode) in tree.walk(&start, filter.max_depth.unwrap_or(usize::MAX)) {
+            if fs::linked_elsewhere(&start, &path) {
+                continue;
+            }
+            let name = path.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("/");
+            if !filter.accepts(state, name, inode) {
+                continue;
+            }
+            let rest = path[start.len()..].trim_start_matches('/');
+            found.push(Ok(match rest {
+                "" => operand.to_string(),
+                rest if operand.ends_with('/') => format!("{}{}", operand, rest),
+                rest => format!("{}/{}", operand, rest),
+            }));
+        }
+    }
+    Ok(found)
+}
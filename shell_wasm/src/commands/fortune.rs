@@ -1,12 +1,31 @@
 use crate::state::SystemState;
-use js_sys::Math;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::platform;
+
+pub struct Fortune;
+
+impl Command for Fortune {
+    fn name(&self) -> &'static str { "fortune" }
+    fn synopsis(&self) -> &'static str { "fortune" }
+    fn summary(&self) -> &'static str { "Random node wisdom" }
+    fn description(&self) -> &'static str {
+        "Prints a random fortune from the node collection."
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        // Screenfuls in the BBS wait for a key before the menu returns
+        state.pause();
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(state: &SystemState) -> String {
     if state.fortunes.is_empty() {
         return "Uplink silent (no fortunes loaded).".to_string();
     }
 
-    let idx = (Math::random() * state.fortunes.len() as f64).floor() as usize;
+    let idx = (platform::current().random() * state.fortunes.len() as f64).floor() as usize;
     let fortune = state.fortunes.get(idx).cloned().unwrap_or_else(|| "Uplink silent.".to_string());
     
     format!("\nNODE WISDOM: {}", fortune)
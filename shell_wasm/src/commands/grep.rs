@@ -0,0 +1,253 @@
+use std::future::Future;
+use std::pin::Pin;
+use regex_lite::{Regex, RegexBuilder};
+use crate::commands::{cat, Command, lines, Stdio};
+use crate::completer;
+use crate::events;
+use crate::fs;
+use crate::http;
+use crate::shell::{Completion, Outcome, ProcessResult};
+use crate::state::SystemState;
+
+/// Colours for a match, a file name and a line number, as GNU grep uses.
+const MATCH: &str = "\x1b[1;31m";
+const NAME: &str = "\x1b[35m";
+const NUMBER: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+pub struct Grep;
+
+impl Command for Grep {
+    fn name(&self) -> &'static str { "grep" }
+    fn synopsis(&self) -> &'static str { "grep [-icvnlrFE] PATTERN [path...]" }
+    fn summary(&self) -> &'static str { "Search files or piped lines for a pattern" }
+    fn description(&self) -> &'static str {
+        "Prints the lines matching PATTERN, a regular expression, from the given files or from its input, e.g. grep -i -n -r rust /posts or ls /posts | grep ^node. Posts and pages are searched in their full text. -r searches directories, -F takes PATTERN as a plain string, -i ignores case, -v keeps the lines that do not match, -c counts them, -n numbers them and -l lists only the files that match. Exits with 1 when nothing matched and 2 when a file could not be searched."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        match handle(&state, args, &stdio) {
+            Ok(Search::Done(found)) => {
+                let status = found.status();
+                ProcessResult::with_errors(found.output, found.errors, status, state)
+            }
+            Ok(Search::Deferred { notice, body }) => {
+                let pending = async move {
+                    let found = body.await;
+                    let status = found.status();
+                    let mut completions: Vec<Completion> = found.errors.into_iter().map(Completion::Stderr).collect();
+                    completions.push(Completion::Stdout(found.output));
+                    Outcome { completions, status }
+                };
+                ProcessResult { pending: Some(Box::pin(pending)), ..ProcessResult::simple(notice, state) }
+            }
+            Err(e) => ProcessResult { status: 2, ..ProcessResult::failure(e, state) },
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Options {
+    ignore_case: bool,
+    invert: bool,
+    count: bool,
+    line_numbers: bool,
+    files_only: bool,
+    recursive: bool,
+    fixed: bool,
+    /// Whether to colour the output for the terminal.
+    color: bool,
+}
+
+/// What a search printed, whether anything matched, and the files it could not read.
+struct Found {
+    output: String,
+    matched: bool,
+    errors: Vec<String>,
+}
+
+impl Found {
+    /// 0 when something matched, 1 when nothing did and 2 when a file could not be searched.
+    fn status(&self) -> i32 {
+        if !self.errors.is_empty() { 2 } else if self.matched { 0 } else { 1 }
+    }
+}
+
+/// A search done at once, or waiting for the files it fetches.
+enum Search {
+    Done(Found),
+    Deferred { notice: String, body: Pin<Box<dyn Future<Output = Found>>> },
+}
+
+/// A file to search: its name as printed, and its text or the URL to fetch it from.
+enum File {
+    Local(String, String),
+    Remote(String, String),
+}
+
+/// Fails only for a bad command line.
+fn handle(state: &SystemState, args: &[String], stdio: &Stdio) -> Result<Search, String> {
+    let mut options = Options { color: !stdio.piped, ..Options::default() };
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.strip_prefix('-') {
+            Some(flags) if !flags.is_empty() && operands.is_empty() => {
+                for flag in flags.chars() {
+                    match flag {
+                        'i' => options.ignore_case = true,
+                        'v' => options.invert = true,
+                        'c' => options.count = true,
+                        'n' => options.line_numbers = true,
+                        'l' => options.files_only = true,
+                        'r' | 'R' => options.recursive = true,
+                        'F' => options.fixed = true,
+                        'E' => options.fixed = false,
+                        _ => return Err(format!("grep: -{}: invalid option\nUsage: grep [-icvnlrFE] PATTERN [path...]", flag)),
+                    }
+                }
+            }
+            _ => operands.push(arg.as_str()),
+        }
+    }
+
+    let Some((pattern, paths)) = operands.split_first() else {
+        return Err("Usage: grep [-icvnlrFE] PATTERN [path...]".to_string());
+    };
+    let pattern = if options.fixed { regex_lite::escape(pattern) } else { pattern.to_string() };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(|e| format!("grep: invalid pattern: {}", e))?;
+
+    if paths.is_empty() {
+        let input = stdio.stdin.as_deref().ok_or("grep: no input (try: grep -r PATTERN /posts)")?;
+        return Ok(Search::Done(search(&regex, options, &[(None, input.to_string())], Vec::new())));
+    }
+
+    let (files, mut errors) = collect(state, options, paths);
+    let named = options.recursive || paths.len() > 1;
+    let urls: Vec<String> = files.iter()
+        .filter_map(|file| match file {
+            File::Remote(_, url) => Some(url.clone()),
+            File::Local(..) => None,
+        })
+        .collect();
+
+    if urls.is_empty() {
+        let texts = files.into_iter()
+            .map(|file| match file {
+                File::Local(name, text) | File::Remote(name, text) => (named.then_some(name), text),
+            })
+            .collect::<Vec<_>>();
+        return Ok(Search::Done(search(&regex, options, &texts, errors)));
+    }
+
+    let total = urls.len();
+    let body = async move {
+        let mut bodies = http::client()
+            .get_all(&urls, |done| events::progress(format!("grep: searched {} of {} files", done, total)))
+            .await
+            .into_iter();
+        let mut texts = Vec::new();
+        for file in files {
+            match file {
+                File::Local(name, text) => texts.push((named.then_some(name), text)),
+                File::Remote(name, url) => match bodies.next() {
+                    Some(Ok(body)) => texts.push((named.then_some(name), cat::content(&body))),
+                    Some(Err(e)) => errors.push(format!("grep: {}: {} fetching {}", name, e, url)),
+                    None => {}
+                },
+            }
+        }
+        search(&regex, options, &texts, errors)
+    };
+    Ok(Search::Deferred {
+        notice: format!("Searching {} file{}...", total, if total == 1 { "" } else { "s" }),
+        body: Box::pin(body),
+    })
+}
+
+/// The files `paths` name, with directories searched through under `-r`,
+/// and the errors for those that cannot be searched.
+fn collect(state: &SystemState, options: Options, paths: &[&str]) -> (Vec<File>, Vec<String>) {
+    let tree = fs::tree(state);
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for operand in paths {
+        let start = fs::resolve_path(&state.cwd, operand);
+        match tree.lookup(&start) {
+            None => errors.push(format!("grep: {}: No such file or directory", operand)),
+            Some(inode) if inode.is_dir() && !options.recursive => errors.push(format!("grep: {}: Is a directory", operand)),
+            Some(_) => {
+                for (path, _, inode) in tree.walk(&start, usize::MAX) {
+                    // A post is searched once, not again under each of its tags
+                    if inode.is_dir() || fs::linked_elsewhere(&start, &path) {
+                        continue;
+                    }
+                    let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    if let Some(text) = fs::read_file(state, &path) {
+                        files.push(File::Local(name, text.to_string()));
+                    } else if let Some(item) = fs::item(state, &inode.source) {
+                        files.push(File::Remote(name, cat::content_url(item.url)));
+                    }
+                }
+            }
+        }
+    }
+    (files, errors)
+}
+
+/// Searches each text, prefixed with its name when it has one. `errors` are
+/// for the files that could not be read.
+fn search(regex: &Regex, options: Options, texts: &[(Option<String>, String)], errors: Vec<String>) -> Found {
+    let paint = |color: &str, text: &str| {
+        if options.color { format!("{}{}{}", color, text, RESET) } else { text.to_string() }
+    };
+    let mut output = Vec::new();
+    let mut matched = false;
+    for (name, text) in texts {
+        let hits: Vec<(usize, &str)> = lines(text).into_iter()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line) != options.invert)
+            .collect();
+        matched |= !hits.is_empty();
+
+        if options.files_only {
+            if !hits.is_empty() {
+                output.push(paint(NAME, name.as_deref().unwrap_or("(standard input)")));
+            }
+            continue;
+        }
+        let prefix = name.as_deref().map(|name| format!("{}:", paint(NAME, name))).unwrap_or_default();
+        if options.count {
+            output.push(format!("{}{}", prefix, hits.len()));
+            continue;
+        }
+        for (i, line) in hits {
+            let number = if options.line_numbers { format!("{}:", paint(NUMBER, &(i + 1).to_string())) } else { String::new() };
+            let line = if options.color && !options.invert { highlight(regex, line) } else { line.to_string() };
+            output.push(format!("{}{}{}", prefix, number, line));
+        }
+    }
+
+    Found { output: output.join("\n"), matched, errors }
+}
+
+/// `line` with every match in bold red.
+fn highlight(regex: &Regex, line: &str) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for found in regex.find_iter(line).filter(|found| !found.is_empty()) {
+        out.push_str(&line[last..found.start()]);
+        out.push_str(MATCH);
+        out.push_str(found.as_str());
+        out.push_str(RESET);
+        last = found.end();
+    }
+    out.push_str(&line[last..]);
+    out
+}
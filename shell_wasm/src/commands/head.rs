@@ -0,0 +1,47 @@
+use crate::commands::{Command, lines, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+/// Parses `-n N` or `-N`, shared with `tail`. Returns `None` on a bad count.
+pub fn parse_count(args: &[String]) -> Option<usize> {
+    let mut count = 10;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-n" {
+            count = iter.next()?.parse().ok()?;
+        } else if let Some(n) = arg.strip_prefix("-n") {
+            count = n.parse().ok()?;
+        } else if let Some(n) = arg.strip_prefix('-') {
+            count = n.parse().ok()?;
+        }
+    }
+    Some(count)
+}
+
+pub struct Head;
+
+impl Command for Head {
+    fn name(&self) -> &'static str { "head" }
+    fn synopsis(&self) -> &'static str { "... | head [-n N]" }
+    fn summary(&self) -> &'static str { "First lines of piped input" }
+    fn description(&self) -> &'static str {
+        "Prints the first N lines (10 by default) of its input. -N is accepted as a shorthand."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_result(handle(args, stdio.stdin.as_deref()), state)
+    }
+}
+
+pub fn handle(args: &[String], stdin: Option<&str>) -> Result<String, String> {
+    let count = match parse_count(args) {
+        Some(n) => n,
+        None => return Err("Usage: head [-n N]".to_string()),
+    };
+    let input = match stdin {
+        Some(text) => text,
+        None => return Err("head: no input (try: ls /posts | head -3)".to_string()),
+    };
+
+    Ok(lines(input).into_iter().take(count).collect::<Vec<_>>().join("\n"))
+}
@@ -1,24 +1,29 @@
-pub fn handle() -> String {
-    let help_text = vec![
-        "Available commands (type 'man [cmd]' for deep info):",
-        "  help       - Show available commands",
-        "  ls [-l]    - List directory contents",
-        "  cd [path]  - Change directory",
-        "  cat [file] - Show file content",
-        "  whoami     - Display system user info",
-        "  bbs        - Launch the BBS interface",
-        "  stats      - Display system statistics",
-        "  uptime     - System availability timer",
-        "  fortune    - Random node wisdom",
-        "  cowsay     - Digital mascot ASCII art",
-        "  weather    - Simulated weather report",
-        "  top        - Display system processes",
-        "  who        - List online users",
-        "  social     - Social media connections",
-        "  curl [url] - Download content from URL",
-        "  date       - Show system date",
-        "  clear      - Clear terminal screen",
-        "  exit       - Terminate session",
-    ];
+use crate::commands::{self, Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str { "help" }
+    fn synopsis(&self) -> &'static str { "help" }
+    fn summary(&self) -> &'static str { "Show available commands" }
+    fn description(&self) -> &'static str {
+        "Displays a list of all commands recognized by the tecnoter.io shell."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
+
+pub fn handle(state: &SystemState) -> String {
+    let available: Vec<&dyn Command> = commands::visible(state.login_state).collect();
+    let width = available.iter().map(|c| c.name().len()).max().unwrap_or(0);
+
+    let mut help_text = vec!["Available commands (type 'man [cmd]' for deep info):".to_string()];
+    for command in available {
+        help_text.push(format!("  {:<width$} - {}", command.name(), command.summary(), width = width));
+    }
     help_text.join("\n")
 }
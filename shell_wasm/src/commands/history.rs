@@ -0,0 +1,38 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct History;
+
+impl Command for History {
+    fn name(&self) -> &'static str { "history" }
+    fn synopsis(&self) -> &'static str { "history [N] | history -c" }
+    fn summary(&self) -> &'static str { "Show previously run commands" }
+    fn description(&self) -> &'static str {
+        "Lists the command history, numbered, or only its last N entries. -c clears it. !! runs the previous command again, !n entry n, !-n the nth last, !prefix the latest starting with prefix, and ^old^new the previous command with old replaced by new. Ctrl-R searches the history."
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let output = handle(&mut state, args);
+        ProcessResult::from_result(output, state)
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<String, String> {
+    let count = match args.first().map(String::as_str) {
+        None => state.history.len(),
+        Some("-c") => {
+            state.history.clear();
+            return Ok(String::new());
+        }
+        Some(n) => n.parse::<usize>().map_err(|_| format!("history: {}: numeric argument required", n))?,
+    };
+
+    let skip = state.history.len().saturating_sub(count);
+    Ok(state.history.iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, line)| format!("{:>5}  {}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
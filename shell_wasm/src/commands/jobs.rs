@@ -0,0 +1,138 @@
+use crate::commands::{Command, Stdio};
+use crate::jobs::{self, JobState};
+use crate::shell::{Outcome, ProcessResult};
+use crate::state::{LoginState, SystemState};
+
+pub struct Jobs;
+
+impl Command for Jobs {
+    fn name(&self) -> &'static str { "jobs" }
+    fn synopsis(&self) -> &'static str { "jobs" }
+    fn summary(&self) -> &'static str { "List background jobs" }
+    fn description(&self) -> &'static str {
+        "Lists the jobs started with &, and any still running in the foreground, with their state. + marks the current job, the one fg, wait and kill act on by default, and - the one before it. Jobs that ended are listed once."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(jobs::list().join("\n"), state)
+    }
+}
+
+/// The job `spec` names, or the error for `command`.
+fn find(command: &str, spec: Option<&String>) -> Result<u32, String> {
+    jobs::parse_spec(spec.map(String::as_str)).ok_or_else(|| match spec {
+        Some(spec) => format!("{}: {}: no such job", command, spec),
+        None => format!("{}: current: no such job", command),
+    })
+}
+
+pub struct Fg;
+
+impl Command for Fg {
+    fn name(&self) -> &'static str { "fg" }
+    fn synopsis(&self) -> &'static str { "fg [%job]" }
+    fn summary(&self) -> &'static str { "Bring a job to the foreground" }
+    fn description(&self) -> &'static str {
+        "Waits for the job, the current one by default, as if it had been started without &. Ctrl-C then stops it."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let id = match find("fg", args.first()) {
+            Ok(id) => id,
+            Err(e) => return ProcessResult::failure(e, state),
+        };
+        match jobs::state(id) {
+            Some(JobState::Running) => ProcessResult::simple(jobs::resume(id).unwrap_or_default(), state),
+            // Too late: report how it ended instead
+            Some(JobState::Done(status) | JobState::Terminated(status)) => {
+                ProcessResult { status, ..ProcessResult::simple(jobs::report(id).unwrap_or_default(), state) }
+            }
+            None => ProcessResult::failure(format!("fg: %{}: no such job", id), state),
+        }
+    }
+}
+
+pub struct Wait;
+
+impl Command for Wait {
+    fn name(&self) -> &'static str { "wait" }
+    fn synopsis(&self) -> &'static str { "wait [%job...]" }
+    fn summary(&self) -> &'static str { "Wait for background jobs" }
+    fn description(&self) -> &'static str {
+        "Waits until the given jobs, or all background jobs, have ended. The exit status is that of the last one. Ctrl-C stops waiting, not the jobs."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let ids = if args.is_empty() {
+            jobs::background()
+        } else {
+            match args.iter().map(|spec| find("wait", Some(spec))).collect::<Result<Vec<_>, _>>() {
+                Ok(ids) => ids,
+                Err(e) => return ProcessResult { status: 127, ..ProcessResult::failure(e, state) },
+            }
+        };
+        if ids.is_empty() {
+            return ProcessResult::simple(String::new(), state);
+        }
+        ProcessResult {
+            pending: Some(Box::pin(async move {
+                let mut status = 0;
+                for id in ids {
+                    status = jobs::wait(id).await;
+                }
+                Outcome { completions: vec![], status }
+            })),
+            ..ProcessResult::simple(String::new(), state)
+        }
+    }
+}
+
+pub struct Kill;
+
+impl Command for Kill {
+    fn name(&self) -> &'static str { "kill" }
+    fn synopsis(&self) -> &'static str { "kill %job..." }
+    fn summary(&self) -> &'static str { "Stop jobs" }
+    fn description(&self) -> &'static str {
+        "Stops each job, cancelling the requests it has in flight, and reports it as Terminated."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        if args.is_empty() {
+            return ProcessResult::failure("Usage: kill %job...".to_string(), state);
+        }
+        let mut lines = Vec::new();
+        let mut errors = Vec::new();
+        for spec in args {
+            match find("kill", Some(spec)).ok().and_then(|id| jobs::kill(id, jobs::TERMINATED)) {
+                Some(line) => lines.push(line),
+                None => errors.push(format!("kill: {}: no such job", spec)),
+            }
+        }
+        let status = if errors.is_empty() { 0 } else { 1 };
+        ProcessResult::with_errors(lines.join("\n"), errors, status, state)
+    }
+}
+
+/// Stops the foreground job when the user presses Ctrl-C.
+pub struct Interrupt;
+
+impl Command for Interrupt {
+    fn name(&self) -> &'static str { "_interrupt" }
+    fn synopsis(&self) -> &'static str { "_interrupt" }
+    fn summary(&self) -> &'static str { "Stop the foreground job" }
+    fn description(&self) -> &'static str {
+        "Internal command run by the terminal on Ctrl-C while a command is still running."
+    }
+
+    fn allowed(&self, _login_state: LoginState) -> bool {
+        true
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        match jobs::interrupt() {
+            Some(line) => ProcessResult { status: jobs::INTERRUPTED, ..ProcessResult::simple(line, state) },
+            None => ProcessResult::simple(String::new(), state),
+        }
+    }
+}
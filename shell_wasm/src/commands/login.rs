@@ -0,0 +1,77 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+use crate::WasmLine;
+
+/// Switches to the login prompt once the boot banner has been shown.
+pub struct StartLogin;
+
+impl Command for StartLogin {
+    fn name(&self) -> &'static str { "_start_login" }
+    fn synopsis(&self) -> &'static str { "_start_login" }
+    fn summary(&self) -> &'static str { "Show the login prompt" }
+    fn description(&self) -> &'static str {
+        "Internal command run by the terminal after booting."
+    }
+
+    fn allowed(&self, login_state: LoginState) -> bool {
+        matches!(login_state, LoginState::Boot | LoginState::Login)
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        if let Err(e) = state.go_to(LoginState::Login) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
+        ProcessResult::simple(String::new(), state)
+    }
+}
+
+/// Checks the name typed at the login prompt.
+pub struct Login;
+
+impl Command for Login {
+    fn name(&self) -> &'static str { "_login" }
+    fn synopsis(&self) -> &'static str { "_login username" }
+    fn summary(&self) -> &'static str { "Log in as username" }
+    fn description(&self) -> &'static str {
+        "Internal command run by the terminal with the name typed at the login prompt. guest and bbs need no password; admin asks for one."
+    }
+
+    // The page sets AUTHENTICATING while it waits for the answer
+    fn allowed(&self, login_state: LoginState) -> bool {
+        matches!(login_state, LoginState::Login | LoginState::Authenticating)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let username = args.first().map(|a| a.to_lowercase()).unwrap_or_default();
+        if username == "guest" || username == "bbs" || username == "admin" {
+            state.current_user = username.clone();
+            if username == "admin" {
+                 if let Err(e) = state.go_to(LoginState::Password) {
+                     return ProcessResult::failure(e.to_string(), state);
+                 }
+            } else if username == "bbs" {
+                 state.is_authenticated = true;
+                 if let Err(e) = state.go_to(LoginState::BbsMain) {
+                     return ProcessResult::failure(e.to_string(), state);
+                 }
+            } else {
+                 state.is_authenticated = true;
+                 if let Err(e) = state.go_to(LoginState::Prompt) {
+                     return ProcessResult::failure(e.to_string(), state);
+                 }
+            }
+            ProcessResult {
+                lines: vec![
+                    WasmLine { text: "\n--- ACCESS GRANTED ---".to_string(), line_type: "regular".to_string() },
+                ],
+                next_state: state,
+                handled: true,
+                pending: None,
+                status: 0,
+            }
+        } else {
+            ProcessResult::failure("Login incorrect.".to_string(), state)
+        }
+    }
+}
@@ -1,5 +1,15 @@
+use std::cmp::Ordering;
 use crate::state::SystemState;
-use crate::fs;
+use crate::fs::{self, Inode, Source, Tree};
+use crate::commands::{self, Command, Stdio};
+use crate::shell::{ProcessResult, STDERR};
+use crate::completer;
+use crate::platform;
+use crate::WasmLine;
+
+/// Directory names on a line of several entries, as `ls-dir` lines look.
+const DIR: &str = "\x1b[1;34m";
+const RESET: &str = "\x1b[0m";
 
 fn format_date(date_str: &str) -> String {
     let parts: Vec<&str> = date_str.split('-').collect();
@@ -17,87 +27,252 @@ fn format_date(date_str: &str) -> String {
     format!("{} {} {}", month, day, year)
 }
 
-pub fn handle(state: &SystemState, args: Vec<&str>) -> String {
-    let mut long_format = false;
-    let mut target_path = state.cwd.clone();
+pub struct Ls;
+
+impl Command for Ls {
+    fn name(&self) -> &'static str { "ls" }
+    fn synopsis(&self) -> &'static str { "ls [-1ahlRrSt] [path...]" }
+    fn summary(&self) -> &'static str { "List directory contents" }
+    fn description(&self) -> &'static str {
+        "Lists files and subdirectories in the current or specified paths by name, e.g. ls -ltr /posts. -l shows when each file last changed, its size in bytes, its reading time and its tags, and -h writes sizes as 4.0K. -t sorts by modification time and -S by size, largest first, and -r reverses the order. -R lists subdirectories too, -1 one name per line and -a includes names starting with a dot, . and .. among them."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        let (lines, errors) = match handle(&state, args, stdio.piped) {
+            Ok(listing) => listing,
+            Err(usage) => return ProcessResult::failure(usage, state),
+        };
+        // Whatever was found is still shown, after the errors
+        let status = if errors.is_empty() { 0 } else { 1 };
+        let mut output: Vec<WasmLine> = errors.into_iter().map(|text| WasmLine { text, line_type: STDERR.to_string() }).collect();
+        output.extend(lines);
+        ProcessResult { lines: output, status, ..ProcessResult::simple(String::new(), state) }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    #[default]
+    Name,
+    /// Newest first.
+    Time,
+    /// Largest first.
+    Size,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Options {
+    all: bool,
+    long: bool,
+    one_per_line: bool,
+    human: bool,
+    recursive: bool,
+    reverse: bool,
+    sort: Sort,
+}
+
+/// Parses flags, alone or combined like `-ltr`, and returns the operands.
+fn parse(args: &[String]) -> Result<(Options, Vec<&str>), String> {
+    let (flags, operands) = commands::split_flags("ls", args, "alh1RrtS")
+        .map_err(|e| format!("{}\nUsage: ls [-1ahlRrSt] [path...]", e))?;
+    let mut options = Options::default();
+    for flag in flags {
+        match flag {
+            'a' => options.all = true,
+            'l' => options.long = true,
+            '1' => options.one_per_line = true,
+            'h' => options.human = true,
+            'R' => options.recursive = true,
+            'r' => options.reverse = true,
+            't' => options.sort = Sort::Time,
+            _ => options.sort = Sort::Size,
+        }
+    }
+    Ok((options, operands))
+}
+
+/// A size the way `ls -h` writes it: bytes up to 1023, then 1.5K, 12K, 3.0M...
+pub fn human_size(size: usize) -> String {
+    let mut value = size as f64;
+    let mut unit = "";
+    for next in ["K", "M", "G"] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if unit.is_empty() {
+        size.to_string()
+    } else if value < 10.0 {
+        // Rounded up, as ls does, so a size is never shown smaller than it is
+        format!("{:.1}{}", (value * 10.0).ceil() / 10.0, unit)
+    } else {
+        format!("{}{}", value.ceil(), unit)
+    }
+}
+
+/// One `ls -l` line for `inode`, shown as `display`.
+fn long_entry(state: &SystemState, inode: &Inode, display: &str, human: bool) -> String {
+    let perm = if inode.is_dir() { "drwxr-xr-x" } else { "-rw-r--r--" };
+    let owner = match inode.source {
+        Source::User(_) => state.current_user.as_str(),
+        _ => "tecnoter",
+    };
+    let size = if human { human_size(inode.size) } else { inode.size.to_string() };
+    let item = fs::item(state, &inode.source);
+    let reading = item.as_ref()
+        .filter(|item| item.meta.reading_time > 0)
+        .map(|item| format!(" ({} min read)", item.meta.reading_time))
+        .unwrap_or_default();
+    let tags = item
+        .filter(|item| !item.tags.is_empty())
+        .map(|item| format!(" [{}]", item.tags.join(",")))
+        .unwrap_or_default();
+    format!("{} {} staff {:>5} {} {}{}{}", perm, owner, size, format_date(&inode.mtime), display, reading, tags)
+}
+
+fn regular(text: String) -> WasmLine {
+    WasmLine { text, line_type: "regular".to_string() }
+}
+
+/// Puts `entries` in the order the options ask for, by name otherwise.
+fn sort(entries: &mut [(String, &Inode)], options: Options) {
+    entries.sort_by(|(a_name, a), (b_name, b)| {
+        let order = match options.sort {
+            Sort::Name => Ordering::Equal,
+            Sort::Time => b.mtime.cmp(&a.mtime),
+            Sort::Size => b.size.cmp(&a.size),
+        };
+        order.then_with(|| a_name.cmp(b_name))
+    });
+    if options.reverse {
+        entries.reverse();
+    }
+}
+
+/// The entries of the directory at the absolute `path` that are listed.
+fn dir_entries<'a>(tree: &'a Tree, path: &str, options: Options) -> Vec<(String, &'a Inode)> {
+    let mut entries: Vec<(String, &Inode)> = tree.entries(path).unwrap_or_default().into_iter()
+        .filter(|(name, _)| options.all || !name.starts_with('.'))
+        .map(|(name, inode)| (name.to_string(), inode))
+        .collect();
+    if options.all {
+        entries.extend(tree.lookup(path).map(|dir| (".".to_string(), dir)));
+        entries.extend(tree.lookup(fs::parent_dir(path)).map(|parent| ("..".to_string(), parent)));
+    }
+    sort(&mut entries, options);
+    entries
+}
+
+/// The sections of a listing being written, and how to write them.
+struct Listing<'a> {
+    state: &'a SystemState,
+    tree: &'a Tree,
+    options: Options,
+    one_per_line: bool,
+    /// Whether each directory gets a `name:` header.
+    headers: bool,
+    sections: Vec<Vec<WasmLine>>,
+}
 
-    for arg in &args {
-        if *arg == "-l" {
-            long_format = true;
-        } else if !arg.starts_with('-') {
-            target_path = fs::resolve_path(&state.cwd, arg);
+impl Listing<'_> {
+    /// `entries` as output lines: one per entry, typed so the terminal can
+    /// tell directories from files, or all on one line with directories
+    /// coloured in it.
+    fn block(&self, entries: &[(String, &Inode)]) -> Vec<WasmLine> {
+        if !self.one_per_line {
+            let names: Vec<String> = entries.iter()
+                .map(|(name, inode)| if inode.is_dir() { format!("{}{}{}", DIR, name, RESET) } else { name.clone() })
+                .collect();
+            return if names.is_empty() { vec![] } else { vec![regular(names.join("  "))] };
         }
+        entries.iter()
+            .map(|(name, inode)| WasmLine {
+                text: if self.options.long { long_entry(self.state, inode, name, self.options.human) } else { name.clone() },
+                line_type: if inode.is_dir() { "ls-dir" } else { "ls-file" }.to_string(),
+            })
+            .collect()
     }
 
-    if let Some(contents) = fs::get_directory_contents(&target_path) {
-        web_sys::console::log_1(&format!("ls: target_path={} found in logic", target_path).into());
-        let mut files: Vec<String> = contents.iter().map(|&s| s.to_string()).collect();
-        if files.is_empty() {
-            if target_path == "/posts" {
-                files = state.posts.iter().map(|p| p.slug.clone()).collect();
-                web_sys::console::log_1(&format!("ls: populated {} posts", files.len()).into());
-            } else if target_path == "/pages" {
-                files = state.pages.iter().map(|p| p.slug.clone()).collect();
-                web_sys::console::log_1(&format!("ls: populated {} pages", files.len()).into());
-            } else if target_path == "/tags" {
-                let mut tags: Vec<String> = state.posts.iter().flat_map(|p| p.tags.clone()).collect();
-                tags.extend(state.pages.iter().flat_map(|p| p.tags.clone()));
-                tags.sort();
-                tags.dedup();
-                files = tags;
-            } else if target_path == "/categories" {
-                let mut cats: Vec<String> = state.posts.iter().flat_map(|p| p.categories.clone()).collect();
-                cats.extend(state.pages.iter().flat_map(|p| p.categories.clone()));
-                cats.sort();
-                cats.dedup();
-                files = cats;
-            } else if target_path.starts_with("/tags/") {
-                let tag = &target_path[6..];
-                files = state.posts.iter().filter(|p| p.tags.contains(&tag.to_string())).map(|p| p.slug.clone()).collect();
-                files.extend(state.pages.iter().filter(|p| p.tags.contains(&tag.to_string())).map(|p| p.slug.clone()).collect::<Vec<_>>());
-            } else if target_path.starts_with("/categories/") {
-                let cat = &target_path[12..];
-                files = state.posts.iter().filter(|p| p.categories.contains(&cat.to_string())).map(|p| p.slug.clone()).collect();
-                files.extend(state.pages.iter().filter(|p| p.categories.contains(&cat.to_string())).map(|p| p.slug.clone()).collect::<Vec<_>>());
+    /// Lists the directory at the absolute `path`, shown as `display`, and
+    /// with `-R` every directory under it, each as a section of its own.
+    fn dir(&mut self, display: &str, path: &str) {
+        let entries = dir_entries(self.tree, path, self.options);
+        let mut section = Vec::new();
+        if self.headers {
+            section.push(regular(format!("{}:", display)));
+        }
+        section.extend(self.block(&entries));
+        self.sections.push(section);
+
+        if !self.options.recursive {
+            return;
+        }
+        for (name, inode) in &entries {
+            if inode.is_dir() && name != "." && name != ".." {
+                let child = |base: &str| if base.ends_with('/') { format!("{}{}", base, name) } else { format!("{}/{}", base, name) };
+                self.dir(&child(display), &child(path));
             }
         }
+    }
+}
 
-        if long_format {
-            let mut output = String::new();
-            for file in files {
-                let mut extra = String::new();
-                let (perm, size, date_raw) = if target_path == "/posts" || target_path.starts_with("/tags/") || target_path.starts_with("/categories/") {
-                    if let Some(post) = state.posts.iter().find(|p| p.slug == file) {
-                        if !post.tags.is_empty() { extra.push_str(&format!(" [{}]", post.tags.join(","))); }
-                        ("-rw-r--r--", "1228", post.date.as_str())
-                    } else if let Some(page) = state.pages.iter().find(|p| p.slug == file) {
-                        if !page.tags.is_empty() { extra.push_str(&format!(" [{}]", page.tags.join(","))); }
-                        ("-rw-r--r--", "1024", page.date.as_str())
-                    } else {
-                        ("-rw-r--r--", "1024", "2026-01-01")
-                    }
-                } else if target_path == "/pages" {
-                    if let Some(page) = state.pages.iter().find(|p| p.slug == file) {
-                        if !page.tags.is_empty() { extra.push_str(&format!(" [{}]", page.tags.join(","))); }
-                        ("-rw-r--r--", "1024", page.date.as_str())
-                    } else {
-                        ("-rw-r--r--", "1024", "2026-01-01")
-                    }
-                } else if file == "posts" || file == "pages" || file == "tags" || file == "categories" || target_path == "/tags" || target_path == "/categories" {
-                    ("drwxr-xr-x", "4096", "2026-01-01")
-                } else {
-                    ("-rw-r--r--", "1024", "2026-01-01")
-                };
-                
-                let date_fmt = format_date(date_raw);
-                output.push_str(&format!("{} tecnoter staff {:>5} {} {}{}\n", 
-                    perm, size, date_fmt, file, extra));
+/// The listing and the errors for operands that do not exist; fails only
+/// on a bad option. `piped` switches to one name per line, like `ls`
+/// writing to anything but a terminal.
+///
+/// File operands are listed first, then each directory; with several
+/// operands or `-R` every directory gets a `name:` header.
+pub fn handle(state: &SystemState, args: &[String], piped: bool) -> Result<(Vec<WasmLine>, Vec<String>), String> {
+    let (options, mut operands) = parse(args)?;
+    if operands.is_empty() {
+        operands.push(&state.cwd);
+    }
+    let tree = fs::tree(state);
+    let mut errors = Vec::new();
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for &operand in &operands {
+        let path = fs::resolve_path(&state.cwd, operand);
+        match tree.lookup(&path) {
+            Some(inode) if inode.is_dir() => {
+                platform::current().log(&format!("ls: target_path={} found in logic", path));
+                dirs.push((operand.to_string(), inode));
             }
-            return output.trim_end().to_string();
-        } else {
-            return files.join("  ");
+            Some(inode) => files.push((operand.to_string(), inode)),
+            None => errors.push(format!("ls: cannot access '{}': No such file or directory", operand)),
         }
     }
 
-    format!("ls: cannot access '{}': No such directory", target_path)
+    let mut listing = Listing {
+        state,
+        tree: &tree,
+        options,
+        one_per_line: options.long || options.one_per_line || piped,
+        headers: operands.len() > 1 || options.recursive,
+        sections: Vec::new(),
+    };
+    if !files.is_empty() {
+        sort(&mut files, options);
+        let block = listing.block(&files);
+        listing.sections.push(block);
+    }
+    sort(&mut dirs, options);
+    for (operand, _) in &dirs {
+        listing.dir(operand, &fs::resolve_path(&state.cwd, operand));
+    }
+
+    let mut lines = Vec::new();
+    for section in listing.sections.into_iter().filter(|section| !section.is_empty()) {
+        if !lines.is_empty() {
+            lines.push(regular(String::new()));
+        }
+        lines.extend(section);
+    }
+    Ok((lines, errors))
 }
@@ -0,0 +1,30 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+
+pub struct Mail;
+
+impl Command for Mail {
+    fn name(&self) -> &'static str { "mail" }
+    fn synopsis(&self) -> &'static str { "mail [username]" }
+    fn summary(&self) -> &'static str { "Send mail to another user" }
+    fn description(&self) -> &'static str {
+        "Prompts for a message and sends it to username."
+    }
+
+    // The next line typed is the message, which only the shell prompt expects
+    fn allowed(&self, login_state: LoginState) -> bool {
+        login_state == LoginState::Prompt
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        if args.is_empty() {
+            return ProcessResult::failure("Usage: mail [username]".to_string(), state);
+        }
+        state.mail_recipient = Some(args[0].clone());
+        if let Err(e) = state.go_to(LoginState::Mail) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
+        ProcessResult::simple(String::new(), state)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+use crate::WasmLine;
+
+/// Character sets the host's digital rain knows about.
+const MODES: [&str; 4] = ["binary", "ascii", "hex", "tecnoter"];
+
+pub struct Matrix;
+
+impl Command for Matrix {
+    fn name(&self) -> &'static str { "matrix" }
+    fn synopsis(&self) -> &'static str { "matrix [binary|ascii|hex|tecnoter]" }
+    fn summary(&self) -> &'static str { "Experience the matrix" }
+    fn description(&self) -> &'static str {
+        "Runs the digital rain animation with the given character set, binary by default."
+    }
+
+    fn complete(&self, _state: &SystemState, args: &[String], partial: &str) -> Vec<String> {
+        if !args.is_empty() {
+            return Vec::new();
+        }
+        MODES.iter().filter(|m| m.starts_with(partial)).map(|m| m.to_string()).collect()
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let mode = args.first().map(String::as_str).unwrap_or("binary");
+        ProcessResult {
+            lines: vec![WasmLine { text: format!("_MATRIX_{}", mode), line_type: "internalInstruction".to_string() }],
+            next_state: state,
+            handled: true,
+            pending: None,
+            status: 0,
+        }
+    }
+}
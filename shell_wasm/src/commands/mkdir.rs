@@ -0,0 +1,59 @@
+use crate::commands::{self, Command, Stdio};
+use crate::completer;
+use crate::fs::{self, Tree};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Mkdir;
+
+impl Command for Mkdir {
+    fn name(&self) -> &'static str { "mkdir" }
+    fn synopsis(&self) -> &'static str { "mkdir [-p] dir..." }
+    fn summary(&self) -> &'static str { "Make directories" }
+    fn description(&self) -> &'static str {
+        "Makes each directory, in /tmp or your home directory. With -p, makes the missing parents too and is quiet about directories that exist. e.g. mkdir -p ~/notes/rust"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&mut state, args) {
+            Ok(()) => ProcessResult::simple(String::new(), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let (flags, operands) = commands::split_flags("mkdir", args, "p")?;
+    if operands.is_empty() {
+        return Err("mkdir: missing operand".to_string());
+    }
+    let parents = flags.contains(&'p');
+    let mut tree = fs::tree_for_writing(state);
+    let mut errors = Vec::new();
+    for operand in operands {
+        let path = fs::resolve_path(&state.cwd, operand);
+        let made = if parents { make_parents(state, &mut tree, &path) } else { fs::make_dir(state, &mut tree, &path) };
+        if let Err(reason) = made {
+            errors.push(format!("mkdir: cannot create directory '{}': {}", operand, reason));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
+
+/// Makes `path` and whichever of its parents are missing.
+fn make_parents(state: &mut SystemState, tree: &mut Tree, path: &str) -> Result<(), &'static str> {
+    let mut dir = String::new();
+    for name in path.split('/').filter(|name| !name.is_empty()) {
+        dir = format!("{}/{}", dir, name);
+        match tree.lookup(&dir) {
+            Some(inode) if inode.is_dir() => {}
+            Some(_) => return Err("File exists"),
+            None => fs::make_dir(state, tree, &dir)?,
+        }
+    }
+    Ok(())
+}
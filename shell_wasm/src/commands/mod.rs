@@ -1,3 +1,8 @@
+use std::future::Future;
+use std::pin::Pin;
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+
 pub mod ls;
 pub mod whoami;
 pub mod fortune;
@@ -14,3 +19,211 @@ pub mod date;
 pub mod motd;
 pub mod curl;
 pub mod cd;
+pub mod dirs;
+pub mod find;
+pub mod file;
+pub mod stat;
+pub mod tree;
+pub mod touch;
+pub mod mkdir;
+pub mod rm;
+pub mod cp;
+pub mod mv;
+pub mod grep;
+pub mod search;
+pub mod head;
+pub mod tail;
+pub mod wc;
+pub mod echo;
+pub mod export;
+pub mod env;
+pub mod set;
+pub mod unset;
+pub mod boolean;
+pub mod ping;
+pub mod bbs;
+pub mod mail;
+pub mod msg;
+pub mod clear;
+pub mod matrix;
+pub mod ansi;
+pub mod exit;
+pub mod boot;
+pub mod login;
+pub mod history;
+pub mod jobs;
+
+/// A shell command. Dispatch, `help`, `man` and completion are all driven by
+/// the [`registry`], so adding a command means implementing this trait in its
+/// own module and listing it there.
+pub trait Command: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Other names the command answers to.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Usage line for the man page, e.g. `ls [-l] [path]`.
+    fn synopsis(&self) -> &'static str;
+
+    /// One line for `help`.
+    fn summary(&self) -> &'static str;
+
+    /// DESCRIPTION section of the man page.
+    fn description(&self) -> &'static str;
+
+    fn man_page(&self) -> String {
+        format!(
+            "NAME\n    {} - {}\n\nSYNOPSIS\n    {}\n\nDESCRIPTION\n    {}",
+            self.name(), self.summary(), self.synopsis(), self.description()
+        )
+    }
+
+    /// Candidates for the argument being typed, which starts with `partial`.
+    /// `args` are the complete arguments before it.
+    fn complete(&self, _state: &SystemState, _args: &[String], _partial: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Whether the command can run in `login_state`. By default that is the
+    /// shell prompt and the BBS screens.
+    fn allowed(&self, login_state: LoginState) -> bool {
+        login_state.is_shell()
+    }
+
+    /// Runs the command. `args` excludes the command name.
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult;
+}
+
+static COMMANDS: &[&dyn Command] = &[
+    &help::Help,
+    &ls::Ls,
+    &cd::Cd,
+    &dirs::Pushd,
+    &dirs::Popd,
+    &dirs::Dirs,
+    &cat::Cat,
+    &find::Find,
+    &file::File,
+    &stat::Stat,
+    &tree::Tree,
+    &touch::Touch,
+    &mkdir::Mkdir,
+    &rm::Rm,
+    &cp::Cp,
+    &mv::Mv,
+    &whoami::Whoami,
+    &bbs::Bbs,
+    &uptime::Uptime,
+    &fortune::Fortune,
+    &cowsay::Cowsay,
+    &weather::Weather,
+    &top::Top,
+    &who::Who,
+    &social::Social,
+    &curl::Curl,
+    &grep::Grep,
+    &search::Search,
+    &head::Head,
+    &tail::Tail,
+    &wc::Wc,
+    &date::Date,
+    &motd::Motd,
+    &echo::Echo,
+    &export::Export,
+    &env::Env,
+    &set::Set,
+    &unset::Unset,
+    &history::History,
+    &jobs::Jobs,
+    &jobs::Fg,
+    &jobs::Wait,
+    &jobs::Kill,
+    &boolean::True,
+    &boolean::False,
+    &man::Man,
+    &mail::Mail,
+    &msg::Msg,
+    &ping::Ping,
+    &matrix::Matrix,
+    &ansi::Ansi,
+    &clear::Clear,
+    &exit::Exit,
+    &boot::Boot,
+    &login::StartLogin,
+    &login::Login,
+    &bbs::ReadInternal,
+    &jobs::Interrupt,
+];
+
+/// Every command, in the order `help` lists them.
+pub fn registry() -> &'static [&'static dyn Command] {
+    COMMANDS
+}
+
+/// Looks a command up by name or alias.
+pub fn find(name: &str) -> Option<&'static dyn Command> {
+    COMMANDS.iter().copied().find(|c| c.name() == name || c.aliases().contains(&name))
+}
+
+/// Commands the user can see in `login_state`. Names starting with `_` are
+/// the host's internal commands and stay out of help and completion.
+pub fn visible(login_state: LoginState) -> impl Iterator<Item = &'static dyn Command> {
+    COMMANDS.iter().copied().filter(move |c| !c.name().starts_with('_') && c.allowed(login_state))
+}
+
+/// Output that is still being produced by a background task (e.g. a fetch).
+/// Resolves to the text on success or to an error message meant for the terminal.
+pub type Deferred = Pin<Box<dyn Future<Output = Result<String, String>>>>;
+
+/// Standard streams handed to a command running inside a pipeline.
+#[derive(Default)]
+pub struct Stdio {
+    /// Output of the previous command in the pipeline, if any.
+    pub stdin: Option<String>,
+    /// Whether our output feeds another command instead of the terminal.
+    pub piped: bool,
+}
+
+/// What a command writes to standard output.
+pub enum Stdout {
+    Text(String),
+    /// Text that arrives later. `notice` is shown right away when the output
+    /// goes to the terminal, so the user knows something is in flight.
+    Deferred { notice: String, body: Deferred },
+}
+
+/// Splits `args` into the single-letter flags in `allowed`, which combine
+/// as in `-rf`, and the operands. `--` ends the flags.
+pub fn split_flags<'a>(command: &str, args: &'a [String], allowed: &str) -> Result<(Vec<char>, Vec<&'a str>), String> {
+    let mut flags = Vec::new();
+    let mut operands = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix('-') {
+            Some("-") => {
+                operands.extend(args.map(String::as_str));
+                break;
+            }
+            Some(letters) if !letters.is_empty() => {
+                for flag in letters.chars() {
+                    if !allowed.contains(flag) {
+                        return Err(format!("{}: invalid option -- '{}'", command, flag));
+                    }
+                    flags.push(flag);
+                }
+            }
+            _ => operands.push(arg.as_str()),
+        }
+    }
+    Ok((flags, operands))
+}
+
+/// Splits a text stream into lines, ignoring the trailing newline.
+pub fn lines(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        return vec![];
+    }
+    text.strip_suffix('\n').unwrap_or(text).split('\n').collect()
+}
@@ -1,4 +1,21 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Motd;
+
+impl Command for Motd {
+    fn name(&self) -> &'static str { "motd" }
+    fn synopsis(&self) -> &'static str { "motd" }
+    fn summary(&self) -> &'static str { "Show Message of the Day" }
+    fn description(&self) -> &'static str {
+        "Displays the system welcome message and node information."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 /// Handles the 'motd' (Message of the Day) command.
 /// Dynamically constructs a welcome message including the user's name and company biography.
@@ -0,0 +1,26 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::{LoginState, SystemState};
+
+pub struct Msg;
+
+impl Command for Msg {
+    fn name(&self) -> &'static str { "msg" }
+    fn aliases(&self) -> &'static [&'static str] { &["message"] }
+    fn synopsis(&self) -> &'static str { "msg" }
+    fn summary(&self) -> &'static str { "Leave a message for the sysop" }
+    fn description(&self) -> &'static str {
+        "Prompts for a message and leaves it for the sysop."
+    }
+
+    fn allowed(&self, login_state: LoginState) -> bool {
+        login_state == LoginState::Prompt
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        if let Err(e) = state.go_to(LoginState::Message) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
+        ProcessResult::simple(String::new(), state)
+    }
+}
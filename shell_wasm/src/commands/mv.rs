@@ -0,0 +1,69 @@
+use crate::commands::{self, Command, Stdio};
+use crate::completer;
+use crate::fs;
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Mv;
+
+impl Command for Mv {
+    fn name(&self) -> &'static str { "mv" }
+    fn synopsis(&self) -> &'static str { "mv source... dest" }
+    fn summary(&self) -> &'static str { "Move or rename files" }
+    fn description(&self) -> &'static str {
+        "Renames source to dest, or moves every source into dest when it is a directory. Only your own files in /tmp and your home directory move; copy a post with cp to keep it. e.g. mv ~/notes.txt ~/notes/"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&mut state, args) {
+            Ok(()) => ProcessResult::simple(String::new(), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let (_, operands) = commands::split_flags("mv", args, "")?;
+    let (sources, dest) = targets("mv", state, &operands)?;
+    let mut tree = fs::tree_for_writing(state);
+    let mut errors = Vec::new();
+    for (operand, to) in sources.iter().zip(dest) {
+        let from = fs::resolve_path(&state.cwd, operand);
+        if tree.lookup(&from).is_none() {
+            errors.push(format!("mv: cannot stat '{}': No such file or directory", operand));
+        } else if let Err(reason) = fs::rename(state, &mut tree, &from, &to) {
+            errors.push(format!("mv: cannot move '{}' to '{}': {}", operand, to, reason));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
+
+/// The sources among `operands` and where each one goes: into the last
+/// operand when it is a directory, or to it when there is one source.
+/// Shared with `cp`.
+pub fn targets<'a>(command: &str, state: &SystemState, operands: &[&'a str]) -> Result<(Vec<&'a str>, Vec<String>), String> {
+    let (dest, sources) = match operands.split_last() {
+        Some((_, [])) => return Err(format!("{}: missing destination file operand after '{}'", command, operands[0])),
+        Some((dest, sources)) => (*dest, sources.to_vec()),
+        None => return Err(format!("{}: missing file operand", command)),
+    };
+    let dest_path = fs::resolve_path(&state.cwd, dest);
+    if !fs::tree(state).is_dir(&dest_path) {
+        if sources.len() > 1 {
+            return Err(format!("{}: target '{}' is not a directory", command, dest));
+        }
+        return Ok((sources, vec![dest_path]));
+    }
+    let inside = sources.iter()
+        .map(|source| {
+            let name = fs::resolve_path(&state.cwd, source);
+            let name = name.rsplit('/').next().unwrap_or("");
+            fs::resolve_path(&dest_path, name)
+        })
+        .collect();
+    Ok((sources, inside))
+}
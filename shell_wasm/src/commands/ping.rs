@@ -0,0 +1,18 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Ping;
+
+impl Command for Ping {
+    fn name(&self) -> &'static str { "ping" }
+    fn synopsis(&self) -> &'static str { "ping" }
+    fn summary(&self) -> &'static str { "Check the WASM core" }
+    fn description(&self) -> &'static str {
+        "Answers PONG when the Rust core is online."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple("PONG (WASM Core Online)".to_string(), state)
+    }
+}
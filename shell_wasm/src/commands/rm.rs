@@ -0,0 +1,47 @@
+use crate::commands::{self, Command, Stdio};
+use crate::completer;
+use crate::fs;
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Rm;
+
+impl Command for Rm {
+    fn name(&self) -> &'static str { "rm" }
+    fn synopsis(&self) -> &'static str { "rm [-rf] path..." }
+    fn summary(&self) -> &'static str { "Remove files and directories" }
+    fn description(&self) -> &'static str {
+        "Removes your own files from /tmp and your home directory. -r removes directories and everything in them, and -f keeps quiet about paths that do not exist. Posts, pages and the rest of the tree cannot be removed. e.g. rm -r ~/drafts"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&mut state, args) {
+            Ok(()) => ProcessResult::simple(String::new(), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let (flags, operands) = commands::split_flags("rm", args, "rRf")?;
+    let recursive = flags.contains(&'r') || flags.contains(&'R');
+    let force = flags.contains(&'f');
+    if operands.is_empty() && !force {
+        return Err("rm: missing operand".to_string());
+    }
+    let mut tree = fs::tree_for_writing(state);
+    let mut errors = Vec::new();
+    for operand in operands {
+        let path = fs::resolve_path(&state.cwd, operand);
+        match fs::remove(state, &mut tree, &path, recursive) {
+            Err("No such file or directory") if force => {}
+            Err(reason) => errors.push(format!("rm: cannot remove '{}': {}", operand, reason)),
+            Ok(()) => {}
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
@@ -0,0 +1,119 @@
+use std::ops::Range;
+use crate::commands::{Command, Stdio, Stdout};
+use crate::fs;
+use crate::http::{self, HttpError};
+use crate::search::{self, Index, Query};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+/// Results shown unless `-n` says otherwise.
+const LIMIT: usize = 10;
+
+/// Colours for a slug and a matched term, as `grep` uses for names and matches.
+const SLUG: &str = "\x1b[35m";
+const MATCH: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+pub struct Search;
+
+impl Command for Search {
+    fn name(&self) -> &'static str { "search" }
+    fn synopsis(&self) -> &'static str { "search [-n N] QUERY..." }
+    fn summary(&self) -> &'static str { "Search the site's full text" }
+    fn description(&self) -> &'static str {
+        "Ranks the posts and pages holding every word of QUERY and shows the best N (10 by default), each as the slug cat opens, its title and the text around the first match. A quoted \"phrase\" must appear as written; tag:NAME and category:NAME keep the pages filed under them. e.g. search \"web assembly\" tag:rust"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        // tag: and category: take the names in /tags and /categories
+        let Some((prefix, name)) = partial.split_once(':') else { return Vec::new() };
+        let dir = match prefix {
+            "tag" => "/tags",
+            "category" => "/categories",
+            _ => return Vec::new(),
+        };
+        fs::tree(state).list(dir).unwrap_or_default().into_iter()
+            .filter(|entry| entry.starts_with(name))
+            .map(|entry| format!("{}:{}", prefix, entry))
+            .collect()
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_stdout(handle(args, stdio.piped), state)
+    }
+}
+
+/// Searches the index, loading it first if this session has not yet.
+pub fn handle(args: &[String], piped: bool) -> Result<Stdout, String> {
+    let (limit, args) = match args {
+        [flag, n, rest @ ..] if flag == "-n" => {
+            (n.parse::<usize>().map_err(|_| format!("search: -n: not a number: {}", n))?, rest)
+        }
+        _ => (LIMIT, args),
+    };
+    let query = Query::parse(args);
+    if query.is_empty() {
+        return Err("Usage: search [-n N] QUERY...".to_string());
+    }
+    // The query as it would be typed again
+    let wanted = args.iter()
+        .map(|arg| if arg.contains(char::is_whitespace) { format!("\"{}\"", arg) } else { arg.clone() })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if let Some(index) = search::loaded() {
+        return results(&index, &query, limit, &wanted, piped).map(Stdout::Text);
+    }
+    let body = async move {
+        let text = http::client().get(search::INDEX_URL).await.map_err(|e| match e {
+            HttpError::Status(404) => format!("search: no search index at {}", search::INDEX_URL),
+            e => format!("search: {} loading {}", e, search::INDEX_URL),
+        })?;
+        let index = search::load(Index::from_json(&text).map_err(|e| format!("search: {}", e))?);
+        results(&index, &query, limit, &wanted, piped)
+    };
+    Ok(Stdout::Deferred {
+        notice: "Loading the search index...".to_string(),
+        body: Box::pin(body),
+    })
+}
+
+/// The best `limit` hits, each as its slug and title over a snippet.
+fn results(index: &Index, query: &Query, limit: usize, wanted: &str, piped: bool) -> Result<String, String> {
+    let hits = index.search(query);
+    if hits.is_empty() {
+        return Err(format!("search: nothing matches {}", wanted));
+    }
+
+    let mut output = Vec::new();
+    for hit in hits.iter().take(limit) {
+        let (snippet, marks) = hit.snippet(query);
+        let line = if piped {
+            output.push(format!("{}  {}", hit.doc.slug, hit.doc.title));
+            snippet
+        } else {
+            output.push(format!("{}{}{}  {}", SLUG, hit.doc.slug, RESET, hit.doc.title));
+            highlight(&snippet, &marks)
+        };
+        if !line.is_empty() {
+            output.push(format!("    {}", line));
+        }
+    }
+    if hits.len() > limit {
+        output.push(format!("({} more, see search -n {} {})", hits.len() - limit, hits.len(), wanted));
+    }
+    Ok(output.join("\n"))
+}
+
+/// `snippet` with the terms at `marks` in bold red.
+fn highlight(snippet: &str, marks: &[Range<usize>]) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for mark in marks {
+        highlighted.push_str(&snippet[last..mark.start]);
+        highlighted.push_str(&format!("{}{}{}", MATCH, &snippet[mark.clone()], RESET));
+        last = mark.end;
+    }
+    highlighted.push_str(&snippet[last..]);
+    highlighted
+}
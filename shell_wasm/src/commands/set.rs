@@ -0,0 +1,28 @@
+use crate::expand;
+use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Set;
+
+impl Command for Set {
+    fn name(&self) -> &'static str { "set" }
+    fn synopsis(&self) -> &'static str { "set" }
+    fn summary(&self) -> &'static str { "List all variables" }
+    fn description(&self) -> &'static str {
+        "Lists every shell variable, exported or not, and the last exit status."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
+
+/// Lists every variable, exported or not, plus `?`.
+pub fn handle(state: &SystemState) -> String {
+    let mut out: Vec<String> = expand::all(state).into_iter()
+        .map(|(name, value, _)| format!("{}='{}'", name, value))
+        .collect();
+    out.push(format!("?={}", state.last_status));
+    out.join("\n")
+}
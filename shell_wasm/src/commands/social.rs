@@ -1,6 +1,50 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::WasmLine;
 
-pub fn handle(state: &SystemState, args: Vec<&str>) -> String {
+pub struct Social;
+
+impl Command for Social {
+    fn name(&self) -> &'static str { "social" }
+    fn synopsis(&self) -> &'static str { "social [network]" }
+    fn summary(&self) -> &'static str { "Social media connections" }
+    fn description(&self) -> &'static str {
+        "Displays connected social networks or opens the specified network in a new uplink."
+    }
+
+    fn complete(&self, state: &SystemState, args: &[String], partial: &str) -> Vec<String> {
+        if !args.is_empty() {
+            return Vec::new();
+        }
+        state.socials.iter()
+            .map(|s| s.name.to_lowercase())
+            .filter(|name| name.starts_with(&partial.to_lowercase()))
+            .collect()
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let output = match handle(&state, args) {
+            Ok(output) => output,
+            Err(e) => return ProcessResult::failure(e, state),
+        };
+        if let Some(url) = output.strip_prefix("_OPEN_URL_") {
+            return ProcessResult {
+                lines: vec![
+                    WasmLine { text: format!("Opening uplink to {}...", url), line_type: "regular".to_string() },
+                    WasmLine { text: output.clone(), line_type: "internalInstruction".to_string() }
+                ],
+                next_state: state,
+                handled: true,
+                pending: None,
+                status: 0,
+            };
+        }
+        ProcessResult::simple(output, state)
+    }
+}
+
+pub fn handle(state: &SystemState, args: &[String]) -> Result<String, String> {
     if args.is_empty() {
         let mut output = String::from("Connected Social Networks:\n");
         if state.socials.is_empty() {
@@ -11,14 +55,14 @@ pub fn handle(state: &SystemState, args: Vec<&str>) -> String {
             }
             output.push_str("\nUsage: social [network] to open in a new link.");
         }
-        output
+        Ok(output)
     } else {
         let network = args[0].to_lowercase();
         let found = state.socials.iter().find(|s| s.name.to_lowercase() == network);
         if let Some(social) = found {
-            format!("_OPEN_URL_{}", social.url)
+            Ok(format!("_OPEN_URL_{}", social.url))
         } else {
-            format!("Social network not found: {}", network)
+            Err(format!("Social network not found: {}", network))
         }
     }
 }
@@ -0,0 +1,93 @@
+use crate::commands::file;
+use crate::commands::{Command, Stdio};
+use crate::completer;
+use crate::fs::{self, Inode, Source, Tree};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Stat;
+
+impl Command for Stat {
+    fn name(&self) -> &'static str { "stat" }
+    fn synopsis(&self) -> &'static str { "stat path..." }
+    fn summary(&self) -> &'static str { "Show everything known about a file" }
+    fn description(&self) -> &'static str {
+        "Shows what kind of file each path is, its size and when it last changed. Posts and pages add their word count, reading time, publication date, URL, tags and categories, and their author and series when they have them. e.g. stat bio"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&state, args) {
+            Ok((output, errors)) => {
+                let status = if errors.is_empty() { 0 } else { 1 };
+                ProcessResult::with_errors(output, errors, status, state)
+            }
+            Err(usage) => ProcessResult::failure(usage, state),
+        }
+    }
+}
+
+/// The output for the paths that exist, and the errors for those that do
+/// not; fails only without a path.
+pub fn handle(state: &SystemState, args: &[String]) -> Result<(String, Vec<String>), String> {
+    if args.is_empty() {
+        return Err("Usage: stat path...".to_string());
+    }
+    let tree = fs::tree(state);
+    let mut blocks = Vec::new();
+    let mut errors = Vec::new();
+    for operand in args {
+        let path = fs::resolve_path(&state.cwd, operand);
+        match tree.lookup(&path) {
+            Some(inode) => blocks.push(describe(state, &tree, &path, inode)),
+            None => errors.push(format!("stat: cannot stat '{}': No such file or directory", operand)),
+        }
+    }
+    Ok((blocks.join("\n"), errors))
+}
+
+/// The fields of the inode at the absolute `path`, one per line.
+fn describe(state: &SystemState, tree: &Tree, path: &str, inode: &Inode) -> String {
+    let mut fields = vec![
+        ("File", path.to_string()),
+        ("Type", file::kind(state, tree, path, inode)),
+        ("Size", format!("{} bytes", inode.size)),
+    ];
+    let list = |names: &[String]| if names.is_empty() { "-".to_string() } else { names.join(", ") };
+
+    if let Some(item) = fs::item(state, &inode.source) {
+        fields.push(("Words", item.meta.word_count.to_string()));
+        fields.push(("Reading", format!("{} min", item.meta.reading_time)));
+        fields.push(("Title", item.title.to_string()));
+        fields.push(("URL", item.url.to_string()));
+        fields.push(("Date", item.meta.date.clone()));
+        fields.push(("Modified", inode.mtime.clone()));
+        fields.push(("Tags", list(item.tags)));
+        fields.push(("Categories", list(item.categories)));
+        if !item.meta.author.is_empty() {
+            fields.push(("Author", item.meta.author.clone()));
+        }
+        if !item.meta.series.is_empty() {
+            fields.push(("Series", list(&item.meta.series)));
+        }
+        if !item.meta.summary.is_empty() {
+            fields.push(("Summary", item.meta.summary.clone()));
+        }
+    } else if inode.is_dir() {
+        let entries = tree.entries(path).map_or(0, |entries| entries.len());
+        fields.push(("Entries", entries.to_string()));
+        fields.push(("Modified", inode.mtime.clone()));
+    } else {
+        let owner = match &inode.source {
+            Source::User(_) => state.current_user.clone(),
+            _ => "tecnoter".to_string(),
+        };
+        fields.push(("Owner", owner));
+        fields.push(("Modified", inode.mtime.clone()));
+    }
+
+    fields.iter().map(|(name, value)| format!("{:>10}: {}", name, value)).collect::<Vec<_>>().join("\n")
+}
@@ -0,0 +1,33 @@
+use crate::commands::{Command, head, lines, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Tail;
+
+impl Command for Tail {
+    fn name(&self) -> &'static str { "tail" }
+    fn synopsis(&self) -> &'static str { "... | tail [-n N]" }
+    fn summary(&self) -> &'static str { "Last lines of piped input" }
+    fn description(&self) -> &'static str {
+        "Prints the last N lines (10 by default) of its input."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_result(handle(args, stdio.stdin.as_deref()), state)
+    }
+}
+
+pub fn handle(args: &[String], stdin: Option<&str>) -> Result<String, String> {
+    let count = match head::parse_count(args) {
+        Some(n) => n,
+        None => return Err("Usage: tail [-n N]".to_string()),
+    };
+    let input = match stdin {
+        Some(text) => text,
+        None => return Err("tail: no input (try: ls /posts | tail -3)".to_string()),
+    };
+
+    let all = lines(input);
+    let skip = all.len().saturating_sub(count);
+    Ok(all[skip..].join("\n"))
+}
@@ -1,4 +1,21 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Top;
+
+impl Command for Top {
+    fn name(&self) -> &'static str { "top" }
+    fn synopsis(&self) -> &'static str { "top" }
+    fn summary(&self) -> &'static str { "Display system processes" }
+    fn description(&self) -> &'static str {
+        "Provides a dynamic real-time view of a running system."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(_state: &SystemState) -> String {
     let mut out = String::new();
@@ -0,0 +1,47 @@
+use crate::commands::{self, Command, Stdio};
+use crate::completer;
+use crate::fs;
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Touch;
+
+impl Command for Touch {
+    fn name(&self) -> &'static str { "touch" }
+    fn synopsis(&self) -> &'static str { "touch file..." }
+    fn summary(&self) -> &'static str { "Create empty files" }
+    fn description(&self) -> &'static str {
+        "Creates each file that does not exist yet, empty. Files can be made in /tmp and in your home directory, e.g. touch ~/notes/todo; the rest of the tree is read-only."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&mut state, args) {
+            Ok(()) => ProcessResult::simple(String::new(), state),
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<(), String> {
+    let (_, operands) = commands::split_flags("touch", args, "")?;
+    if operands.is_empty() {
+        return Err("touch: missing file operand".to_string());
+    }
+    let mut tree = fs::tree_for_writing(state);
+    let mut errors = Vec::new();
+    for operand in operands {
+        let path = fs::resolve_path(&state.cwd, operand);
+        // Times are not kept, so touching a directory changes nothing
+        if tree.is_dir(&path) {
+            continue;
+        }
+        if let Err(reason) = fs::create_file(state, &mut tree, &path) {
+            errors.push(format!("touch: cannot touch '{}': {}", operand, reason));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
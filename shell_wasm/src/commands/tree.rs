@@ -0,0 +1,105 @@
+use crate::commands::{Command, Stdio};
+use crate::completer;
+use crate::fs::{self, Inode};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+use crate::WasmLine;
+
+pub struct Tree;
+
+impl Command for Tree {
+    fn name(&self) -> &'static str { "tree" }
+    fn synopsis(&self) -> &'static str { "tree [-a] [-L depth] [path]" }
+    fn summary(&self) -> &'static str { "Draw the directory hierarchy" }
+    fn description(&self) -> &'static str {
+        "Draws everything under path, the working directory by default, as a tree, then counts the directories and files in it. -L stops depth levels down and -a includes names starting with a dot. e.g. tree -L 2 /"
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        completer::complete_path(state, partial)
+    }
+
+    fn run(&self, state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        match handle(&state, args) {
+            Ok(lines) => ProcessResult { lines, ..ProcessResult::simple(String::new(), state) },
+            Err(e) => ProcessResult::failure(e, state),
+        }
+    }
+}
+
+/// The drawing, one output line per entry, typed like `ls` entries.
+pub fn handle(state: &SystemState, args: &[String]) -> Result<Vec<WasmLine>, String> {
+    let mut all = false;
+    let mut max_depth = usize::MAX;
+    let mut operand = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-a" => all = true,
+            "-L" => {
+                let level = args.next().ok_or("tree: -L needs a depth")?;
+                max_depth = level.parse().ok().filter(|&depth| depth > 0)
+                    .ok_or_else(|| format!("tree: invalid level, must be greater than 0: {}", level))?;
+            }
+            _ if arg.starts_with('-') => return Err(format!("tree: invalid option: {}\nUsage: tree [-a] [-L depth] [path]", arg)),
+            _ if operand.is_none() => operand = Some(arg.as_str()),
+            _ => return Err("tree: too many arguments".to_string()),
+        }
+    }
+
+    let operand = operand.unwrap_or(&state.cwd);
+    let path = fs::resolve_path(&state.cwd, operand);
+    let tree = fs::tree(state);
+    let root = tree.lookup(&path).ok_or_else(|| format!("tree: {}: No such file or directory", operand))?;
+
+    let mut drawing = Drawing { tree: &tree, all, max_depth, lines: vec![entry(operand.to_string(), root)], dirs: 0, files: 0 };
+    if root.is_dir() {
+        drawing.draw(&path, "", 1);
+    }
+    let mut lines = drawing.lines;
+    let dirs = if drawing.dirs == 1 { "directory" } else { "directories" };
+    let files = if drawing.files == 1 { "file" } else { "files" };
+    lines.push(WasmLine { text: String::new(), line_type: "regular".to_string() });
+    lines.push(WasmLine { text: format!("{} {}, {} {}", drawing.dirs, dirs, drawing.files, files), line_type: "regular".to_string() });
+    Ok(lines)
+}
+
+fn entry(text: String, inode: &Inode) -> WasmLine {
+    WasmLine { text, line_type: if inode.is_dir() { "ls-dir" } else { "ls-file" }.to_string() }
+}
+
+/// The lines drawn so far, and what they hold.
+struct Drawing<'a> {
+    tree: &'a fs::Tree,
+    all: bool,
+    max_depth: usize,
+    lines: Vec<WasmLine>,
+    dirs: usize,
+    files: usize,
+}
+
+impl Drawing<'_> {
+    /// Draws the entries of the directory at `path`, `depth` levels down,
+    /// each line starting with `indent`.
+    fn draw(&mut self, path: &str, indent: &str, depth: usize) {
+        let mut entries = self.tree.entries(path).unwrap_or_default();
+        entries.retain(|(name, _)| self.all || !name.starts_with('.'));
+        entries.sort_by_key(|(name, _)| *name);
+
+        for (i, (name, inode)) in entries.iter().enumerate() {
+            let last = i + 1 == entries.len();
+            let branch = if last { "└── " } else { "├── " };
+            self.lines.push(entry(format!("{}{}{}", indent, branch, name), inode));
+            if !inode.is_dir() {
+                self.files += 1;
+                continue;
+            }
+            self.dirs += 1;
+            if depth < self.max_depth {
+                let child = if path == "/" { format!("/{}", name) } else { format!("{}/{}", path, name) };
+                let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+                self.draw(&child, &indent, depth + 1);
+            }
+        }
+    }
+}
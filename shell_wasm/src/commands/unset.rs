@@ -0,0 +1,38 @@
+use crate::expand;
+use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Unset;
+
+impl Command for Unset {
+    fn name(&self) -> &'static str { "unset" }
+    fn synopsis(&self) -> &'static str { "unset NAME..." }
+    fn summary(&self) -> &'static str { "Remove a variable" }
+    fn description(&self) -> &'static str {
+        "Removes each NAME. USER, HOME, PWD, HOSTNAME and SHELL are read-only."
+    }
+
+    fn complete(&self, state: &SystemState, _args: &[String], partial: &str) -> Vec<String> {
+        expand::complete_name(state, partial)
+    }
+
+    fn run(&self, mut state: SystemState, args: &[String], _stdio: Stdio) -> ProcessResult {
+        let output = handle(&mut state, args);
+        ProcessResult::from_result(output, state)
+    }
+}
+
+pub fn handle(state: &mut SystemState, args: &[String]) -> Result<String, String> {
+    let mut errors = Vec::new();
+    for name in args {
+        if expand::BUILTIN_VARS.contains(&name.as_str()) {
+            errors.push(format!("unset: {}: cannot unset: readonly variable", name));
+        } else if !expand::is_valid_name(name) {
+            errors.push(format!("unset: `{}': not a valid identifier", name));
+        } else {
+            state.variables.remove(name);
+        }
+    }
+    if errors.is_empty() { Ok(String::new()) } else { Err(errors.join("\n")) }
+}
@@ -1,4 +1,23 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Uptime;
+
+impl Command for Uptime {
+    fn name(&self) -> &'static str { "uptime" }
+    fn synopsis(&self) -> &'static str { "uptime" }
+    fn summary(&self) -> &'static str { "System availability timer" }
+    fn description(&self) -> &'static str {
+        "Shows how long the node has been online."
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        // Screenfuls in the BBS wait for a key before the menu returns
+        state.pause();
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(state: &SystemState) -> String {
     format!("up {}, 4 users, load average: {}", state.system_info.uptime, state.system_info.load_average)
@@ -0,0 +1,47 @@
+use crate::commands::{Command, lines, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Wc;
+
+impl Command for Wc {
+    fn name(&self) -> &'static str { "wc" }
+    fn synopsis(&self) -> &'static str { "... | wc [-l] [-w] [-c]" }
+    fn summary(&self) -> &'static str { "Count lines, words and bytes of piped input" }
+    fn description(&self) -> &'static str {
+        "Counts the lines, words and bytes of its input, e.g. cat bio | wc -w."
+    }
+
+    fn run(&self, state: SystemState, args: &[String], stdio: Stdio) -> ProcessResult {
+        ProcessResult::from_result(handle(args, stdio.stdin.as_deref()), state)
+    }
+}
+
+pub fn handle(args: &[String], stdin: Option<&str>) -> Result<String, String> {
+    let input = match stdin {
+        Some(text) => text,
+        None => return Err("wc: no input (try: cat bio | wc -w)".to_string()),
+    };
+
+    let line_count = lines(input).len();
+    let word_count = input.split_whitespace().count();
+    let byte_count = input.len();
+
+    let mut columns = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-l" => columns.push(line_count),
+            "-w" => columns.push(word_count),
+            "-c" => columns.push(byte_count),
+            _ => return Err(format!("wc: invalid option -- '{}'\nUsage: wc [-l] [-w] [-c]", arg.trim_start_matches('-'))),
+        }
+    }
+
+    if columns.is_empty() {
+        return Ok(format!("{:>7} {:>7} {:>7}", line_count, word_count, byte_count));
+    }
+    if columns.len() == 1 {
+        return Ok(columns[0].to_string());
+    }
+    Ok(columns.iter().map(|c| format!("{:>7}", c)).collect::<Vec<_>>().join(" "))
+}
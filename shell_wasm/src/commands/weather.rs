@@ -1,3 +1,25 @@
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+use crate::state::SystemState;
+
+pub struct Weather;
+
+impl Command for Weather {
+    fn name(&self) -> &'static str { "weather" }
+    fn aliases(&self) -> &'static [&'static str] { &["climate"] }
+    fn synopsis(&self) -> &'static str { "weather" }
+    fn summary(&self) -> &'static str { "Simulated weather report" }
+    fn description(&self) -> &'static str {
+        "Prints a simulated weather report for the node."
+    }
+
+    fn run(&self, mut state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        // Screenfuls in the BBS wait for a key before the menu returns
+        state.pause();
+        ProcessResult::simple(handle(), state)
+    }
+}
+
 pub fn handle() -> String {
     "\nWEATHER: 24C | SUNNY\n".to_string()
 }
@@ -1,4 +1,21 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Who;
+
+impl Command for Who {
+    fn name(&self) -> &'static str { "who" }
+    fn synopsis(&self) -> &'static str { "who" }
+    fn summary(&self) -> &'static str { "List online users" }
+    fn description(&self) -> &'static str {
+        "Shows who is currently logged on to the tecnoter node."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(_state: &SystemState) -> String {
     let mut out = String::new();
@@ -1,4 +1,21 @@
 use crate::state::SystemState;
+use crate::commands::{Command, Stdio};
+use crate::shell::ProcessResult;
+
+pub struct Whoami;
+
+impl Command for Whoami {
+    fn name(&self) -> &'static str { "whoami" }
+    fn synopsis(&self) -> &'static str { "whoami" }
+    fn summary(&self) -> &'static str { "Display system user info" }
+    fn description(&self) -> &'static str {
+        "Shows the user, host and shell of the current session."
+    }
+
+    fn run(&self, state: SystemState, _args: &[String], _stdio: Stdio) -> ProcessResult {
+        ProcessResult::simple(handle(&state), state)
+    }
+}
 
 pub fn handle(state: &SystemState) -> String {
     format!("User: {}\nHost: tecnoter.io\nShell: ttsh-rust\nStatus: AUTHENTICATED\nProtocol: ENCRYPTED-WASM/1.0", state.current_user)
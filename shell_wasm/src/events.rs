@@ -0,0 +1,57 @@
+//! Output that arrives after a command line returned. Background work runs
+//! as a [job](crate::jobs), and queues what it produces here as events tagged
+//! with that job. The host drains the queue once the platform tells it there
+//! is something new, or right after running a line.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use serde::Serialize;
+use crate::{jobs, platform};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// The job that produced it.
+    pub job: u32,
+    /// The command line the job was started by.
+    pub command: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EventKind {
+    /// Text for the terminal.
+    Stdout { text: String },
+    /// An error message for the terminal.
+    Stderr { text: String },
+    /// New contents of a user file written by a background `>` or `>>`.
+    Write { path: String, text: String },
+    /// How far the job got, replacing its previous progress line. Not part
+    /// of its output: the line goes away once the job is done.
+    Progress { text: String },
+    /// The job finished, or was killed, with this exit status; always its last event.
+    Done { status: i32 },
+}
+
+thread_local! {
+    static QUEUE: RefCell<VecDeque<Event>> = const { RefCell::new(VecDeque::new()) };
+}
+
+pub fn push(event: Event) {
+    QUEUE.with(|queue| queue.borrow_mut().push_back(event));
+}
+
+/// Reports how far the job being polled got, if there is one.
+pub fn progress(text: String) {
+    let Some(job) = jobs::current() else { return };
+    let command = jobs::command(job).unwrap_or_default();
+    push(Event { job, command, kind: EventKind::Progress { text } });
+    platform::current().notify();
+}
+
+/// Everything queued so far, oldest first.
+pub fn drain() -> Vec<Event> {
+    QUEUE.with(|queue| queue.borrow_mut().drain(..).collect())
+}
@@ -0,0 +1,362 @@
+//! Tilde expansion of a leading `~`, parameter expansion (`$NAME`, `${NAME}`,
+//! `${NAME:-default}` and `$?`), then pathname expansion of unquoted `*`,
+//! `?` and `[...]`.
+//!
+//! Expansion runs on parsed words right before a command executes, so that
+//! `cd /posts` followed by `echo $PWD` sees the new directory.
+
+use crate::fs;
+use crate::glob;
+use crate::parser::{Quote, Word};
+use crate::state::SystemState;
+
+/// Variables derived from the session. They cannot be assigned or unset.
+pub const BUILTIN_VARS: [&str; 5] = ["HOME", "HOSTNAME", "PWD", "SHELL", "USER"];
+
+fn builtin(state: &SystemState, name: &str) -> Option<String> {
+    match name {
+        "HOME" => Some(fs::home_dir(state)),
+        "HOSTNAME" => Some(state.system_info.node_name.clone()),
+        "PWD" => Some(state.cwd.clone()),
+        "SHELL" => Some("/bin/ttsh".to_string()),
+        "USER" => Some(state.current_user.clone()),
+        _ => None,
+    }
+}
+
+pub fn lookup(state: &SystemState, name: &str) -> Option<String> {
+    if name == "?" {
+        return Some(state.last_status.to_string());
+    }
+    builtin(state, name).or_else(|| state.variables.get(name).map(|v| v.value.clone()))
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Sets a user variable, keeping its exported flag unless `export` is given.
+pub fn assign(state: &mut SystemState, name: &str, value: Option<String>, export: bool) -> Result<(), String> {
+    if !is_valid_name(name) {
+        return Err(format!("`{}': not a valid identifier", name));
+    }
+    if BUILTIN_VARS.contains(&name) {
+        return Err(format!("{}: readonly variable", name));
+    }
+    let var = state.variables.entry(name.to_string()).or_default();
+    if let Some(value) = value {
+        var.value = value;
+    }
+    var.exported |= export;
+    Ok(())
+}
+
+/// All variables as `(name, value, exported)`, sorted by name. Built-ins count as exported.
+pub fn all(state: &SystemState) -> Vec<(String, String, bool)> {
+    let mut vars: Vec<(String, String, bool)> = BUILTIN_VARS.iter()
+        .map(|name| (name.to_string(), builtin(state, name).unwrap_or_default(), true))
+        .collect();
+    vars.extend(state.variables.iter().map(|(name, var)| (name.clone(), var.value.clone(), var.exported)));
+    vars.sort_by(|a, b| a.0.cmp(&b.0));
+    vars
+}
+
+/// Variable names starting with `partial`, for completing `export` and `unset`.
+pub fn complete_name(state: &SystemState, partial: &str) -> Vec<String> {
+    all(state).into_iter().map(|(name, _, _)| name).filter(|name| name.starts_with(partial)).collect()
+}
+
+/// The directory a tilde prefix at the start of `text` stands for, and the
+/// length of that prefix: `~` is `$HOME`, `~user` the home of `user`, `~+`
+/// `$PWD` and `~-` `$OLDPWD`. The prefix runs up to the first `/`.
+pub fn tilde(state: &SystemState, text: &str) -> Option<(String, usize)> {
+    let rest = text.strip_prefix('~')?;
+    let end = rest.find('/').unwrap_or(rest.len());
+    let dir = match &rest[..end] {
+        "" => fs::home_dir(state),
+        "+" => state.cwd.clone(),
+        "-" => lookup(state, "OLDPWD")?,
+        user => {
+            let home = format!("/home/{}", user);
+            // Like `~nobody` in other shells, users without a home stay as typed
+            fs::tree(state).is_dir(&home).then_some(home)?
+        }
+    };
+    Some((dir, end + 1))
+}
+
+/// `text` with its tilde prefix, if any, expanded.
+pub fn expand_tilde(state: &SystemState, text: &str) -> String {
+    match tilde(state, text) {
+        Some((dir, len)) => format!("{}{}", dir, &text[len..]),
+        None => text.to_string(),
+    }
+}
+
+/// Splits a `NAME=value` word into its name and the word holding the value.
+/// Only an unquoted name followed by `=` counts, as in POSIX shells.
+pub fn split_assignment(word: &Word) -> Option<(String, Word)> {
+    let first = word.parts.first()?;
+    if first.quote != Quote::None {
+        return None;
+    }
+    let (name, rest) = first.text.split_once('=')?;
+    if !is_valid_name(name) {
+        return None;
+    }
+    let mut value = word.clone();
+    value.parts[0].text = rest.to_string();
+    Some((name.to_string(), value))
+}
+
+/// Expands the parameters in `text`, reading from `chars` positioned after a `$`.
+fn expand_parameter(state: &SystemState, chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<String> {
+    match chars.peek().copied() {
+        Some('?') => {
+            chars.next();
+            lookup(state, "?")
+        }
+        Some('{') => {
+            chars.next();
+            let mut inner = String::new();
+            for c in chars.by_ref() {
+                if c == '}' {
+                    break;
+                }
+                inner.push(c);
+            }
+            Some(expand_braced(state, &inner))
+        }
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            Some(lookup(state, &name).unwrap_or_default())
+        }
+        // A lone `$` stays literal
+        _ => None,
+    }
+}
+
+/// Handles the inside of `${...}`: a name, optionally followed by `:-word` or `-word`.
+fn expand_braced(state: &SystemState, inner: &str) -> String {
+    if let Some((name, default)) = inner.split_once(":-") {
+        return match lookup(state, name) {
+            Some(value) if !value.is_empty() => value,
+            _ => expand_text(state, default),
+        };
+    }
+    if let Some((name, default)) = inner.split_once('-') {
+        return lookup(state, name).unwrap_or_else(|| expand_text(state, default));
+    }
+    lookup(state, inner).unwrap_or_default()
+}
+
+/// Expands every parameter in `text`, without field splitting.
+pub fn expand_text(state: &SystemState, text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        match expand_parameter(state, &mut chars) {
+            Some(value) => out.push_str(&value),
+            None => out.push('$'),
+        }
+    }
+    out
+}
+
+/// A field being built. `pattern` holds the same text with quoted glob
+/// characters escaped, so only unquoted ones take part in pathname expansion.
+#[derive(Default)]
+struct Field {
+    text: String,
+    pattern: String,
+}
+
+impl Field {
+    fn push_str(&mut self, text: &str, quoted: bool) {
+        self.text.push_str(text);
+        if quoted {
+            self.pattern.push_str(&glob::escape(text));
+        } else {
+            self.pattern.push_str(text);
+        }
+    }
+}
+
+/// Splits a word into fields after parameter expansion.
+///
+/// Expansions outside quotes are split on whitespace and vanish when empty;
+/// inside double quotes they stay a single field. Single-quoted text is literal.
+fn split_fields(state: &SystemState, word: &Word) -> Vec<Field> {
+    let mut fields = Vec::new();
+    let mut current = Field::default();
+    // Quoted text (even "") forces a field to exist
+    let mut has_field = false;
+
+    // A tilde prefix must be unquoted up to its `/` or the end of the word
+    let mut expanded = 0;
+    if let Some(first) = word.parts.first() {
+        if first.quote == Quote::None && (word.parts.len() == 1 || first.text.contains('/')) {
+            if let Some((dir, len)) = tilde(state, &first.text) {
+                current.push_str(&dir, true);
+                has_field = true;
+                expanded = len;
+            }
+        }
+    }
+
+    for (i, part) in word.parts.iter().enumerate() {
+        match part.quote {
+            Quote::Single => {
+                current.push_str(&part.text, true);
+                has_field = true;
+            }
+            Quote::Double => {
+                current.push_str(&expand_text(state, &part.text), true);
+                has_field = true;
+            }
+            Quote::None => {
+                let text = if i == 0 { &part.text[expanded..] } else { &part.text };
+                let mut chars = text.chars().peekable();
+                while let Some(c) = chars.next() {
+                    if c != '$' {
+                        current.push_str(c.encode_utf8(&mut [0; 4]), false);
+                        has_field = true;
+                        continue;
+                    }
+                    let value = match expand_parameter(state, &mut chars) {
+                        Some(value) => value,
+                        None => {
+                            current.push_str("$", false);
+                            has_field = true;
+                            continue;
+                        }
+                    };
+                    let mut pieces = value.split_whitespace().peekable();
+                    if value.starts_with(char::is_whitespace) && has_field {
+                        fields.push(std::mem::take(&mut current));
+                        has_field = false;
+                    }
+                    while let Some(piece) = pieces.next() {
+                        current.push_str(piece, false);
+                        has_field = true;
+                        if pieces.peek().is_some() {
+                            fields.push(std::mem::take(&mut current));
+                        }
+                    }
+                    if value.ends_with(char::is_whitespace) && has_field {
+                        fields.push(std::mem::take(&mut current));
+                        has_field = false;
+                    }
+                }
+            }
+        }
+    }
+    if has_field {
+        fields.push(current);
+    }
+    fields
+}
+
+/// Expands a word into zero or more fields.
+///
+/// A field with unquoted wildcards becomes the sorted list of matching paths;
+/// when nothing matches it is kept as written, as in POSIX shells.
+pub fn expand_word(state: &SystemState, word: &Word) -> Vec<String> {
+    split_fields(state, word).into_iter()
+        .flat_map(|field| {
+            let matches = if glob::has_magic(&field.pattern) { glob::expand(state, &field.pattern) } else { vec![] };
+            if matches.is_empty() { vec![field.text] } else { matches }
+        })
+        .collect()
+}
+
+/// Expands a word into a single string, as for the value of `NAME=value`.
+pub fn expand_value(state: &SystemState, word: &Word) -> String {
+    word.parts.iter()
+        .map(|part| match part.quote {
+            Quote::Single => part.text.clone(),
+            _ => expand_text(state, &part.text),
+        })
+        .collect()
+}
+
+/// Expands a list of words into the final argument vector.
+pub fn expand_words(state: &SystemState, words: &[Word]) -> Vec<String> {
+    words.iter().flat_map(|w| expand_word(state, w)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    /// The arguments `line` expands to.
+    fn expand(state: &SystemState, line: &str) -> Vec<String> {
+        let script = parser::parse(line).unwrap();
+        expand_words(state, &script.lists[0].first.commands[0].words)
+    }
+
+    fn with_vars(vars: &[(&str, &str)]) -> SystemState {
+        let mut state = SystemState { current_user: "guest".to_string(), cwd: "/posts".to_string(), ..Default::default() };
+        for (name, value) in vars {
+            assign(&mut state, name, Some(value.to_string()), false).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn defaults_apply_to_unset_and_empty_variables() {
+        let state = with_vars(&[("EMPTY", ""), ("NAME", "ttsh")]);
+        assert_eq!(expand(&state, "echo ${NAME:-x} ${UNSET:-x} ${EMPTY:-x}"), ["echo", "ttsh", "x", "x"]);
+        assert_eq!(expand(&state, "echo ${UNSET-x} \"${EMPTY-x}\""), ["echo", "x", ""]);
+        assert_eq!(expand(&state, "echo \"${UNSET:-two words}\""), ["echo", "two words"]);
+        assert_eq!(expand(&state, "echo ${NAME}s $NAME.sh '$NAME'"), ["echo", "ttshs", "ttsh.sh", "$NAME"]);
+    }
+
+    #[test]
+    fn status_of_the_last_pipeline() {
+        let mut state = with_vars(&[]);
+        state.last_status = 127;
+        assert_eq!(expand(&state, "echo $? \"$?\" x$?"), ["echo", "127", "127", "x127"]);
+    }
+
+    #[test]
+    fn tilde_prefixes_name_directories() {
+        let state = with_vars(&[("OLDPWD", "/tags")]);
+        assert_eq!(expand(&state, "ls ~ ~/notes ~+ ~- ~guest/a"), ["ls", "/home/guest", "/home/guest/notes", "/posts", "/tags", "/home/guest/a"]);
+        assert_eq!(expand(&state, "ls ~nobody '~' \"~\" a~"), ["ls", "~nobody", "~", "~", "a~"]);
+        assert_eq!(expand_tilde(&with_vars(&[]), "~-/x"), "~-/x");
+    }
+
+    #[test]
+    fn unquoted_expansions_split_into_fields() {
+        let state = with_vars(&[("WORDS", "  a  b\tc "), ("EMPTY", "")]);
+        assert_eq!(expand(&state, "echo $WORDS"), ["echo", "a", "b", "c"]);
+        assert_eq!(expand(&state, "echo \"$WORDS\""), ["echo", "  a  b\tc "]);
+        assert_eq!(expand(&state, "echo x$WORDS"), ["echo", "x", "a", "b", "c"]);
+        assert_eq!(expand(&state, "echo $EMPTY \"$EMPTY\" ''"), ["echo", "", ""]);
+    }
+
+    #[test]
+    fn assignments_need_a_valid_unquoted_name() {
+        let mut state = with_vars(&[]);
+        assert_eq!(assign(&mut state, "HOME", Some("/".to_string()), false), Err("HOME: readonly variable".to_string()));
+        assert_eq!(assign(&mut state, "1X", None, false), Err("`1X': not a valid identifier".to_string()));
+        let words = &parser::parse("A=1 'B=2'").unwrap().lists[0].first.commands[0].words;
+        assert_eq!(split_assignment(&words[0]).map(|(name, value)| (name, value.text())), Some(("A".to_string(), "1".to_string())));
+        assert!(split_assignment(&words[1]).is_none());
+    }
+}
@@ -1,32 +1,549 @@
-pub fn get_directory_contents(path: &str) -> Option<Vec<&'static str>> {
-    web_sys::console::log_1(&format!("fs: get_directory_contents input='{}'", path).into());
-    match path {
-        "/" => Some(vec!["posts", "pages", "tags", "categories"]),
-        "/posts" | "/pages" | "/tags" | "/categories" => Some(vec![]),
-        p if p.starts_with("/tags/") || p.starts_with("/categories/") => Some(vec![]),
-        _ => None,
+//! The file system: an inode tree built from the catalog and the user's files.
+//!
+//! Every post and page lives in the directory of its URL, so Hugo sections
+//! and nested page bundles become directories of their own. `/tags` and
+//! `/categories` hold one directory per term, whose entries are links to the
+//! same inodes. All of that is read-only: users write only in `/tmp` and
+//! `/home/<user>`, an overlay of the files and directories in `state.files`
+//! and `state.dirs`.
+//!
+//! Building the tree walks the whole catalog, so it is kept for as long as
+//! the state it was built from stays the same.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use crate::platform;
+use crate::state::{Metadata, SystemState};
+
+/// Size shown for directories.
+const DIR_SIZE: usize = 4096;
+/// Modification date of directories.
+const SITE_DATE: &str = "2026-01-01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Directory,
+    File,
+}
+
+/// Where the contents of an inode come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A directory.
+    None,
+    /// `state.posts[i]`, fetched from the site.
+    Post(usize),
+    /// `state.pages[i]`, fetched from the site.
+    Page(usize),
+    /// The user file at this path, in `state.files`.
+    User(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Inode {
+    pub kind: Kind,
+    pub size: usize,
+    /// Last modification, as the catalog or the system gives it (e.g. `2026-10-01`).
+    pub mtime: String,
+    pub source: Source,
+    /// Names and inode numbers of a directory's entries, in listing order.
+    entries: Vec<(String, usize)>,
+}
+
+impl Inode {
+    fn dir() -> Self {
+        Self { kind: Kind::Directory, size: DIR_SIZE, mtime: SITE_DATE.to_string(), source: Source::None, entries: Vec::new() }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind == Kind::Directory
     }
 }
 
+/// A post or a page of the catalog.
+pub struct Item<'a> {
+    pub source: Source,
+    pub title: &'a str,
+    pub slug: &'a str,
+    pub url: &'a str,
+    pub tags: &'a [String],
+    pub categories: &'a [String],
+    pub meta: &'a Metadata,
+}
+
+/// Every post, then every page.
+pub fn items(state: &SystemState) -> impl Iterator<Item = Item<'_>> {
+    let posts = state.posts.iter().enumerate().map(|(i, p)| Item {
+        source: Source::Post(i), title: &p.title, slug: &p.slug, url: &p.url,
+        tags: &p.tags, categories: &p.categories, meta: &p.meta,
+    });
+    let pages = state.pages.iter().enumerate().map(|(i, p)| Item {
+        source: Source::Page(i), title: &p.title, slug: &p.slug, url: &p.url,
+        tags: &p.tags, categories: &p.categories, meta: &p.meta,
+    });
+    posts.chain(pages)
+}
+
+/// The post or page an inode holds.
+pub fn item<'a>(state: &'a SystemState, source: &Source) -> Option<Item<'a>> {
+    let i = match source {
+        Source::Post(i) => *i,
+        Source::Page(i) => state.posts.len() + i,
+        _ => return None,
+    };
+    items(state).nth(i)
+}
+
+/// The directory an item lives in: its URL without the last segment, or the
+/// section of its list when the URL has no directory.
+fn item_dir(item: &Item) -> String {
+    let path = item.url.split(['?', '#']).next().unwrap_or("").trim_end_matches('/');
+    match path.rfind('/') {
+        Some(idx) if idx > 0 => path[..idx].to_string(),
+        _ => match item.source {
+            Source::Post(_) => "/posts".to_string(),
+            _ => "/pages".to_string(),
+        },
+    }
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// A date as the host gives it (`Sun Oct 18 2026`), written like the
+/// catalog's (`2026-10-18`). Anything else is returned as it is.
+fn iso_date(date: &str) -> String {
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    match parts[..] {
+        [_, month, day, year, ..] => match MONTHS.iter().position(|&m| m == month) {
+            Some(i) => format!("{}-{:02}-{}", year, i + 1, day),
+            None => date.to_string(),
+        },
+        _ => date.to_string(),
+    }
+}
+
+/// How deep the absolute `path` is in `/tags` or `/categories`: 0 for the
+/// directory itself, 1 for a term, 2 for a post or page filed under it,
+/// which is a link to an inode that lives in its section.
+pub fn taxonomy_depth(path: &str) -> Option<usize> {
+    let mut parts = components(path);
+    matches!(parts.next(), Some("tags" | "categories")).then(|| parts.count())
+}
+
+/// Whether `path`, reached from `start`, is a post or page filed under a tag
+/// or category, which a search from `start` also finds in its section.
+pub fn linked_elsewhere(start: &str, path: &str) -> bool {
+    taxonomy_depth(start).is_none() && taxonomy_depth(path).is_some_and(|depth| depth >= 2)
+}
+
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|c| !c.is_empty())
+}
+
+#[derive(Clone)]
+pub struct Tree {
+    /// Inode 0 is the root.
+    inodes: Vec<Inode>,
+}
+
+impl Tree {
+    pub fn build(state: &SystemState) -> Self {
+        let mut tree = Tree { inodes: vec![Inode::dir()] };
+        let items: Vec<Item> = items(state).collect();
+
+        // Sections, and the directories of nested bundles, before any file
+        tree.mkdir("/posts");
+        tree.mkdir("/pages");
+        let dirs: Vec<String> = items.iter().map(item_dir).collect();
+        for dir in &dirs {
+            tree.mkdir(dir);
+        }
+        tree.mkdir("/tags");
+        tree.mkdir("/categories");
+        tree.mkdir("/tmp");
+        tree.mkdir(&home_dir(state));
+        for dir in &state.dirs {
+            tree.mkdir(dir);
+        }
+
+        let mut files = Vec::new();
+        for (item, dir) in items.iter().zip(&dirs) {
+            let file = tree.push(Inode {
+                kind: Kind::File,
+                size: item.meta.size,
+                mtime: item.meta.modified().to_string(),
+                source: item.source.clone(),
+                entries: Vec::new(),
+            });
+            files.push(file);
+            let Some(mut parent) = tree.find(dir) else { continue };
+            let mut name = item.slug;
+            // A bundle other pages live under is the index of its directory
+            if let Some(bundle) = tree.child(parent, name).filter(|&bundle| tree.inodes[bundle].is_dir()) {
+                parent = bundle;
+                name = "index";
+            }
+            tree.link(parent, name, file);
+        }
+        tree.index("/tags", &items, &files, |item| item.tags);
+        tree.index("/categories", &items, &files, |item| item.categories);
+
+        for (path, text) in &state.files {
+            tree.add_file(state, path, text.len());
+        }
+        tree
+    }
+
+    /// Adds the user file at `path`, making its directory if needed, unless
+    /// something has that name already.
+    fn add_file(&mut self, state: &SystemState, path: &str, size: usize) {
+        let dir = self.mkdir(parent_dir(path));
+        let inode = self.push(Inode {
+            kind: Kind::File,
+            size,
+            mtime: iso_date(&state.system_info.current_date),
+            source: Source::User(path.to_string()),
+            entries: Vec::new(),
+        });
+        self.link(dir, file_name(path), inode);
+    }
+
+    /// Takes the entry at `path` out of its directory.
+    fn unlink(&mut self, path: &str) {
+        if let Some(dir) = self.find(parent_dir(path)) {
+            self.inodes[dir].entries.retain(|(name, _)| name != file_name(path));
+        }
+    }
+
+    /// Moves the entry at `from` to `to`, replacing what is there, and the
+    /// user files under it along.
+    fn relink(&mut self, from: &str, to: &str) {
+        let Some(inode) = self.find(from) else { return };
+        self.unlink(to);
+        self.unlink(from);
+        if let Some(dir) = self.find(parent_dir(to)) {
+            self.link(dir, file_name(to), inode);
+        }
+        self.relocate(inode, from, to);
+    }
+
+    fn relocate(&mut self, inode: usize, from: &str, to: &str) {
+        if let Source::User(path) = &mut self.inodes[inode].source {
+            if let Some(rest) = path.strip_prefix(from) {
+                *path = format!("{}{}", to, rest);
+            }
+        }
+        let children: Vec<usize> = self.inodes[inode].entries.iter().map(|&(_, child)| child).collect();
+        for child in children {
+            self.relocate(child, from, to);
+        }
+    }
+
+    /// Makes a directory in `root` for every term, linking the items that have it.
+    fn index<'a>(&mut self, root: &str, items: &[Item<'a>], files: &[usize], terms: impl Fn(&Item<'a>) -> &'a [String]) {
+        let mut all: Vec<&String> = items.iter().flat_map(&terms).collect();
+        all.sort();
+        all.dedup();
+        for term in all {
+            let dir = self.mkdir(&format!("{}/{}", root, term));
+            for (item, &file) in items.iter().zip(files) {
+                if terms(item).contains(term) {
+                    self.link(dir, item.slug, file);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, inode: Inode) -> usize {
+        self.inodes.push(inode);
+        self.inodes.len() - 1
+    }
+
+    /// Adds `name` to the directory `dir`, unless something has that name already.
+    fn link(&mut self, dir: usize, name: &str, inode: usize) {
+        if self.child(dir, name).is_none() {
+            self.inodes[dir].entries.push((name.to_string(), inode));
+        }
+    }
+
+    /// Creates the directory at `path` and its parents, as `mkdir -p` does.
+    /// A file in the way is left alone, and the deepest directory returned.
+    fn mkdir(&mut self, path: &str) -> usize {
+        let mut dir = 0;
+        for name in components(path) {
+            dir = match self.child(dir, name) {
+                Some(child) if self.inodes[child].is_dir() => child,
+                Some(_) => return dir,
+                None => {
+                    let child = self.push(Inode::dir());
+                    self.link(dir, name, child);
+                    child
+                }
+            };
+        }
+        dir
+    }
+
+    fn child(&self, dir: usize, name: &str) -> Option<usize> {
+        self.inodes[dir].entries.iter().find(|(n, _)| n == name).map(|&(_, inode)| inode)
+    }
+
+    fn find(&self, path: &str) -> Option<usize> {
+        components(path).try_fold(0, |dir, name| self.child(dir, name))
+    }
+
+    /// The inode at the absolute `path`.
+    pub fn lookup(&self, path: &str) -> Option<&Inode> {
+        self.find(path).map(|inode| &self.inodes[inode])
+    }
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        self.lookup(path).is_some_and(Inode::is_dir)
+    }
+
+    /// The entries of the directory at the absolute `path`, or `None` when
+    /// it is not a directory.
+    pub fn entries(&self, path: &str) -> Option<Vec<(&str, &Inode)>> {
+        let dir = self.lookup(path).filter(|inode| inode.is_dir())?;
+        Some(dir.entries.iter().map(|(name, inode)| (name.as_str(), &self.inodes[*inode])).collect())
+    }
+
+    /// Everything under the absolute `path`, itself first, depth first in
+    /// listing order: each path with its depth below `path` and its inode.
+    /// Nothing deeper than `max_depth` is visited.
+    pub fn walk(&self, path: &str, max_depth: usize) -> Vec<(String, usize, &Inode)> {
+        let mut found = Vec::new();
+        if let Some(inode) = self.find(path) {
+            self.walk_from(path.to_string(), inode, 0, max_depth, &mut found);
+        }
+        found
+    }
+
+    fn walk_from<'a>(&'a self, path: String, inode: usize, depth: usize, max_depth: usize, found: &mut Vec<(String, usize, &'a Inode)>) {
+        let entries = &self.inodes[inode].entries;
+        let dir = path.trim_end_matches('/').to_string();
+        found.push((path, depth, &self.inodes[inode]));
+        if depth < max_depth {
+            for (name, child) in entries {
+                self.walk_from(format!("{}/{}", dir, name), *child, depth + 1, max_depth, found);
+            }
+        }
+    }
+
+    /// The names in the directory at the absolute `path`.
+    pub fn list(&self, path: &str) -> Option<Vec<String>> {
+        Some(self.entries(path)?.into_iter().map(|(name, _)| name.to_string()).collect())
+    }
+}
+
+thread_local! {
+    static TREE: RefCell<Option<(u64, Rc<Tree>)>> = const { RefCell::new(None) };
+}
+
+/// What the tree of `state` is built from.
+fn fingerprint(state: &SystemState) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.posts.len().hash(&mut hasher);
+    for item in items(state) {
+        (item.slug, item.url, item.meta.modified(), item.meta.size, item.tags, item.categories).hash(&mut hasher);
+    }
+    for (path, text) in &state.files {
+        (path, text.len()).hash(&mut hasher);
+    }
+    state.dirs.hash(&mut hasher);
+    (&state.current_user, &state.system_info.current_date).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The tree of `state`, built again only when the catalog, the user files
+/// or the user changed.
+pub fn tree(state: &SystemState) -> Rc<Tree> {
+    let key = fingerprint(state);
+    TREE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        match &*cache {
+            Some((built, tree)) if *built == key => tree.clone(),
+            _ => {
+                let tree = Rc::new(Tree::build(state));
+                platform::current().log(&format!("fs: built a tree of {} inodes", tree.inodes.len()));
+                *cache = Some((key, tree.clone()));
+                tree
+            }
+        }
+    })
+}
+
+/// A tree of `state` for a command that writes, which [`create_file`],
+/// [`save_file`], [`make_dir`], [`remove`] and [`rename`] keep up to date
+/// as it goes, so it is built once however many paths the command takes.
+pub fn tree_for_writing(state: &SystemState) -> Tree {
+    tree(state).as_ref().clone()
+}
+
+/// The absolute form of `path` seen from `current_cwd`, normalised: empty
+/// and `.` components are dropped, and `..` goes up a level, staying at `/`.
 pub fn resolve_path(current_cwd: &str, path: &str) -> String {
-    if path.starts_with('/') {
-        return path.to_string();
+    let base = if path.starts_with('/') { "" } else { current_cwd };
+    let mut parts = Vec::new();
+    for part in components(base).chain(components(path)) {
+        match part {
+            "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+pub fn home_dir(state: &SystemState) -> String {
+    format!("/home/{}", state.current_user)
+}
+
+/// Whether `dir` is one of the scratch areas users may write into, or
+/// somewhere under them.
+pub fn is_writable_dir(state: &SystemState, dir: &str) -> bool {
+    ["/tmp".to_string(), home_dir(state)].iter().any(|root| dir == root || dir.strip_prefix(root.as_str()).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// The last component of `path`.
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+pub fn parent_dir(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(idx) => &path[..idx],
     }
-    if path == ".." {
-        return "/".to_string();
+}
+
+pub fn read_file<'a>(state: &'a SystemState, path: &str) -> Option<&'a str> {
+    state.files.get(path).map(String::as_str)
+}
+
+/// Why the entry at the absolute `path` cannot be made or removed, if it cannot.
+fn check_parent(state: &SystemState, tree: &Tree, path: &str) -> Result<(), &'static str> {
+    let dir = parent_dir(path);
+    match tree.lookup(dir) {
+        None => Err("No such file or directory"),
+        Some(inode) if !inode.is_dir() => Err("Not a directory"),
+        Some(_) if !is_writable_dir(state, dir) => Err("Permission denied"),
+        Some(_) => Ok(()),
     }
-    if path == "." || path.is_empty() {
-        return current_cwd.to_string();
+}
+
+/// Why a user file cannot be written at the absolute `path`, if it cannot.
+/// Errors are the reason alone, e.g. `Permission denied`, as for
+/// [`create_file`], [`make_dir`], [`remove`] and [`rename`].
+pub fn check_writable(state: &SystemState, tree: &Tree, path: &str) -> Result<(), &'static str> {
+    match tree.lookup(path) {
+        Some(inode) if inode.is_dir() => Err("Is a directory"),
+        Some(Inode { source: Source::User(_), .. }) => Ok(()),
+        Some(_) => Err("Permission denied"),
+        None => check_parent(state, tree, path),
     }
-    
-    let mut new_path = if current_cwd == "/" {
-        format!("/{}", path)
-    } else {
-        format!("{}/{}", current_cwd, path)
-    };
+}
+
+/// Makes an empty user file at the absolute `path`, unless there is one.
+pub fn create_file(state: &mut SystemState, tree: &mut Tree, path: &str) -> Result<(), &'static str> {
+    check_writable(state, tree, path)?;
+    if !state.files.contains_key(path) {
+        state.files.insert(path.to_string(), String::new());
+        tree.add_file(state, path, 0);
+    }
+    Ok(())
+}
+
+/// Replaces the user file at the absolute `path` with `text`, creating it if needed.
+pub fn save_file(state: &mut SystemState, tree: &mut Tree, path: &str, text: String) -> Result<(), &'static str> {
+    create_file(state, tree, path)?;
+    if let Some(inode) = tree.find(path) {
+        tree.inodes[inode].size = text.len();
+    }
+    state.files.insert(path.to_string(), text);
+    Ok(())
+}
+
+/// Writes `text` to the user file at the absolute `path`, creating it if needed.
+/// Errors are phrased like the system call failures a shell would report.
+pub fn write_file(state: &mut SystemState, path: &str, text: &str, append: bool) -> Result<(), String> {
+    check_writable(state, &tree(state), path).map_err(|reason| format!("{}: {}", path, reason))?;
+    let file = state.files.entry(path.to_string()).or_default();
+    if !append {
+        file.clear();
+    }
+    file.push_str(text);
+    Ok(())
+}
+
+/// Makes the directory at the absolute `path`.
+pub fn make_dir(state: &mut SystemState, tree: &mut Tree, path: &str) -> Result<(), &'static str> {
+    if tree.lookup(path).is_some() {
+        return Err("File exists");
+    }
+    check_parent(state, tree, path)?;
+    state.dirs.insert(path.to_string());
+    tree.mkdir(path);
+    Ok(())
+}
+
+/// Removes the user file or directory at the absolute `path`, and with
+/// `recursive` everything under it.
+pub fn remove(state: &mut SystemState, tree: &mut Tree, path: &str, recursive: bool) -> Result<(), &'static str> {
+    let inode = tree.lookup(path).ok_or("No such file or directory")?;
+    check_parent(state, tree, path)?;
+    if inode.is_dir() && !recursive {
+        return Err("Is a directory");
+    }
+    let under = |other: &String| other == path || other.strip_prefix(path).is_some_and(|rest| rest.starts_with('/'));
+    state.files.retain(|other, _| !under(other));
+    state.dirs.retain(|other| !under(other));
+    tree.unlink(path);
+    Ok(())
+}
 
-    if new_path.ends_with('/') && new_path.len() > 1 {
-        new_path.pop();
+/// Moves the user file or directory at the absolute `from` to `to`,
+/// replacing a file or an empty directory there, as `rename(2)` does.
+pub fn rename(state: &mut SystemState, tree: &mut Tree, from: &str, to: &str) -> Result<(), &'static str> {
+    let source = tree.lookup(from).ok_or("No such file or directory")?;
+    check_parent(state, tree, from)?;
+    check_parent(state, tree, to)?;
+    if from == to {
+        return Ok(());
+    }
+    if to.strip_prefix(from).is_some_and(|rest| rest.starts_with('/')) {
+        return Err("Invalid argument");
+    }
+    match tree.lookup(to) {
+        Some(target) if target.is_dir() && !source.is_dir() => return Err("Is a directory"),
+        Some(target) if !target.is_dir() && source.is_dir() => return Err("Not a directory"),
+        Some(target) if target.is_dir() && !target.entries.is_empty() => return Err("Directory not empty"),
+        Some(_) => {
+            state.files.remove(to);
+            state.dirs.remove(to);
+        }
+        None => {}
+    }
+
+    let moved = |path: &String| match path.strip_prefix(from) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => Some(format!("{}{}", to, rest)),
+        _ => None,
+    };
+    let files: Vec<(String, String)> = state.files.keys().filter_map(|path| Some((path.clone(), moved(path)?))).collect();
+    for (old, new) in files {
+        if let Some(text) = state.files.remove(&old) {
+            state.files.insert(new, text);
+        }
+    }
+    let dirs: Vec<(String, String)> = state.dirs.iter().filter_map(|path| Some((path.clone(), moved(path)?))).collect();
+    for (old, new) in dirs {
+        state.dirs.remove(&old);
+        state.dirs.insert(new);
     }
-    new_path
+    tree.relink(from, to);
+    Ok(())
 }
@@ -0,0 +1,260 @@
+//! Pathname expansion: `*`, `?` and `[...]` matched against the virtual filesystem.
+//!
+//! Patterns use `\` to mark characters that must match literally; the
+//! expander escapes quoted text this way, so `'*'` and `\*` stay literal.
+
+use crate::fs;
+use crate::state::SystemState;
+
+fn is_special(c: char) -> bool {
+    matches!(c, '*' | '?' | '[' | '\\')
+}
+
+/// Escapes every glob character in `text`.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_special(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Removes the escapes from a pattern without wildcards.
+fn unescape(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether `pattern` contains an unescaped wildcard.
+pub fn has_magic(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Matches the bracket expression starting after `[` at `p[start]`.
+/// Returns whether `c` is in the set and the index after the closing `]`,
+/// or `None` when there is no closing `]` and the `[` is literal.
+fn match_bracket(p: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start;
+    let negate = matches!(p.get(i), Some('!' | '^'));
+    if negate {
+        i += 1;
+    }
+    let mut found = false;
+    let mut first = true;
+    loop {
+        let mut lo = *p.get(i)?;
+        // A `]` right after the `[` (or `[!`) is a member, not the end
+        if lo == ']' && !first {
+            return Some((found != negate, i + 1));
+        }
+        first = false;
+        if lo == '\\' {
+            i += 1;
+            lo = *p.get(i)?;
+        }
+        i += 1;
+        let mut hi = lo;
+        if p.get(i) == Some(&'-') && p.get(i + 1).is_some_and(|&h| h != ']') {
+            hi = p[i + 1];
+            i += 2;
+        }
+        if lo <= c && c <= hi {
+            found = true;
+        }
+    }
+}
+
+fn match_from(p: &[char], n: &[char]) -> bool {
+    let (mut pi, mut ni) = (0, 0);
+    // Where to resume after the last `*`: pattern index and name index
+    let mut star: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        let step = match p.get(pi) {
+            Some('*') => {
+                star = Some((pi + 1, ni));
+                pi += 1;
+                continue;
+            }
+            Some('?') => Some(pi + 1),
+            Some('[') => match match_bracket(p, pi + 1, n[ni]) {
+                Some((true, next)) => Some(next),
+                Some((false, _)) => None,
+                None => (n[ni] == '[').then_some(pi + 1),
+            },
+            Some('\\') => (p.get(pi + 1) == Some(&n[ni])).then_some(pi + 2),
+            Some(&c) => (c == n[ni]).then_some(pi + 1),
+            None => None,
+        };
+        match (step, star) {
+            (Some(next), _) => {
+                pi = next;
+                ni += 1;
+            }
+            // Let the last `*` swallow one more character and retry
+            (None, Some((star_pi, star_ni))) => {
+                pi = star_pi;
+                ni = star_ni + 1;
+                star = Some((star_pi, star_ni + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Whether the file name `name` matches `pattern`. A leading `.` must be
+/// matched explicitly, as in POSIX shells.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') && !pattern.starts_with("\\.") {
+        return false;
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    match_from(&p, &n)
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else if prefix.ends_with('/') {
+        format!("{}{}", prefix, name)
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// Paths matching `pattern`, written the way the pattern was (relative
+/// patterns give relative paths), sorted. Empty when nothing matches.
+pub fn expand(state: &SystemState, pattern: &str) -> Vec<String> {
+    let tree = fs::tree(state);
+    let mut paths = vec![if pattern.starts_with('/') { "/".to_string() } else { String::new() }];
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+
+    for (i, component) in components.iter().enumerate() {
+        let last = i + 1 == components.len();
+        let mut next = Vec::new();
+        for prefix in &paths {
+            if !has_magic(component) {
+                next.push(join(prefix, &unescape(component)));
+                continue;
+            }
+            let dir = fs::resolve_path(&state.cwd, if prefix.is_empty() { "." } else { prefix });
+            let Some(mut names) = tree.list(&dir) else { continue };
+            names.sort();
+            for name in names.into_iter().filter(|name| matches(component, name)) {
+                let path = join(prefix, &name);
+                // Only directories can hold the rest of the pattern
+                if last || tree.is_dir(&fs::resolve_path(&state.cwd, &path)) {
+                    next.push(path);
+                }
+            }
+        }
+        paths = next;
+    }
+
+    let mut found: Vec<String> = paths.into_iter()
+        .filter(|path| tree.lookup(&fs::resolve_path(&state.cwd, path)).is_some())
+        .collect();
+    if pattern.ends_with('/') && pattern.len() > 1 {
+        found.retain(|path| tree.is_dir(&fs::resolve_path(&state.cwd, path)));
+        for path in &mut found {
+            path.push('/');
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expand, parser};
+
+    fn home() -> SystemState {
+        let mut state = SystemState { current_user: "guest".to_string(), cwd: "/home/guest".to_string(), ..Default::default() };
+        for name in ["a.md", "b.md", "ab.txt", "[x]", ".profile", "notes/c.md"] {
+            state.files.insert(format!("/home/guest/{}", name), String::new());
+        }
+        state
+    }
+
+    #[test]
+    fn bracket_expressions_match_sets_and_ranges() {
+        assert!(matches("[abc]x", "bx"));
+        assert!(matches("[a-c]x", "cx"));
+        assert!(!matches("[a-c]x", "dx"));
+        assert!(matches("[!a-c]x", "dx"));
+        assert!(matches("[^a-c]x", "dx"));
+        assert!(!matches("[!a-c]x", "ax"));
+        assert!(matches("[]a]", "]"));
+        assert!(matches("[!]]", "a"));
+        assert!(matches("[a-]", "-"));
+        assert!(matches("[\\]]", "]"));
+        // Without a closing `]` the `[` is an ordinary character
+        assert!(matches("[ab", "[ab"));
+    }
+
+    #[test]
+    fn stars_backtrack() {
+        assert!(matches("*.md", "a.b.md"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("*a*", "banana"));
+        assert!(matches("**", ""));
+        assert!(matches("?*", "x"));
+        assert!(!matches("?*", ""));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "x"));
+    }
+
+    #[test]
+    fn dotfiles_need_an_explicit_dot() {
+        assert!(!matches("*", ".profile"));
+        assert!(!matches("?profile", ".profile"));
+        assert!(matches(".*", ".profile"));
+        assert!(matches("\\.p*", ".profile"));
+        assert_eq!(expand(&home(), "*"), ["[x]", "a.md", "ab.txt", "b.md", "notes"]);
+        assert_eq!(expand(&home(), ".p*"), [".profile"]);
+    }
+
+    #[test]
+    fn expansion_keeps_the_form_of_the_pattern() {
+        let state = home();
+        assert_eq!(expand(&state, "*.md"), ["a.md", "b.md"]);
+        assert_eq!(expand(&state, "/home/guest/?.md"), ["/home/guest/a.md", "/home/guest/b.md"]);
+        assert_eq!(expand(&state, "*/c.md"), ["notes/c.md"]);
+        assert_eq!(expand(&state, "*/"), ["notes/"]);
+        assert_eq!(expand(&state, "\\[x]"), ["[x]"]);
+        assert!(expand(&state, "*.rs").is_empty());
+    }
+
+    #[test]
+    fn patterns_matching_nothing_stay_as_written() {
+        let state = home();
+        let script = parser::parse("ls *.rs '*.md' [ab].md").unwrap();
+        let words = &script.lists[0].first.commands[0].words;
+        assert_eq!(expand::expand_words(&state, words), ["ls", "*.rs", "*.md", "a.md", "b.md"]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Command history: recording the lines the user runs, `!` event designators
+//! and `^old^new` quick substitution, as in bash.
+
+use crate::state::SystemState;
+
+/// Lines kept, oldest dropped first.
+pub const HISTORY_SIZE: usize = 100;
+
+/// Adds a line the user ran. Blank lines, internal commands, lines starting
+/// with a space and repeats of the previous line are not kept.
+pub fn record(state: &mut SystemState, line: &str) {
+    if line.trim().is_empty() || line.starts_with(' ') || line.starts_with('_') {
+        return;
+    }
+    if state.history.last().map(String::as_str) == Some(line) {
+        return;
+    }
+    state.history.push(line.to_string());
+    let excess = state.history.len().saturating_sub(HISTORY_SIZE);
+    state.history.drain(..excess);
+}
+
+/// Entries containing `query`, most recent first and without repeats, for
+/// the host's reverse search (Ctrl-R).
+pub fn search<'a>(history: &'a [String], query: &str) -> Vec<&'a str> {
+    let mut matches: Vec<&str> = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+    for line in history.iter().rev() {
+        if line.contains(query) && !matches.contains(&line.as_str()) {
+            matches.push(line);
+        }
+    }
+    matches
+}
+
+/// Expands history references in `line`: `!!`, `!n`, `!-n`, `!prefix` and a
+/// leading `^old^new`. Returns `None` when the line had none.
+pub fn expand(history: &[String], line: &str) -> Result<Option<String>, String> {
+    if let Some(spec) = line.strip_prefix('^') {
+        return quick_substitution(history, spec).map(Some);
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::new();
+    let mut changed = false;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if !in_single && i + 1 < chars.len() => {
+                // Escaped characters, `\!` included, are left for the lexer
+                out.push(c);
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            // `[!` negates a glob bracket expression
+            '!' if !in_single && (i == 0 || chars[i - 1] != '[') => {
+                let designator = event_designator(&chars[i + 1..]);
+                if !designator.is_empty() {
+                    out.push_str(event(history, &designator)?);
+                    changed = true;
+                    i += 1 + designator.chars().count();
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        i += 1;
+    }
+
+    Ok(if changed { Some(out) } else { None })
+}
+
+/// The text after a `!` that names an event, or "" when the `!` is literal
+/// (at the end of a word, before `=` or `(`).
+fn event_designator(rest: &[char]) -> String {
+    match rest.first() {
+        None | Some(' ' | '\t' | '=' | '(' | '"') => String::new(),
+        Some('!') => "!".to_string(),
+        Some('-') => {
+            let digits: String = rest[1..].iter().take_while(|c| c.is_ascii_digit()).collect();
+            if digits.is_empty() { String::new() } else { format!("-{}", digits) }
+        }
+        Some(c) if c.is_ascii_digit() => rest.iter().take_while(|c| c.is_ascii_digit()).collect(),
+        _ => rest.iter()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, ';' | '|' | '&' | '<' | '>' | '(' | ')' | '\'' | '"'))
+            .collect(),
+    }
+}
+
+/// Looks up the entry named by `designator`.
+fn event<'a>(history: &'a [String], designator: &str) -> Result<&'a str, String> {
+    let found = if designator == "!" {
+        history.last()
+    } else if let Some(back) = designator.strip_prefix('-') {
+        back.parse::<usize>().ok()
+            .filter(|n| *n > 0)
+            .and_then(|n| history.len().checked_sub(n))
+            .and_then(|i| history.get(i))
+    } else if let Ok(n) = designator.parse::<usize>() {
+        n.checked_sub(1).and_then(|i| history.get(i))
+    } else {
+        history.iter().rev().find(|line| line.starts_with(designator))
+    };
+    found.map(String::as_str).ok_or_else(|| format!("ttsh: !{}: event not found", designator))
+}
+
+/// `^old^new[^]`: the previous line with the first `old` replaced by `new`.
+fn quick_substitution(history: &[String], spec: &str) -> Result<String, String> {
+    let mut parts = spec.splitn(3, '^');
+    let old = parts.next().unwrap_or("");
+    let new = parts.next().unwrap_or("");
+    let trailing = parts.next().unwrap_or("");
+
+    let previous = history.last().ok_or_else(|| "ttsh: !!: event not found".to_string())?;
+    if old.is_empty() || !previous.contains(old) {
+        return Err(format!("ttsh: :s^{}^{}^: substitution failed", old, new));
+    }
+    Ok(format!("{}{}", previous.replacen(old, new, 1), trailing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> Vec<String> {
+        ["ls /posts", "cat bio", "echo one", "cat notes"].iter().map(|line| line.to_string()).collect()
+    }
+
+    fn expanded(line: &str) -> Result<Option<String>, String> {
+        expand(&history(), line)
+    }
+
+    #[test]
+    fn bang_designators_name_earlier_lines() {
+        assert_eq!(expanded("!!"), Ok(Some("cat notes".to_string())));
+        assert_eq!(expanded("sudo !! | wc"), Ok(Some("sudo cat notes | wc".to_string())));
+        assert_eq!(expanded("!1"), Ok(Some("ls /posts".to_string())));
+        assert_eq!(expanded("!-2"), Ok(Some("echo one".to_string())));
+        assert_eq!(expanded("!cat; !ls"), Ok(Some("cat notes; ls /posts".to_string())));
+        assert_eq!(expanded("!9"), Err("ttsh: !9: event not found".to_string()));
+        assert_eq!(expanded("!-5"), Err("ttsh: !-5: event not found".to_string()));
+        assert_eq!(expanded("!grep"), Err("ttsh: !grep: event not found".to_string()));
+    }
+
+    #[test]
+    fn literal_bangs_are_left_alone() {
+        for line in ["echo hi!", "echo '!!'", "echo \\!!", "ls [!a]*", "x != y", "echo \"a!\""] {
+            assert_eq!(expanded(line), Ok(None), "{}", line);
+        }
+        assert_eq!(expanded("echo \"!!\""), Ok(Some("echo \"cat notes\"".to_string())));
+    }
+
+    #[test]
+    fn quick_substitution_edits_the_last_line() {
+        assert_eq!(expanded("^notes^bio"), Ok(Some("cat bio".to_string())));
+        assert_eq!(expanded("^cat^less^ -N"), Ok(Some("less notes -N".to_string())));
+        assert_eq!(expanded("^zzz^x"), Err("ttsh: :s^zzz^x^: substitution failed".to_string()));
+        assert_eq!(expand(&[], "^a^b"), Err("ttsh: !!: event not found".to_string()));
+    }
+
+    #[test]
+    fn record_skips_blank_hidden_and_repeated_lines() {
+        let mut state = SystemState::default();
+        for line in ["ls", "ls", " secret", "", "_autocomplete ls", "cat bio", "ls"] {
+            record(&mut state, line);
+        }
+        assert_eq!(state.history, ["ls", "cat bio", "ls"]);
+
+        for i in 0..HISTORY_SIZE + 5 {
+            record(&mut state, &format!("echo {}", i));
+        }
+        assert_eq!(state.history.len(), HISTORY_SIZE);
+        assert_eq!(state.history[0], "echo 5");
+        assert_eq!(state.history.last().map(String::as_str), Some("echo 104"));
+    }
+
+    #[test]
+    fn search_finds_recent_lines_once() {
+        let mut lines = history();
+        lines.push("cat bio".to_string());
+        assert_eq!(search(&lines, "cat"), ["cat bio", "cat notes"]);
+        assert!(search(&lines, "").is_empty());
+    }
+}
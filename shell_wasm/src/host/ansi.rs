@@ -0,0 +1,35 @@
+//! Output lines rendered for a real terminal: the page's CSS classes become
+//! ANSI colours, and lines end in CRLF as terminals on the wire expect.
+
+use crate::WasmLine;
+
+const RESET: &str = "\x1b[0m";
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// The SGR sequence for a line type, if it has a colour.
+fn style(line_type: &str) -> Option<&'static str> {
+    match line_type {
+        "bbs-border" => Some("\x1b[36m"),
+        "bbs-title" => Some("\x1b[1;33m"),
+        "bbs-header" => Some("\x1b[1;37m"),
+        "bbs-footer" => Some("\x1b[32m"),
+        "suggestion" | "autocomplete-list" | "history-search" => Some("\x1b[2m"),
+        "matrix-line" => Some("\x1b[1;32m"),
+        "ls-dir" => Some("\x1b[1;34m"),
+        // Menu rows, post rows and page links
+        t if t.starts_with("bbs-") => Some("\x1b[33m"),
+        _ => None,
+    }
+}
+
+/// `line` as terminal output, ending in CRLF.
+pub fn render(line: &WasmLine) -> String {
+    if line.line_type == "clearScreen" {
+        return CLEAR_SCREEN.to_string();
+    }
+    let text = line.text.replace('\n', "\r\n");
+    match style(&line.line_type) {
+        Some(sgr) => format!("{}{}{}\r\n", sgr, text, RESET),
+        None => format!("{}\r\n", text),
+    }
+}
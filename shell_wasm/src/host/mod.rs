@@ -0,0 +1,131 @@
+//! What the native hosts (`ttsh` and the telnet and SSH nodes) share: loading the
+//! site, prompts, and running a line the way the page's scripts do.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use crate::catalog::Catalog;
+use crate::events::{self, EventKind};
+use crate::platform::Platform;
+use crate::shell;
+use crate::state::{LoginState, SystemState};
+use crate::WasmLine;
+
+pub mod ansi;
+pub mod session;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ssh;
+pub mod telnet;
+
+/// A fresh, logged-out session with the catalog of the Hugo build in `site` loaded.
+pub fn load_site(site: &Path, platform: &dyn Platform) -> Result<SystemState, String> {
+    let path = site.join("index.json");
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let catalog = Catalog::from_json(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut state = SystemState::default();
+    catalog.apply(&mut state);
+    // What the page's `new Date().toDateString()` gives, e.g. `Sat Oct 18 2026`
+    state.system_info.current_date = platform.now().split(' ').take(4).collect::<Vec<_>>().join(" ");
+    Ok(state)
+}
+
+/// The prompt the page shows for the session's state.
+pub fn prompt(state: &SystemState) -> String {
+    match state.login_state {
+        LoginState::Prompt => format!("{}@tecnoter.io:{}$ ", state.current_user, state.cwd),
+        LoginState::Login => "tecnoter login: ".to_string(),
+        LoginState::Password => "Password: ".to_string(),
+        LoginState::Message => "Message to Admin: ".to_string(),
+        LoginState::Mail => format!("Mail to {}: ", state.mail_recipient.as_deref().unwrap_or("")),
+        s if s.is_bbs() => format!("BBS Selection (1-{}, Q to Quit, M for Menu): ", state.posts.len()),
+        _ => String::new(),
+    }
+}
+
+/// What a line produced, background output included.
+pub struct Reply {
+    pub lines: Vec<WasmLine>,
+    /// `exit` was run.
+    pub closed: bool,
+}
+
+fn regular(text: String) -> WasmLine {
+    WasmLine { text, line_type: "regular".to_string() }
+}
+
+/// Runs `line` with its jobs, which run to their end at once natively, taking
+/// their output from the event queue. Instructions for the page are resolved
+/// here: `exit` closes the session, links are shown, and browser-only effects
+/// are dropped.
+pub fn execute(state: &mut SystemState, line: &str) -> Reply {
+    let mut result = shell::process_input(state.clone(), line);
+    let mut lines = Vec::new();
+    let mut closed = false;
+
+    for output in std::mem::take(&mut result.lines) {
+        if output.line_type != "internalInstruction" {
+            lines.push(output);
+        } else if output.text == "exit" {
+            closed = true;
+        } else if let Some(url) = output.text.strip_prefix("_OPEN_URL_") {
+            lines.push(regular(url.to_string()));
+        }
+        // Matrix rain and ANSI art only exist in the browser
+    }
+    // The page falls back to its own commands here; there are none natively
+    if !result.handled && result.status == 127 {
+        lines.push(regular(format!("command not found: {}", line.split_whitespace().next().unwrap_or(line))));
+    }
+
+    *state = result.next_state;
+    let foreground = result.pending.take().map(|pending| shell::start_job(line, pending, false));
+    for event in events::drain() {
+        match event.kind {
+            EventKind::Stdout { text } | EventKind::Stderr { text } => lines.push(regular(text)),
+            EventKind::Write { path, text } => {
+                state.files.insert(path, text);
+            }
+            EventKind::Done { status } if Some(event.job) == foreground => state.last_status = status,
+            EventKind::Done { .. } => {}
+            // Jobs have ended by now, so their progress is stale
+            EventKind::Progress { .. } => {}
+        }
+    }
+    Reply { lines, closed }
+}
+
+/// Open connections per client address.
+pub type Connections = Arc<Mutex<HashMap<IpAddr, usize>>>;
+
+/// A connection counted against its address until dropped.
+pub struct Slot {
+    connections: Connections,
+    ip: IpAddr,
+}
+
+impl Slot {
+    /// A slot for `ip`, unless it already has `limit` connections open.
+    pub fn acquire(connections: &Connections, ip: IpAddr, limit: usize) -> Option<Slot> {
+        let mut open = connections.lock().unwrap_or_else(|e| e.into_inner());
+        let count = open.entry(ip).or_insert(0);
+        if *count >= limit {
+            return None;
+        }
+        *count += 1;
+        Some(Slot { connections: connections.clone(), ip })
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut open = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = open.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                open.remove(&self.ip);
+            }
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! A character-at-a-time terminal session over any byte stream: the boot
+//! banner and login flow the page runs, line editing with history and tab
+//! completion, and the shell or BBS behind it.
+
+use std::rc::Rc;
+use crate::platform::{self, native::NativePlatform};
+use crate::shell;
+use crate::state::{LoginState, SystemState};
+use crate::terminal::TerminalState;
+use super::{ansi, execute, prompt};
+
+/// Longest line we buffer; further keystrokes are dropped.
+const MAX_LINE: usize = 1024;
+
+/// How a login attempt went.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Login {
+    Granted,
+    /// The account needs a password; see [`Session::password`].
+    Password,
+    Refused,
+}
+
+pub struct Session {
+    state: SystemState,
+    terminal: TerminalState,
+    line: String,
+    /// Bytes of a character still arriving.
+    utf8: Vec<u8>,
+    /// An escape sequence being read, e.g. the arrow keys.
+    escape: Option<Vec<u8>>,
+    /// Position in the history while browsing it with the arrow keys.
+    history_index: Option<usize>,
+    /// Output of a login made through [`Session::login`], shown once the shell starts.
+    held: String,
+    closed: bool,
+}
+
+impl Session {
+    /// A session on `state`, a loaded site. The platform is installed for this
+    /// thread, so each session needs a thread of its own.
+    pub fn new(state: SystemState, platform: Rc<NativePlatform>) -> Self {
+        platform::install(platform);
+        Self {
+            state,
+            terminal: TerminalState::new(),
+            line: String::new(),
+            utf8: Vec::new(),
+            escape: None,
+            history_index: None,
+            held: String::new(),
+            closed: false,
+        }
+    }
+
+    /// Whether the user has left; the host should hang up.
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The status of the last command, for hosts that report one on exit.
+    pub fn status(&self) -> i32 {
+        self.state.last_status
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.resize(u32::from(width), u32::from(height));
+    }
+
+    /// The boot banner and the login prompt.
+    pub fn start(&mut self) -> Vec<u8> {
+        let mut out = self.boot();
+        out += &prompt(&self.state);
+        out.into_bytes()
+    }
+
+    fn boot(&mut self) -> String {
+        let mut out = self.run("_boot");
+        out += &self.run("_start_login");
+        out
+    }
+
+    /// Logs in as `name`, as if it was typed at the login prompt, for hosts
+    /// that ask for credentials themselves. What the node prints meanwhile,
+    /// boot banner included, is held until [`Session::resume`].
+    pub fn login(&mut self, name: &str) -> Login {
+        match self.state.login_state {
+            LoginState::Login => {}
+            // An unanswered password prompt starts over
+            LoginState::Password => {
+                if self.state.go_to(LoginState::Login).is_err() {
+                    self.held = self.boot();
+                }
+            }
+            _ => self.held = self.boot(),
+        }
+        // Anything but a plain name is not a user
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            self.held += "Login incorrect.\r\n";
+            return Login::Refused;
+        }
+        let out = self.run(&format!("_login {}", name));
+        self.held += &out;
+        match self.state.login_state {
+            LoginState::Login => Login::Refused,
+            LoginState::Password => Login::Password,
+            _ => Login::Granted,
+        }
+    }
+
+    /// Answers the password prompt of [`Session::login`]; true when it let the user in.
+    pub fn password(&mut self, password: &str) -> bool {
+        let out = self.run(password);
+        self.held += &out;
+        self.state.login_state == LoginState::Prompt
+    }
+
+    /// The shell after a login through [`Session::login`]: what was held, the
+    /// MOTD or the BBS, and the prompt.
+    pub fn resume(&mut self) -> Vec<u8> {
+        let mut out = std::mem::take(&mut self.held);
+        out += &self.logged_in();
+        out += &prompt(&self.state);
+        out.into_bytes()
+    }
+
+    /// Runs a single command line without a terminal, e.g. `ssh node ls`.
+    pub fn command(&mut self, line: &str) -> Vec<u8> {
+        self.run(line).into_bytes()
+    }
+
+    /// Handles keystrokes, returning what to send back.
+    pub fn input(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = String::new();
+        for &b in bytes {
+            if self.closed {
+                break;
+            }
+            if let Some(sequence) = &mut self.escape {
+                sequence.push(b);
+                // CSI sequences end with a letter or `~`; `ESC O x` is one more byte
+                let done = match sequence.as_slice() {
+                    [b'['] | [b'O'] => false,
+                    [b'[', .., last] => (0x40..=0x7e).contains(last),
+                    _ => true,
+                };
+                if done {
+                    let sequence = self.escape.take().unwrap_or_default();
+                    out += &self.key(&sequence);
+                }
+                continue;
+            }
+            // A paused BBS screen goes back on any key
+            if self.state.login_state == LoginState::BbsPause {
+                out += &self.submit();
+                continue;
+            }
+            out += &match b {
+                0x1b => {
+                    self.escape = Some(Vec::new());
+                    String::new()
+                }
+                b'\r' => self.submit(),
+                0x7f | 0x08 => self.backspace(),
+                // Ctrl-C drops the line
+                0x03 => {
+                    self.line.clear();
+                    self.history_index = None;
+                    format!("^C\r\n{}", prompt(&self.state))
+                }
+                // Ctrl-D on an empty line logs out
+                0x04 if self.line.is_empty() => {
+                    self.closed = true;
+                    "logout\r\n".to_string()
+                }
+                // Ctrl-U erases the line
+                0x15 => {
+                    self.line.clear();
+                    self.redraw()
+                }
+                // Ctrl-L clears the screen
+                0x0c => format!("{}{}", ansi::CLEAR_SCREEN, self.redraw()),
+                b'\t' => self.complete(),
+                b if b < 0x20 => String::new(),
+                b => self.typed(b),
+            };
+        }
+        out.into_bytes()
+    }
+
+    /// Shows the prompt and the line typed so far, e.g. after the line changed.
+    fn redraw(&self) -> String {
+        format!("\r\x1b[K{}{}", prompt(&self.state), self.shown(&self.line))
+    }
+
+    /// What the terminal shows of `text`: nothing while a password is typed.
+    fn shown<'a>(&self, text: &'a str) -> &'a str {
+        if self.state.login_state == LoginState::Password { "" } else { text }
+    }
+
+    fn typed(&mut self, b: u8) -> String {
+        self.utf8.push(b);
+        let text = match std::str::from_utf8(&self.utf8) {
+            Ok(text) => text.to_string(),
+            // Wait for the rest of the character
+            Err(e) if e.error_len().is_none() => return String::new(),
+            Err(_) => String::new(),
+        };
+        self.utf8.clear();
+        if text.is_empty() || self.line.len() + text.len() > MAX_LINE {
+            return String::new();
+        }
+        self.line.push_str(&text);
+        self.shown(&text).to_string()
+    }
+
+    fn backspace(&mut self) -> String {
+        match self.line.pop() {
+            Some(_) if self.state.login_state != LoginState::Password => "\x08 \x08".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Up and down browse the history at the prompt; other keys are ignored.
+    fn key(&mut self, sequence: &[u8]) -> String {
+        if self.state.login_state != LoginState::Prompt {
+            return String::new();
+        }
+        let history = &self.state.history;
+        let index = match (sequence.last(), self.history_index) {
+            (Some(b'A'), None) if !history.is_empty() => Some(history.len() - 1),
+            (Some(b'A'), Some(i)) => Some(i.saturating_sub(1)),
+            (Some(b'B'), Some(i)) if i + 1 < history.len() => Some(i + 1),
+            (Some(b'B'), Some(_)) => None,
+            _ => return String::new(),
+        };
+        self.history_index = index;
+        self.line = index.map(|i| history[i].clone()).unwrap_or_default();
+        self.redraw()
+    }
+
+    /// Completes the word before the cursor, or lists the candidates.
+    fn complete(&mut self) -> String {
+        if self.state.login_state != LoginState::Prompt {
+            return String::new();
+        }
+        let reply = shell::process_input(self.state.clone(), &format!("_autocomplete {}", self.line));
+        let matches: Vec<String> = reply.lines.first()
+            .map(|line| line.text.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+        let start = self.line.rfind([' ', '|', ';', '&']).map_or(0, |i| i + 1);
+        let partial = self.line[start..].to_string();
+
+        match matches.as_slice() {
+            [] => "\x07".to_string(),
+            [only] => {
+                let rest = format!("{} ", only.strip_prefix(partial.as_str()).unwrap_or(""));
+                self.line.push_str(&rest);
+                rest
+            }
+            many => {
+                let common = many.iter().skip(1).fold(many[0].clone(), |common, m| {
+                    common.chars().zip(m.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+                });
+                if let Some(rest) = common.strip_prefix(partial.as_str()).filter(|rest| !rest.is_empty()) {
+                    self.line.push_str(rest);
+                    return rest.to_string();
+                }
+                format!("\r\n{}{}", self.columns(many), self.redraw())
+            }
+        }
+    }
+
+    /// Candidates laid out in columns across the terminal.
+    fn columns(&self, items: &[String]) -> String {
+        let width = items.iter().map(|item| item.chars().count()).max().unwrap_or(0) + 2;
+        let per_row = (self.terminal.width as usize / width).max(1);
+        items.chunks(per_row)
+            .map(|row| row.iter().map(|item| format!("{:<width$}", item)).collect::<String>().trim_end().to_string() + "\r\n")
+            .collect()
+    }
+
+    fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.line);
+        self.history_index = None;
+        let mut out = "\r\n".to_string();
+
+        match self.state.login_state {
+            LoginState::Login => {
+                if let Some(name) = line.split_whitespace().next() {
+                    self.login(name);
+                    out += &std::mem::take(&mut self.held);
+                    out += &self.logged_in();
+                }
+            }
+            LoginState::Password => {
+                self.password(&line);
+                out += &std::mem::take(&mut self.held);
+                out += &self.logged_in();
+            }
+            _ => out += &self.run(&line),
+        }
+        if !self.closed {
+            out += &prompt(&self.state);
+        }
+        out
+    }
+
+    /// What the page shows once a login went through: the MOTD, or the BBS.
+    fn logged_in(&mut self) -> String {
+        match self.state.login_state {
+            LoginState::Prompt => self.run("motd"),
+            LoginState::BbsMain => self.run("bbs"),
+            _ => String::new(),
+        }
+    }
+
+    fn run(&mut self, line: &str) -> String {
+        let reply = execute(&mut self.state, line);
+        self.closed |= reply.closed;
+        reply.lines.iter().map(ansi::render).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A session at the shell prompt, or at `login_state`.
+    fn at(login_state: LoginState) -> Session {
+        let state = SystemState {
+            current_user: "guest".to_string(),
+            cwd: "/home/guest".to_string(),
+            booted: true,
+            is_authenticated: true,
+            login_state,
+            ..Default::default()
+        };
+        Session::new(state, Rc::new(NativePlatform::default()))
+    }
+
+    fn input(session: &mut Session, bytes: &[u8]) -> String {
+        String::from_utf8(session.input(bytes)).unwrap()
+    }
+
+    #[test]
+    fn characters_split_across_reads_are_echoed_whole() {
+        let mut session = at(LoginState::Prompt);
+        assert_eq!(input(&mut session, b"caf\xc3"), "caf");
+        assert_eq!(input(&mut session, b"\xa9 \xe2\x82"), "é ");
+        assert_eq!(input(&mut session, b"\xac"), "€");
+        assert_eq!(session.line, "café €");
+        // Bytes that never make a character are dropped
+        assert_eq!(input(&mut session, b"\xff!"), "!");
+        assert_eq!(session.line, "café €!");
+    }
+
+    #[test]
+    fn backspace_erases_one_character() {
+        let mut session = at(LoginState::Prompt);
+        input(&mut session, "né".as_bytes());
+        assert_eq!(input(&mut session, b"\x7f"), "\x08 \x08");
+        assert_eq!(session.line, "n");
+        assert_eq!(input(&mut session, b"\x08"), "\x08 \x08");
+        assert_eq!(input(&mut session, b"\x7f"), "");
+        assert_eq!(session.line, "");
+    }
+
+    #[test]
+    fn passwords_are_not_echoed() {
+        let mut session = at(LoginState::Password);
+        assert_eq!(input(&mut session, b"secret"), "");
+        assert_eq!(input(&mut session, b"\x7f"), "");
+        assert_eq!(session.line, "secre");
+    }
+
+    #[test]
+    fn enter_runs_the_line() {
+        let mut session = at(LoginState::Prompt);
+        assert_eq!(input(&mut session, b"echo hi\r"), "echo hi\r\nhi\r\nguest@tecnoter.io:/home/guest$ ");
+        assert_eq!(input(&mut session, b"\x1b[A"), "\r\x1b[Kguest@tecnoter.io:/home/guest$ echo hi");
+        assert_eq!(input(&mut session, b"\x03"), "^C\r\nguest@tecnoter.io:/home/guest$ ");
+        assert_eq!(session.line, "");
+    }
+
+    #[test]
+    fn logout_and_ctrl_d_close_the_session() {
+        let mut session = at(LoginState::Prompt);
+        input(&mut session, b"x\x04");
+        assert!(!session.closed());
+        assert_eq!(input(&mut session, b"\x15\x04echo ignored\r"), "\r\x1b[Kguest@tecnoter.io:/home/guest$ logout\r\n");
+        assert!(session.closed());
+
+        let mut session = at(LoginState::Prompt);
+        input(&mut session, b"logout\r");
+        assert!(session.closed());
+    }
+}
@@ -0,0 +1,322 @@
+//! The node over SSH, with russh speaking the protocol: keyboard-interactive
+//! logins (RFC 4256) go through the node's own `_login` flow, so `guest` and
+//! `bbs` get straight in and `admin` is asked for the password. One session
+//! channel per connection runs the shell, or a single command for
+//! `ssh guest@node ls /posts`.
+//!
+//! russh runs the connection on tokio and wants a handler it can move between
+//! threads, which a [`Session`] is not. The handler sends each step to the
+//! connection's own thread, where the session lives, and writes back what
+//! the step returned.
+
+use std::borrow::Cow;
+use std::io::{self, ErrorKind, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use russh::keys::ssh_key::private::Ed25519Keypair;
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::{HashAlg, PrivateKey};
+use russh::server::{self, Auth, ChannelOpenHandle, Config, Handle, Msg, Response};
+use russh::{Channel, ChannelId, ChannelOpenFailure, Disconnect, MethodKind, MethodSet};
+use tokio::sync::{mpsc, oneshot};
+use super::session::{Login, Session};
+
+/// Failed logins before we hang up.
+const MAX_AUTH_TRIES: usize = 3;
+
+/// The server's identity.
+pub struct HostKey(PrivateKey);
+
+impl HostKey {
+    pub fn generate() -> io::Result<Self> {
+        let mut seed = [0; 32];
+        getrandom::getrandom(&mut seed).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Self(Ed25519Keypair::from_seed(&seed).into()))
+    }
+
+    /// The key in `path`, in the format `ssh-keygen -t ed25519 -N ''` writes.
+    /// A missing file is created with a new key; the flag tells which happened.
+    pub fn load_or_generate(path: &Path) -> io::Result<(Self, bool)> {
+        let invalid = |message: String| io::Error::new(ErrorKind::InvalidData, message);
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                let key = PrivateKey::from_openssh(text).map_err(|e| invalid(e.to_string()))?;
+                if key.is_encrypted() {
+                    return Err(invalid("the host key must not have a passphrase".to_string()));
+                }
+                Ok((Self(key), false))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let key = Self::generate()?;
+                let text = key.0.to_openssh(LineEnding::LF).map_err(|e| invalid(e.to_string()))?;
+                let mut options = std::fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                options.open(path)?.write_all(text.as_bytes())?;
+                Ok((key, true))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// What `ssh-keygen -lf` and the client's first-connection question show,
+    /// e.g. `SHA256:kH3...`.
+    pub fn fingerprint(&self) -> String {
+        self.0.public_key().fingerprint(HashAlg::Sha256).to_string()
+    }
+}
+
+/// The session and its one channel, on the connection's thread.
+struct Node<'a> {
+    session: &'a mut Session,
+    /// Once the client opened it.
+    channel: Option<ChannelId>,
+    /// A terminal was asked for; without one, lines end in LF.
+    pty: bool,
+    /// `shell` or `exec` was run; anything later is refused.
+    started: bool,
+    /// The exit status was sent and the channel closed.
+    closed: bool,
+}
+
+/// What a step sends on the channel: output, then the exit status and the
+/// close if the channel is done.
+#[derive(Default)]
+struct Reply {
+    data: Vec<u8>,
+    exit: Option<u32>,
+}
+
+impl Node<'_> {
+    fn login(&mut self, user: &str, response: Option<String>) -> Auth {
+        let granted = match response {
+            // The answer to the password prompt below
+            Some(password) => self.session.password(&password),
+            None => match self.session.login(user) {
+                Login::Granted => true,
+                Login::Refused => false,
+                Login::Password => return Auth::Partial {
+                    name: Cow::Borrowed(""),
+                    instructions: Cow::Borrowed(""),
+                    prompts: Cow::Owned(vec![(Cow::Borrowed("Password: "), false)]),
+                },
+            },
+        };
+        if granted { Auth::Accept } else { Auth::reject() }
+    }
+
+    /// `output` as the client expects it, ending the channel if `done`.
+    fn reply(&mut self, output: Vec<u8>, done: bool) -> Reply {
+        // Without a terminal, plain newlines are what the client expects
+        let data = if self.pty { output } else { output.into_iter().filter(|&b| b != b'\r').collect() };
+        let exit = (done && !self.closed).then(|| self.session.status() as u32);
+        self.closed |= done;
+        Reply { data, exit }
+    }
+
+    fn shell(&mut self) -> Option<Reply> {
+        if self.started {
+            return None;
+        }
+        self.started = true;
+        let output = self.session.resume();
+        Some(self.reply(output, false))
+    }
+
+    fn exec(&mut self, command: &str) -> Option<Reply> {
+        if self.started {
+            return None;
+        }
+        self.started = true;
+        let output = self.session.command(command);
+        Some(self.reply(output, true))
+    }
+
+    fn input(&mut self, data: &[u8]) -> Reply {
+        if !self.started || self.closed {
+            return Reply::default();
+        }
+        // Piped input ends its lines in LF; a terminal sends CR
+        let keys: Vec<u8> = if self.pty {
+            data.to_vec()
+        } else {
+            data.iter().map(|&b| if b == b'\n' { b'\r' } else { b }).collect()
+        };
+        let output = self.session.input(&keys);
+        let done = self.session.closed();
+        self.reply(output, done)
+    }
+}
+
+/// Work for the connection's thread, run on its [`Node`].
+type Step = Box<dyn FnOnce(&mut Node) + Send>;
+
+/// Hands what the client asks for to the [`Node`].
+struct Handler {
+    steps: mpsc::UnboundedSender<Step>,
+}
+
+impl Handler {
+    /// Runs `step` on the node and returns its result.
+    async fn step<T: Send + 'static>(&self, step: impl FnOnce(&mut Node) -> T + Send + 'static) -> Result<T, russh::Error> {
+        let (done, result) = oneshot::channel();
+        self.steps.send(Box::new(move |node| {
+            let _ = done.send(step(node));
+        })).map_err(|_| russh::Error::SendError)?;
+        result.await.map_err(|_| russh::Error::SendError)
+    }
+
+    /// Sends `reply` on `channel`, refusing the request it answers if there is none.
+    fn send(channel: ChannelId, reply: Option<Reply>, session: &mut server::Session) -> Result<(), russh::Error> {
+        let Some(reply) = reply else {
+            return session.channel_failure(channel);
+        };
+        if !reply.data.is_empty() {
+            session.data(channel, reply.data)?;
+        }
+        if let Some(status) = reply.exit {
+            session.exit_status_request(channel, status)?;
+            session.eof(channel)?;
+            session.close(channel)?;
+        }
+        Ok(())
+    }
+}
+
+impl server::Handler for Handler {
+    type Error = russh::Error;
+
+    async fn auth_keyboard_interactive<'a>(&'a mut self, user: &str, _submethods: &str, response: Option<Response<'a>>) -> Result<Auth, Self::Error> {
+        let user = user.to_string();
+        let response = response.map(|mut answers| String::from_utf8_lossy(&answers.next().unwrap_or_default()).into_owned());
+        self.step(move |node| node.login(&user, response)).await
+    }
+
+    async fn channel_open_session(&mut self, channel: Channel<Msg>, reply: ChannelOpenHandle, _session: &mut server::Session) -> Result<(), Self::Error> {
+        let id = channel.id();
+        let first = self.step(move |node| node.channel.get_or_insert(id) == &id).await?;
+        if first {
+            reply.accept().await;
+        } else {
+            reply.reject(ChannelOpenFailure::ResourceShortage).await;
+        }
+        Ok(())
+    }
+
+    async fn pty_request(&mut self, channel: ChannelId, _term: &str, width: u32, height: u32, _pix_width: u32, _pix_height: u32, _modes: &[(russh::Pty, u32)], session: &mut server::Session) -> Result<(), Self::Error> {
+        self.step(move |node| {
+            node.pty = true;
+            node.session.resize(size(width), size(height));
+        }).await?;
+        session.channel_success(channel)
+    }
+
+    async fn window_change_request(&mut self, _channel: ChannelId, width: u32, height: u32, _pix_width: u32, _pix_height: u32, _session: &mut server::Session) -> Result<(), Self::Error> {
+        self.step(move |node| node.session.resize(size(width), size(height))).await
+    }
+
+    async fn shell_request(&mut self, channel: ChannelId, session: &mut server::Session) -> Result<(), Self::Error> {
+        let reply = self.step(|node| node.shell()).await?;
+        if reply.is_some() {
+            session.channel_success(channel)?;
+        }
+        Self::send(channel, reply, session)
+    }
+
+    async fn exec_request(&mut self, channel: ChannelId, data: &[u8], session: &mut server::Session) -> Result<(), Self::Error> {
+        let command = String::from_utf8_lossy(data).into_owned();
+        let reply = self.step(move |node| node.exec(&command)).await?;
+        if reply.is_some() {
+            session.channel_success(channel)?;
+        }
+        Self::send(channel, reply, session)
+    }
+
+    /// There is no `sftp` or anything else to run.
+    async fn subsystem_request(&mut self, channel: ChannelId, _name: &str, session: &mut server::Session) -> Result<(), Self::Error> {
+        session.channel_failure(channel)
+    }
+
+    async fn data(&mut self, channel: ChannelId, data: &[u8], session: &mut server::Session) -> Result<(), Self::Error> {
+        let data = data.to_vec();
+        let reply = self.step(move |node| node.input(&data)).await?;
+        Self::send(channel, Some(reply), session)
+    }
+
+    /// No more input is coming, as if the user typed Ctrl-D.
+    async fn channel_eof(&mut self, channel: ChannelId, session: &mut server::Session) -> Result<(), Self::Error> {
+        let reply = self.step(|node| node.reply(Vec::new(), true)).await?;
+        Self::send(channel, Some(reply), session)
+    }
+}
+
+/// A terminal size as the session takes it.
+fn size(n: u32) -> u16 {
+    n.min(u32::from(u16::MAX)) as u16
+}
+
+/// Tells the user why the channel closes, then closes it.
+async fn time_out(handle: &Handle, channel: ChannelId, reply: Reply) {
+    let _ = handle.data(channel, reply.data).await;
+    if let Some(status) = reply.exit {
+        let _ = handle.exit_status_request(channel, status).await;
+        let _ = handle.eof(channel).await;
+        let _ = handle.close(channel).await;
+    }
+}
+
+/// Runs the node for the SSH client on `stream` until it leaves, hanging up
+/// after `idle_timeout` without a word from it.
+pub fn serve(stream: TcpStream, host_key: &HostKey, idle_timeout: Duration, session: &mut Session) -> io::Result<()> {
+    let config = Arc::new(Config {
+        server_id: russh::SshId::Standard(Cow::Borrowed("SSH-2.0-tecnoter_1.0")),
+        methods: MethodSet::from(&[MethodKind::KeyboardInteractive][..]),
+        keys: vec![host_key.0.clone()],
+        max_auth_attempts: MAX_AUTH_TRIES,
+        // Only for clients that stop mid-handshake: the node times out idle
+        // users itself, so it can tell them first
+        inactivity_timeout: Some(idle_timeout * 2),
+        ..Config::default()
+    });
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    runtime.block_on(async {
+        stream.set_nonblocking(true)?;
+        let stream = tokio::net::TcpStream::from_std(stream)?;
+        let (steps, mut queue) = mpsc::unbounded_channel();
+        let running = server::run_stream(config, stream, Handler { steps }).await.map_err(io_error)?;
+        let handle = running.handle();
+
+        let mut node = Node { session, channel: None, pty: false, started: false, closed: false };
+        loop {
+            match tokio::time::timeout(idle_timeout, queue.recv()).await {
+                Ok(Some(step)) => step(&mut node),
+                // The handler is gone with the connection
+                Ok(None) => break,
+                // Closing the channel first lets the client show why
+                Err(_) => match node.channel {
+                    Some(channel) if !node.closed => {
+                        let reply = node.reply(b"\r\nIdle timeout, closing connection.\r\n".to_vec(), true);
+                        time_out(&handle, channel, reply).await;
+                    }
+                    _ => {
+                        let _ = handle.disconnect(Disconnect::ByApplication, "idle timeout".to_string(), String::new()).await;
+                        break;
+                    }
+                },
+            }
+        }
+        running.await.map_err(io_error)
+    })
+}
+
+fn io_error(e: russh::Error) -> io::Error {
+    match e {
+        russh::Error::IO(e) => e,
+        // The client went away without saying goodbye
+        russh::Error::Disconnect | russh::Error::HUP => ErrorKind::UnexpectedEof.into(),
+        e => io::Error::other(e.to_string()),
+    }
+}
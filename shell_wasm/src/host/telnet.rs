@@ -0,0 +1,220 @@
+//! Just enough of the telnet protocol (RFC 854) for a character-at-a-time
+//! session: the server echoes and suppresses go-ahead, and the client reports
+//! its window size (NAWS, RFC 1073).
+
+pub const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const ECHO: u8 = 1;
+const SGA: u8 = 3;
+const NAWS: u8 = 31;
+
+/// Bytes of a subnegotiation kept; NAWS, the only one read, takes 5. The
+/// rest of a longer one is dropped, so a client cannot make us buffer it.
+const SUB_MAX: usize = 8;
+
+/// Sent on connect: we echo and don't need go-aheads; please send your window size.
+pub fn negotiation() -> Vec<u8> {
+    vec![IAC, WILL, ECHO, IAC, WILL, SGA, IAC, DO, SGA, IAC, DO, NAWS]
+}
+
+/// Output for the wire: a literal 255 byte must be doubled.
+pub fn escape(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    for &b in bytes {
+        out.push(b);
+        if b == IAC {
+            out.push(IAC);
+        }
+    }
+    out
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Keystrokes, with the protocol stripped and a line end as a single `\r`.
+    Data(Vec<u8>),
+    /// The client's window, in columns and rows.
+    Resize(u16, u16),
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Data,
+    /// After a CR, whose LF or NUL is dropped.
+    Cr,
+    Iac,
+    /// After `IAC <verb>`, waiting for the option.
+    Option(u8),
+    Sub,
+    SubIac,
+}
+
+/// Splits what the client sends into keystrokes and protocol events.
+#[derive(Default)]
+pub struct Decoder {
+    state: State,
+    sub: Vec<u8>,
+}
+
+impl Decoder {
+    /// Decodes `bytes`, returning the events and whatever must be sent back.
+    pub fn feed(&mut self, bytes: &[u8]) -> (Vec<Event>, Vec<u8>) {
+        let mut events = Vec::new();
+        let mut data = Vec::new();
+        let mut reply = Vec::new();
+
+        for &b in bytes {
+            self.state = match (self.state, b) {
+                (State::Data | State::Cr, IAC) => State::Iac,
+                (State::Cr, b'\n' | 0) => State::Data,
+                (State::Data | State::Cr, b'\r') => {
+                    data.push(b'\r');
+                    State::Cr
+                }
+                // Clients in line mode may send a bare LF
+                (State::Data, b'\n') => {
+                    data.push(b'\r');
+                    State::Data
+                }
+                (State::Data | State::Cr, b) => {
+                    data.push(b);
+                    State::Data
+                }
+                (State::Iac, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, DO | DONT | WILL | WONT) => State::Option(b),
+                (State::Iac, SB) => {
+                    self.sub.clear();
+                    State::Sub
+                }
+                // NOP, go-ahead, break and the like
+                (State::Iac, _) => State::Data,
+                (State::Option(verb), option) => {
+                    reply.extend(answer(verb, option));
+                    State::Data
+                }
+                (State::Sub, IAC) => State::SubIac,
+                (State::Sub, b) => {
+                    self.keep(b);
+                    State::Sub
+                }
+                (State::SubIac, SE) => {
+                    if let [NAWS, w1, w0, h1, h0] = self.sub[..] {
+                        if !data.is_empty() {
+                            events.push(Event::Data(std::mem::take(&mut data)));
+                        }
+                        events.push(Event::Resize(u16::from_be_bytes([w1, w0]), u16::from_be_bytes([h1, h0])));
+                    }
+                    State::Data
+                }
+                (State::SubIac, b) => {
+                    // IAC IAC inside a subnegotiation is a 255 byte
+                    self.keep(b);
+                    State::Sub
+                }
+            };
+        }
+        if !data.is_empty() {
+            events.push(Event::Data(data));
+        }
+        (events, reply)
+    }
+
+    /// Adds `b` to the subnegotiation, unless it is already too long to be one we read.
+    fn keep(&mut self, b: u8) {
+        if self.sub.len() < SUB_MAX {
+            self.sub.push(b);
+        }
+    }
+}
+
+/// Our answer to `IAC <verb> <option>`. Options we asked for need no answer;
+/// anything else is refused.
+fn answer(verb: u8, option: u8) -> Vec<u8> {
+    match (verb, option) {
+        (DO, ECHO | SGA) | (WILL, SGA | NAWS) | (DONT | WONT, _) => vec![],
+        (DO, _) => vec![IAC, WONT, option],
+        (WILL, _) => vec![IAC, DONT, option],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(bytes: &[u8]) -> (Vec<Event>, Vec<u8>) {
+        Decoder::default().feed(bytes)
+    }
+
+    #[test]
+    fn a_literal_255_is_doubled_both_ways() {
+        assert_eq!(escape(b"a\xffb"), b"a\xff\xffb");
+        assert_eq!(data(b"a\xff\xffb").0, [Event::Data(b"a\xffb".to_vec())]);
+    }
+
+    #[test]
+    fn options_we_asked_for_need_no_answer() {
+        assert_eq!(data(&[IAC, DO, ECHO, IAC, DO, SGA, IAC, WILL, SGA, IAC, WILL, NAWS]), (vec![], vec![]));
+        assert_eq!(data(&[IAC, DONT, ECHO, IAC, WONT, NAWS]), (vec![], vec![]));
+    }
+
+    #[test]
+    fn other_options_are_refused() {
+        // Terminal type and linemode
+        assert_eq!(data(&[IAC, DO, 24]).1, [IAC, WONT, 24]);
+        assert_eq!(data(&[IAC, WILL, 34]).1, [IAC, DONT, 34]);
+        // Commands in between keystrokes are dropped
+        assert_eq!(data(&[b'a', IAC, DO, 24, b'b']), (vec![Event::Data(b"ab".to_vec())], vec![IAC, WONT, 24]));
+    }
+
+    #[test]
+    fn every_line_end_is_one_cr() {
+        for bytes in [&b"ls\r\n"[..], b"ls\r\0", b"ls\r", b"ls\n"] {
+            assert_eq!(data(bytes).0, [Event::Data(b"ls\r".to_vec())], "{:?}", bytes);
+        }
+        assert_eq!(data(b"a\r\r\n").0, [Event::Data(b"a\r\r".to_vec())]);
+    }
+
+    #[test]
+    fn state_carries_over_between_reads() {
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.feed(b"ls\r").0, [Event::Data(b"ls\r".to_vec())]);
+        assert_eq!(decoder.feed(b"\n").0, []);
+        assert_eq!(decoder.feed(&[IAC]), (vec![], vec![]));
+        assert_eq!(decoder.feed(&[DO]), (vec![], vec![]));
+        assert_eq!(decoder.feed(&[24]).1, [IAC, WONT, 24]);
+    }
+
+    #[test]
+    fn naws_reports_the_window_size() {
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.feed(&[b'x', IAC, SB, NAWS, 0, 80]).0, [Event::Data(b"x".to_vec())]);
+        assert_eq!(decoder.feed(&[0, 24, IAC, SE, b'y']).0, [Event::Resize(80, 24), Event::Data(b"y".to_vec())]);
+        // A 255 in the size comes doubled
+        assert_eq!(data(&[IAC, SB, NAWS, 1, IAC, IAC, 0, 50, IAC, SE]).0, [Event::Resize(511, 50)]);
+        // Other subnegotiations are ignored
+        assert_eq!(data(&[IAC, SB, 24, 0, b'x', IAC, SE]).0, []);
+    }
+
+    #[test]
+    fn long_subnegotiations_are_not_buffered() {
+        let mut decoder = Decoder::default();
+        decoder.feed(&[IAC, SB, NAWS]);
+        for _ in 0..1000 {
+            assert_eq!(decoder.feed(&[0; 1024]).0, []);
+        }
+        assert!(decoder.sub.len() <= SUB_MAX);
+        // It is still no window size, and what follows is read as usual
+        assert_eq!(decoder.feed(&[IAC, SE, b'x']).0, [Event::Data(b"x".to_vec())]);
+    }
+}
@@ -0,0 +1,236 @@
+//! The HTTP client commands fetch through: a timeout on every attempt,
+//! retries with exponential backoff when the network or the server fails,
+//! and an in-memory LRU cache of responses that carried an `ETag` or a
+//! `Last-Modified`, revalidated on every request so that an unchanged page
+//! comes back as a bodyless 304.
+//!
+//! One client serves the whole thread, so `cat bio` run twice downloads the
+//! page once.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::task::Poll;
+use std::time::Duration;
+use crate::platform::{self, FetchError, Response};
+
+/// Requests `Client::get_all` keeps in flight at once.
+const PARALLEL: usize = 6;
+
+/// Why a request gave no usable response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    InvalidUrl,
+    /// The request never completed (offline, refused, blocked by CORS...).
+    Network(String),
+    /// No response within the timeout, on every attempt.
+    Timeout,
+    /// The server answered with this status, which is not a success.
+    Status(u16),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::InvalidUrl => write!(f, "invalid URL"),
+            HttpError::Network(reason) => write!(f, "network error: {}", reason),
+            HttpError::Timeout => write!(f, "timed out"),
+            HttpError::Status(status) => write!(f, "error {}", status),
+        }
+    }
+}
+
+impl HttpError {
+    /// Whether trying again might help.
+    fn is_transient(&self) -> bool {
+        match self {
+            HttpError::Network(_) | HttpError::Timeout => true,
+            HttpError::Status(status) => *status == 429 || *status >= 500,
+            HttpError::InvalidUrl => false,
+        }
+    }
+}
+
+impl From<FetchError> for HttpError {
+    fn from(e: FetchError) -> Self {
+        match e {
+            FetchError::InvalidUrl => HttpError::InvalidUrl,
+            FetchError::Network(reason) => HttpError::Network(reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// How long one attempt may take.
+    pub timeout: Duration,
+    /// Attempts after the first one, for transient errors.
+    pub retries: u32,
+    /// Wait before the first retry, doubled before each of the next ones.
+    pub backoff: Duration,
+    /// Responses kept in the cache; 0 turns it off.
+    pub cache_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            retries: 2,
+            backoff: Duration::from_millis(250),
+            cache_size: 32,
+        }
+    }
+}
+
+/// A cached response, with what the server can tell it apart by.
+struct Entry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+pub struct Client {
+    config: Config,
+    /// Least recently used first.
+    cache: RefCell<Vec<Entry>>,
+}
+
+thread_local! {
+    static CLIENT: RefCell<Rc<Client>> = RefCell::new(Rc::new(Client::new(Config::default())));
+}
+
+/// The client for this thread.
+pub fn client() -> Rc<Client> {
+    CLIENT.with(|client| client.borrow().clone())
+}
+
+/// Replaces the client for this thread, emptying the cache.
+pub fn configure(config: Config) {
+    CLIENT.with(|client| *client.borrow_mut() = Rc::new(Client::new(config)));
+}
+
+/// `future`, or `None` if `duration` passes first.
+async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut sleep = platform::current().sleep(duration);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        sleep.as_mut().poll(cx).map(|_| None)
+    }).await
+}
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        Self { config, cache: RefCell::new(Vec::new()) }
+    }
+
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// GETs `url` and returns the body of the successful response, from the
+    /// cache when the server says it has not changed.
+    pub async fn get(&self, url: &str) -> Result<String, HttpError> {
+        let mut delay = self.config.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.attempt(url).await {
+                Err(e) if e.is_transient() && attempt < self.config.retries => {
+                    attempt += 1;
+                    platform::current().log(&format!("http: {}: {}, retry {} in {:?}", url, e, attempt, delay));
+                    platform::current().sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// GETs every URL, a few at a time, calling `progress` with how many
+    /// are done after each one. Results come in the order of `urls`.
+    pub async fn get_all(&self, urls: &[String], mut progress: impl FnMut(usize)) -> Vec<Result<String, HttpError>> {
+        let mut requests: Vec<Pin<Box<_>>> = urls.iter().map(|url| Box::pin(self.get(url))).collect();
+        let mut results: Vec<Option<Result<String, HttpError>>> = urls.iter().map(|_| None).collect();
+        // Requests do nothing until polled, so only the first few are started
+        let mut started = PARALLEL.min(urls.len());
+        let mut done = 0;
+        poll_fn(|cx| {
+            let mut i = 0;
+            while i < started {
+                if results[i].is_none() {
+                    if let Poll::Ready(result) = requests[i].as_mut().poll(cx) {
+                        results[i] = Some(result);
+                        done += 1;
+                        started = (started + 1).min(urls.len());
+                        progress(done);
+                    }
+                }
+                i += 1;
+            }
+            if done == urls.len() { Poll::Ready(()) } else { Poll::Pending }
+        }).await;
+        results.into_iter().flatten().collect()
+    }
+
+    async fn attempt(&self, url: &str) -> Result<String, HttpError> {
+        let headers = self.validators(url);
+        let fetch = platform::current().fetch(url, &headers);
+        let response = timeout(self.config.timeout, fetch).await.ok_or(HttpError::Timeout)??;
+
+        if response.status == 304 {
+            if let Some(body) = self.cached(url) {
+                return Ok(body);
+            }
+        }
+        if !response.ok() {
+            return Err(HttpError::Status(response.status));
+        }
+        self.store(url, &response);
+        Ok(response.body)
+    }
+
+    /// Headers asking the server to answer 304 if our copy is still current.
+    fn validators(&self, url: &str) -> Vec<(String, String)> {
+        let cache = self.cache.borrow();
+        let Some(entry) = cache.iter().find(|entry| entry.url == url) else { return Vec::new() };
+        let mut headers = Vec::new();
+        if let Some(etag) = &entry.etag {
+            headers.push(("If-None-Match".to_string(), etag.clone()));
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            headers.push(("If-Modified-Since".to_string(), last_modified.clone()));
+        }
+        headers
+    }
+
+    /// The cached body of `url`, which becomes the most recently used.
+    fn cached(&self, url: &str) -> Option<String> {
+        let mut cache = self.cache.borrow_mut();
+        let i = cache.iter().position(|entry| entry.url == url)?;
+        let entry = cache.remove(i);
+        let body = entry.body.clone();
+        cache.push(entry);
+        Some(body)
+    }
+
+    /// Keeps `response` if it can be revalidated, evicting the least recently used.
+    fn store(&self, url: &str, response: &Response) {
+        let mut cache = self.cache.borrow_mut();
+        cache.retain(|entry| entry.url != url);
+        let etag = response.header("ETag").map(String::from);
+        let last_modified = response.header("Last-Modified").map(String::from);
+        if self.config.cache_size == 0 || (etag.is_none() && last_modified.is_none()) {
+            return;
+        }
+        if cache.len() >= self.config.cache_size {
+            cache.remove(0);
+        }
+        cache.push(Entry { url: url.to_string(), etag, last_modified, body: response.body.clone() });
+    }
+}
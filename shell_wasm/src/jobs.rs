@@ -0,0 +1,250 @@
+//! Job control. A command line that leaves work running becomes a job: in the
+//! foreground until it finishes, or in the background when it ended with `&`,
+//! where it stays listed until its end has been reported. Killing a job drops
+//! its work, which is how in-flight requests get aborted.
+
+use std::cell::{Cell, RefCell};
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Poll, Waker};
+
+/// Exit status of a job stopped by `_interrupt` (Ctrl-C).
+pub const INTERRUPTED: i32 = 130;
+/// Exit status of a job stopped by `kill`.
+pub const TERMINATED: i32 = 143;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Done(i32),
+    /// Killed, with the status it exits with.
+    Terminated(i32),
+}
+
+struct Job {
+    id: u32,
+    command: String,
+    background: bool,
+    state: JobState,
+    /// Wakes the job's work so it notices it was killed.
+    task: Option<Waker>,
+    /// `fg` and `wait` calls waiting for the job to end.
+    waiters: Vec<Waker>,
+}
+
+impl Job {
+    /// A line as `jobs` prints it, e.g. `[1]+  Running                 cat bio &`.
+    fn line(&self, marker: char) -> String {
+        let (state, suffix) = match self.state {
+            JobState::Running => ("Running".to_string(), " &"),
+            JobState::Done(0) => ("Done".to_string(), ""),
+            JobState::Done(status) => (format!("Exit {}", status), ""),
+            JobState::Terminated(_) => ("Terminated".to_string(), ""),
+        };
+        format!("[{}]{}  {:<24}{}{}", self.id, marker, state, self.command, suffix)
+    }
+}
+
+thread_local! {
+    static JOBS: RefCell<Vec<Job>> = const { RefCell::new(Vec::new()) };
+    /// The job whose work is being polled.
+    static CURRENT: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// `+` for the current job (the newest one), `-` for the one before it.
+fn marker(jobs: &[Job], id: u32) -> char {
+    let mut ids: Vec<u32> = jobs.iter().map(|job| job.id).collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    match ids.iter().position(|&i| i == id) {
+        Some(0) => '+',
+        Some(1) => '-',
+        _ => ' ',
+    }
+}
+
+/// Takes the job's wakers once it stopped running, for the caller to wake
+/// after letting go of the table.
+fn wakers(job: &mut Job) -> Vec<Waker> {
+    let mut wakers: Vec<Waker> = job.waiters.drain(..).collect();
+    wakers.extend(job.task.take());
+    wakers
+}
+
+/// Numbers a job that is starting: one more than the newest job, so numbers
+/// are reused once every job has been reported.
+pub fn start(command: &str, background: bool) -> u32 {
+    JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let id = jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        jobs.push(Job { id, command: command.to_string(), background, state: JobState::Running, task: None, waiters: Vec::new() });
+        id
+    })
+}
+
+/// Records that the work of job `id` ended. A foreground job is gone at
+/// once; a background one waits to be reported.
+pub fn finish(id: u32, status: i32) {
+    let wakers = JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let Some(i) = jobs.iter().position(|job| job.id == id && job.state == JobState::Running) else {
+            return Vec::new();
+        };
+        let wakers = wakers(&mut jobs[i]);
+        if jobs[i].background {
+            jobs[i].state = JobState::Done(status);
+        } else {
+            jobs.remove(i);
+        }
+        wakers
+    });
+    wakers.into_iter().for_each(Waker::wake);
+}
+
+/// Runs `work` as job `id` until it finishes, or until the job is killed,
+/// which drops the work and gives the status it was killed with.
+pub async fn run<F: Future + Unpin>(id: u32, mut work: F) -> Result<F::Output, i32> {
+    poll_fn(|cx| {
+        let killed = JOBS.with(|jobs| {
+            let mut jobs = jobs.borrow_mut();
+            match jobs.iter_mut().find(|job| job.id == id) {
+                Some(Job { state: JobState::Terminated(status), .. }) => Some(*status),
+                Some(job) => {
+                    job.task = Some(cx.waker().clone());
+                    None
+                }
+                // Killed and already reported
+                None => Some(TERMINATED),
+            }
+        });
+        match killed {
+            Some(status) => Poll::Ready(Err(status)),
+            None => {
+                let outer = CURRENT.replace(Some(id));
+                let poll = Pin::new(&mut work).poll(cx);
+                CURRENT.set(outer);
+                poll.map(Ok)
+            }
+        }
+    }).await
+}
+
+/// The job running the work being polled, e.g. for it to report progress.
+pub fn current() -> Option<u32> {
+    CURRENT.get()
+}
+
+/// The command line job `id` was started by.
+pub fn command(id: u32) -> Option<String> {
+    JOBS.with(|jobs| jobs.borrow().iter().find(|job| job.id == id).map(|job| job.command.clone()))
+}
+
+/// Stops job `id` if it is running. Returns its line, which reports it, or
+/// `None` when there is no such job.
+pub fn kill(id: u32, status: i32) -> Option<String> {
+    let wakers = JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let job = jobs.iter_mut().find(|job| job.id == id)?;
+        if job.state != JobState::Running {
+            return Some(Vec::new());
+        }
+        job.state = JobState::Terminated(status);
+        Some(wakers(job))
+    })?;
+    wakers.into_iter().for_each(Waker::wake);
+    report(id)
+}
+
+/// Kills the foreground job, as Ctrl-C does, and reports it.
+pub fn interrupt() -> Option<String> {
+    kill(foreground()?, INTERRUPTED)
+}
+
+/// The job the host is waiting for, if any.
+pub fn foreground() -> Option<u32> {
+    JOBS.with(|jobs| {
+        jobs.borrow().iter().find(|job| !job.background && job.state == JobState::Running).map(|job| job.id)
+    })
+}
+
+/// Moves job `id` to the foreground. Returns its command, or `None` when
+/// there is no such job.
+pub fn resume(id: u32) -> Option<String> {
+    JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let job = jobs.iter_mut().find(|job| job.id == id)?;
+        job.background = false;
+        Some(job.command.clone())
+    })
+}
+
+pub fn state(id: u32) -> Option<JobState> {
+    JOBS.with(|jobs| jobs.borrow().iter().find(|job| job.id == id).map(|job| job.state))
+}
+
+/// Job `id`'s line. A job that is no longer running is forgotten once reported.
+pub fn report(id: u32) -> Option<String> {
+    JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let i = jobs.iter().position(|job| job.id == id)?;
+        let line = jobs[i].line(marker(&jobs, id));
+        if jobs[i].state != JobState::Running {
+            jobs.remove(i);
+        }
+        Some(line)
+    })
+}
+
+/// Lines for every job, oldest first, reporting those that ended.
+pub fn list() -> Vec<String> {
+    ids(|_| true).into_iter().filter_map(report).collect()
+}
+
+/// Lines for the background jobs that ended since they were last listed,
+/// for the shell to print before the next prompt.
+pub fn finished() -> Vec<String> {
+    ids(|job| job.background && job.state != JobState::Running).into_iter().filter_map(report).collect()
+}
+
+/// Background jobs, oldest first.
+pub fn background() -> Vec<u32> {
+    ids(|job| job.background)
+}
+
+fn ids(filter: impl Fn(&Job) -> bool) -> Vec<u32> {
+    JOBS.with(|jobs| jobs.borrow().iter().filter(|job| filter(job)).map(|job| job.id).collect())
+}
+
+/// Resolves to the exit status of job `id` once it ends; at once if it has.
+pub fn wait(id: u32) -> impl Future<Output = i32> {
+    poll_fn(move |cx| {
+        JOBS.with(|jobs| {
+            let mut jobs = jobs.borrow_mut();
+            match jobs.iter_mut().find(|job| job.id == id) {
+                Some(Job { state: JobState::Done(status) | JobState::Terminated(status), .. }) => Poll::Ready(*status),
+                Some(job) => {
+                    job.waiters.push(cx.waker().clone());
+                    Poll::Pending
+                }
+                None => Poll::Ready(127),
+            }
+        })
+    })
+}
+
+/// The job a spec such as `%2`, `2`, `%%`, `%+` or `%-` names; the current
+/// job when there is no spec.
+pub fn parse_spec(spec: Option<&str>) -> Option<u32> {
+    let newest = |skip: usize| {
+        let mut ids = ids(|_| true);
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        ids.get(skip).copied()
+    };
+    match spec {
+        None | Some("%%") | Some("%+") | Some("%") => newest(0),
+        Some("%-") => newest(1),
+        Some(spec) => {
+            let id = spec.strip_prefix('%').unwrap_or(spec).parse().ok()?;
+            state(id).map(|_| id)
+        }
+    }
+}
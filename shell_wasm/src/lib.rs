@@ -8,6 +8,17 @@ pub mod bbs;
 pub mod terminal;
 pub mod fs;
 pub mod completer;
+pub mod parser;
+pub mod expand;
+pub mod history;
+pub mod glob;
+pub mod platform;
+pub mod catalog;
+pub mod host;
+pub mod events;
+pub mod jobs;
+pub mod http;
+pub mod search;
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +33,12 @@ pub struct WasmResponse {
     pub lines: Vec<WasmLine>,
     pub state: state::SystemState,
     pub handled: bool,
+    /// Exit status of the command line, also kept in the state as `$?`.
+    pub status: i32,
+    /// The foreground job still running, if any: the page waits for its
+    /// `done` event, or sends `_interrupt` on Ctrl-C. Events of every job
+    /// arrive through `drain_events`.
+    pub job: Option<u32>,
 }
 
 #[wasm_bindgen]
@@ -31,18 +48,36 @@ pub fn process_input(js_state: JsValue, input: &str) -> JsValue {
     let state = match state_res {
         Ok(s) => s,
         Err(e) => {
-            web_sys::console::error_1(&format!("WASM: Critical state deserialization failure: {}", e).into());
+            platform::current().error(&format!("WASM: Critical state deserialization failure: {}", e));
             state::SystemState::default()
         }
     };
     
     let result = shell::process_input(state, input);
+
+    // Background output (fetches and the pipelines waiting on them) is queued when ready
+    if let Some(pending) = result.pending {
+        shell::start_job(input, pending, false);
+    }
+    let job = jobs::foreground();
     
     let response = WasmResponse {
         lines: result.lines,
         state: result.next_state,
         handled: result.handled,
+        status: result.status,
+        job,
     };
 
-    serde_wasm_bindgen::to_value(&response).unwrap()
+    // User files travel as a plain object so the JS side can keep editing them
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    response.serialize(&serializer).unwrap()
+}
+
+/// Output of background jobs queued since the last call, oldest first. The
+/// page calls it when the core signals `terminalSystem.onEvents`.
+#[wasm_bindgen]
+pub fn drain_events() -> JsValue {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    events::drain().serialize(&serializer).unwrap()
 }
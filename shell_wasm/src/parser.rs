@@ -0,0 +1,590 @@
+//! Lexer and parser for the ttsh command language.
+//!
+//! Input is split into tokens honouring single quotes, double quotes,
+//! backslash escapes and `#` comments, then grouped into lists of pipelines
+//! joined by `;`, `&`, `&&` and `||`.
+//! Every word keeps track of how each of its pieces was quoted so that later
+//! stages can decide what is subject to expansion.
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How a piece of a word was written in the source line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote {
+    /// Bare text, subject to every expansion.
+    None,
+    /// Text inside '...' or escaped with a backslash; taken literally.
+    Single,
+    /// Text inside "...".
+    Double,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordPart {
+    pub text: String,
+    pub quote: Quote,
+}
+
+/// A single shell word, e.g. `"my post"` or `/posts/a\ b`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    fn push(&mut self, c: char, quote: Quote) {
+        match self.parts.last_mut() {
+            Some(part) if part.quote == quote => part.text.push(c),
+            _ => self.parts.push(WordPart { text: c.to_string(), quote }),
+        }
+    }
+
+    /// The word with all quoting removed.
+    pub fn text(&self) -> String {
+        self.parts.iter().map(|p| p.text.as_str()).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(Word),
+    Pipe,
+    /// `&&`
+    AndIf,
+    /// `||`
+    OrIf,
+    /// `;`
+    Semi,
+    /// `&`
+    Amp,
+    /// `>`
+    Great,
+    /// `>>`
+    DGreat,
+    /// `<`
+    Less,
+    Newline,
+}
+
+impl Token {
+    /// How the token is written, for error messages.
+    fn symbol(&self) -> &'static str {
+        match self {
+            Token::Word(_) => "word",
+            Token::Pipe => "|",
+            Token::AndIf => "&&",
+            Token::OrIf => "||",
+            Token::Semi => ";",
+            Token::Amp => "&",
+            Token::Great => ">",
+            Token::DGreat => ">>",
+            Token::Less => "<",
+            Token::Newline => "newline",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectKind {
+    /// `< file`
+    Input,
+    /// `> file`
+    Output,
+    /// `>> file`
+    Append,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: Word,
+}
+
+/// A command name followed by its arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SimpleCommand {
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+impl SimpleCommand {
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.redirects.is_empty()
+    }
+
+    /// The unquoted words of the command, name first.
+    pub fn argv(&self) -> Vec<String> {
+        self.words.iter().map(Word::text).collect()
+    }
+}
+
+/// Commands joined with `|`, each one reading the previous one's output.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pipeline {
+    pub commands: Vec<SimpleCommand>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    /// `&&`: run when the previous pipeline succeeded.
+    And,
+    /// `||`: run when the previous pipeline failed.
+    Or,
+}
+
+/// Pipelines joined with `&&` and `||`, e.g. `cat bio && echo ok || echo failed`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AndOrList {
+    pub first: Pipeline,
+    pub rest: Vec<(Connector, Pipeline)>,
+    /// Ended with `&`: runs as a background job.
+    pub background: bool,
+}
+
+/// Writes the AST back as source, quoting words the way they were quoted,
+/// e.g. to name a job by its command line.
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part.quote {
+                Quote::None => write!(f, "{}", part.text)?,
+                Quote::Single => write!(f, "'{}'", part.text.replace('\'', "'\\''"))?,
+                Quote::Double => write!(f, "\"{}\"", part.text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SimpleCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let words = self.words.iter().map(|w| w.to_string());
+        let redirects = self.redirects.iter().map(|r| {
+            let op = match r.kind {
+                RedirectKind::Input => "<",
+                RedirectKind::Output => ">",
+                RedirectKind::Append => ">>",
+            };
+            format!("{} {}", op, r.target)
+        });
+        write!(f, "{}", words.chain(redirects).collect::<Vec<_>>().join(" "))
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.commands.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" | "))
+    }
+}
+
+/// Without the trailing `&` of a background list.
+impl fmt::Display for AndOrList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.first)?;
+        for (connector, pipeline) in &self.rest {
+            let op = match connector {
+                Connector::And => "&&",
+                Connector::Or => "||",
+            };
+            write!(f, " {} {}", op, pipeline)?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed input line: the lists it contains, separated by `;`, `&` or newlines.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Script {
+    pub lists: Vec<AndOrList>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ttsh: syntax error: {}", self.message)
+    }
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// When set, unterminated quotes end the word instead of failing.
+    lenient: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, lenient: bool) -> Self {
+        Self { chars: input.chars().peekable(), lenient }
+    }
+
+    fn tokens(mut self) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '\n' => {
+                    self.chars.next();
+                    tokens.push(Token::Newline);
+                }
+                c if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                '|' => {
+                    self.chars.next();
+                    if self.chars.peek() == Some(&'|') {
+                        self.chars.next();
+                        tokens.push(Token::OrIf);
+                    } else {
+                        tokens.push(Token::Pipe);
+                    }
+                }
+                '&' => {
+                    self.chars.next();
+                    if self.chars.peek() == Some(&'&') {
+                        self.chars.next();
+                        tokens.push(Token::AndIf);
+                    } else {
+                        tokens.push(Token::Amp);
+                    }
+                }
+                ';' => {
+                    self.chars.next();
+                    tokens.push(Token::Semi);
+                }
+                '>' => {
+                    self.chars.next();
+                    if self.chars.peek() == Some(&'>') {
+                        self.chars.next();
+                        tokens.push(Token::DGreat);
+                    } else {
+                        tokens.push(Token::Great);
+                    }
+                }
+                '<' => {
+                    self.chars.next();
+                    tokens.push(Token::Less);
+                }
+                '#' => {
+                    while let Some(&c) = self.chars.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.chars.next();
+                    }
+                }
+                _ => tokens.push(Token::Word(self.word()?)),
+            }
+        }
+        Ok(tokens)
+    }
+
+    fn word(&mut self) -> Result<Word, ParseError> {
+        let mut word = Word::default();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                c if c.is_whitespace() || is_operator(c) => break,
+                '\'' => {
+                    self.chars.next();
+                    self.single_quoted(&mut word)?;
+                }
+                '"' => {
+                    self.chars.next();
+                    self.double_quoted(&mut word)?;
+                }
+                '\\' => {
+                    self.chars.next();
+                    match self.chars.next() {
+                        // Line continuation
+                        Some('\n') => {}
+                        Some(escaped) => word.push(escaped, Quote::Single),
+                        None => {}
+                    }
+                }
+                '$' => {
+                    self.chars.next();
+                    word.push('$', Quote::None);
+                    self.braced_parameter(&mut word, Quote::None)?;
+                }
+                _ => {
+                    self.chars.next();
+                    word.push(c, Quote::None);
+                }
+            }
+        }
+        Ok(word)
+    }
+
+    fn single_quoted(&mut self, word: &mut Word) -> Result<(), ParseError> {
+        // An empty pair of quotes still produces a (empty) word part.
+        word.parts.push(WordPart { text: String::new(), quote: Quote::Single });
+        loop {
+            match self.chars.next() {
+                Some('\'') => return Ok(()),
+                Some(c) => word.push(c, Quote::Single),
+                None => return self.unterminated('\''),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self, word: &mut Word) -> Result<(), ParseError> {
+        word.parts.push(WordPart { text: String::new(), quote: Quote::Double });
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(()),
+                Some('\\') => match self.chars.peek() {
+                    Some(&c) if matches!(c, '"' | '\\' | '$' | '`') => {
+                        self.chars.next();
+                        word.push(c, Quote::Single);
+                    }
+                    Some('\n') => {
+                        self.chars.next();
+                    }
+                    _ => word.push('\\', Quote::Double),
+                },
+                Some('$') => {
+                    word.push('$', Quote::Double);
+                    self.braced_parameter(word, Quote::Double)?;
+                }
+                Some(c) => word.push(c, Quote::Double),
+                None => return self.unterminated('"'),
+            }
+        }
+    }
+
+    /// Copies a `${...}` parameter verbatim, spaces included, so that
+    /// `${NAME:-some default}` stays one word for the expansion stage.
+    fn braced_parameter(&mut self, word: &mut Word, quote: Quote) -> Result<(), ParseError> {
+        if self.chars.peek() != Some(&'{') {
+            return Ok(());
+        }
+        for c in self.chars.by_ref() {
+            word.push(c, quote);
+            if c == '}' {
+                return Ok(());
+            }
+        }
+        if self.lenient {
+            Ok(())
+        } else {
+            Err(ParseError { message: "missing `}' in parameter expansion".to_string() })
+        }
+    }
+
+    fn unterminated(&self, quote: char) -> Result<(), ParseError> {
+        if self.lenient {
+            Ok(())
+        } else {
+            Err(ParseError { message: format!("unterminated {} quote", if quote == '\'' { "single" } else { "double" }) })
+        }
+    }
+}
+
+fn is_operator(c: char) -> bool {
+    matches!(c, '|' | '&' | ';' | '>' | '<')
+}
+
+/// Splits `input` into tokens.
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    Lexer::new(input, false).tokens()
+}
+
+/// The unquoted words of the last command in a possibly incomplete line:
+/// after a `|`, `;`, `&`, `&&` or `||` a new command starts.
+///
+/// Used by completion, where the user may still be typing a quoted word.
+pub fn split_partial(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for token in Lexer::new(input, true).tokens().unwrap_or_default() {
+        match token {
+            Token::Word(word) => words.push(word.text()),
+            Token::Great | Token::DGreat | Token::Less => {}
+            _ => words.clear(),
+        }
+    }
+    words
+}
+
+fn unexpected(token: &str) -> ParseError {
+    ParseError { message: format!("unexpected token `{}'", token) }
+}
+
+/// Builds the AST while walking the token stream.
+#[derive(Default)]
+struct Builder {
+    script: Script,
+    list: Option<AndOrList>,
+    /// Connector waiting for the pipeline that follows it.
+    connector: Option<Connector>,
+    pipeline: Pipeline,
+    command: SimpleCommand,
+}
+
+impl Builder {
+    fn end_command(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.command.is_empty() {
+            return Err(unexpected(token));
+        }
+        self.pipeline.commands.push(std::mem::take(&mut self.command));
+        Ok(())
+    }
+
+    fn end_pipeline(&mut self, token: &str) -> Result<(), ParseError> {
+        self.end_command(token)?;
+        let pipeline = std::mem::take(&mut self.pipeline);
+        match (&mut self.list, self.connector.take()) {
+            (Some(list), Some(connector)) => list.rest.push((connector, pipeline)),
+            (list, _) => *list = Some(AndOrList { first: pipeline, rest: Vec::new(), background: false }),
+        }
+        Ok(())
+    }
+
+    /// Ends the current list at `;`, `&`, a newline or the end of input.
+    fn end_list(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.command.is_empty() && self.pipeline.commands.is_empty() && self.connector.is_none() {
+            // Blank lines and trailing `;` are fine, a `;` with nothing before it is not
+            let misplaced = token == "&" || (token == ";" && self.script.lists.is_empty());
+            return if misplaced { Err(unexpected(token)) } else { Ok(()) };
+        }
+        self.end_pipeline(token)?;
+        if let Some(mut list) = self.list.take() {
+            list.background = token == "&";
+            self.script.lists.push(list);
+        }
+        Ok(())
+    }
+}
+
+/// Parses an input line into a [`Script`].
+pub fn parse(input: &str) -> Result<Script, ParseError> {
+    let mut b = Builder::default();
+    let mut tokens = tokenize(input)?.into_iter();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Word(word) => b.command.words.push(word),
+            Token::Great | Token::DGreat | Token::Less => {
+                let kind = match token {
+                    Token::Great => RedirectKind::Output,
+                    Token::DGreat => RedirectKind::Append,
+                    _ => RedirectKind::Input,
+                };
+                match tokens.next() {
+                    Some(Token::Word(target)) => b.command.redirects.push(Redirect { kind, target }),
+                    Some(other) => return Err(unexpected(other.symbol())),
+                    None => return Err(unexpected("newline")),
+                }
+            }
+            Token::Pipe => b.end_command("|")?,
+            Token::AndIf | Token::OrIf => {
+                b.end_pipeline(token.symbol())?;
+                b.connector = Some(if token == Token::AndIf { Connector::And } else { Connector::Or });
+            }
+            Token::Semi => b.end_list(";")?,
+            Token::Amp => b.end_list("&")?,
+            Token::Newline => b.end_list("newline")?,
+        }
+    }
+
+    if b.command.is_empty() && (!b.pipeline.commands.is_empty() || b.connector.is_some()) {
+        return Err(ParseError { message: "unexpected end of input".to_string() });
+    }
+    b.end_list("newline")?;
+    Ok(b.script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The unquoted words of each command, list by list.
+    fn argv(input: &str) -> Vec<Vec<String>> {
+        parse(input).unwrap().lists.iter()
+            .flat_map(|list| std::iter::once(&list.first).chain(list.rest.iter().map(|(_, pipeline)| pipeline)))
+            .flat_map(|pipeline| pipeline.commands.iter().map(SimpleCommand::argv))
+            .collect()
+    }
+
+    fn error(input: &str) -> String {
+        parse(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn quotes_keep_words_together() {
+        assert_eq!(argv(r#"echo 'a  b' "c $HOME" d'e'"f""#), [["echo", "a  b", "c $HOME", "def"]]);
+        assert_eq!(argv("echo '' \"\""), [["echo", "", ""]]);
+        assert_eq!(argv(r#"echo "it's" 'say "hi"'"#), [["echo", "it's", "say \"hi\""]]);
+
+        let word = &parse(r#"a'b'"c""#).unwrap().lists[0].first.commands[0].words[0];
+        let quotes: Vec<Quote> = word.parts.iter().map(|part| part.quote).collect();
+        assert_eq!(quotes, [Quote::None, Quote::Single, Quote::Double]);
+    }
+
+    #[test]
+    fn backslashes_escape_one_character() {
+        assert_eq!(argv(r"cat /posts/a\ b \; \|"), [["cat", "/posts/a b", ";", "|"]]);
+        assert_eq!(argv(r#"echo "\"\$x\\" "\n""#), [["echo", "\"$x\\", "\\n"]]);
+        assert_eq!(argv("echo a\\\nb"), [["echo", "ab"]]);
+    }
+
+    #[test]
+    fn comments_run_to_the_end_of_the_line() {
+        assert_eq!(argv("ls # list\necho '#not' a#b"), [vec!["ls"], vec!["echo", "#not", "a#b"]]);
+        assert!(parse("# only a comment").unwrap().lists.is_empty());
+    }
+
+    #[test]
+    fn operators_build_lists_and_pipelines() {
+        let script = parse("cat bio | wc -l && echo ok || echo no; ls & date").unwrap();
+        assert_eq!(script.lists.len(), 3);
+        let first = &script.lists[0];
+        assert_eq!(first.first.commands.len(), 2);
+        let connectors: Vec<Connector> = first.rest.iter().map(|(connector, _)| *connector).collect();
+        assert_eq!(connectors, [Connector::And, Connector::Or]);
+        assert!(!first.background);
+        assert!(script.lists[1].background);
+        assert_eq!(first.to_string(), "cat bio | wc -l && echo ok || echo no");
+        assert_eq!(argv("a;b||c"), [["a"], ["b"], ["c"]]);
+    }
+
+    #[test]
+    fn redirects_take_the_next_word() {
+        let script = parse("sort < in > 'my out' >> log").unwrap();
+        let command = &script.lists[0].first.commands[0];
+        assert_eq!(command.argv(), ["sort"]);
+        let redirects: Vec<(RedirectKind, String)> = command.redirects.iter().map(|r| (r.kind, r.target.text())).collect();
+        assert_eq!(redirects, [
+            (RedirectKind::Input, "in".to_string()),
+            (RedirectKind::Output, "my out".to_string()),
+            (RedirectKind::Append, "log".to_string()),
+        ]);
+        assert_eq!(command.to_string(), "sort < in > 'my out' >> log");
+        assert_eq!(argv("> /tmp/empty"), [Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn syntax_errors_name_the_offending_token() {
+        assert_eq!(error("echo 'open"), "ttsh: syntax error: unterminated single quote");
+        assert_eq!(error("echo \"open"), "ttsh: syntax error: unterminated double quote");
+        assert_eq!(error("echo ${HOME"), "ttsh: syntax error: missing `}' in parameter expansion");
+        assert_eq!(error("| wc"), "ttsh: syntax error: unexpected token `|'");
+        assert_eq!(error("; ls"), "ttsh: syntax error: unexpected token `;'");
+        assert_eq!(error("ls && && ls"), "ttsh: syntax error: unexpected token `&&'");
+        assert_eq!(error("cat >"), "ttsh: syntax error: unexpected token `newline'");
+        assert_eq!(error("cat > | wc"), "ttsh: syntax error: unexpected token `|'");
+        assert_eq!(error("ls |"), "ttsh: syntax error: unexpected end of input");
+        assert_eq!(error("ls &&"), "ttsh: syntax error: unexpected end of input");
+    }
+
+    #[test]
+    fn partial_lines_split_leniently() {
+        assert_eq!(split_partial("cat 'my po"), ["cat", "my po"]);
+        assert_eq!(split_partial("echo ${HO"), ["echo", "${HO"]);
+        assert_eq!(split_partial("cat bio | gr"), ["gr"]);
+        assert_eq!(split_partial("ls; cd /po"), ["cd", "/po"]);
+        assert_eq!(split_partial("true && echo 'a|b' x||"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,184 @@
+//! A scripted host for tests: a fixed clock, a fixed sequence of random
+//! numbers, canned HTTP responses, jobs and timers that run when the test
+//! says so, and everything the shell logged kept for inspection. Background output is in
+//! [`crate::events`].
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use super::{Fetch, FetchError, Platform, Response, Sleep, Task};
+
+pub struct MockPlatform {
+    pub now: String,
+    randoms: Vec<f64>,
+    next_random: Cell<usize>,
+    responses: RefCell<HashMap<String, Result<Response, FetchError>>>,
+    /// Answers used up one per request before those in `responses`.
+    queued: RefCell<HashMap<String, VecDeque<Result<Response, FetchError>>>>,
+    /// URLs whose requests never answer.
+    hung: RefCell<Vec<String>>,
+    /// How many requests to hung URLs were cancelled.
+    pub aborted: Rc<Cell<usize>>,
+    /// Jobs still running.
+    tasks: RefCell<Vec<Task>>,
+    /// Every URL fetched, in order.
+    pub fetched: RefCell<Vec<String>>,
+    /// The headers sent with each of them.
+    pub sent_headers: RefCell<Vec<Vec<(String, String)>>>,
+    /// Every sleep asked for, in order.
+    pub slept: RefCell<Vec<Duration>>,
+    /// Bumped by [`pass_time`](Self::pass_time); sleeps end once it moved on.
+    clock: Rc<Cell<u64>>,
+    pub logs: RefCell<Vec<String>>,
+    /// How often the shell said events were queued.
+    pub notified: Cell<usize>,
+}
+
+impl Default for MockPlatform {
+    fn default() -> Self {
+        Self {
+            now: "Thu Jan 01 2026 00:00:00 GMT+0000 (Coordinated Universal Time)".to_string(),
+            randoms: Vec::new(),
+            next_random: Cell::new(0),
+            responses: RefCell::new(HashMap::new()),
+            queued: RefCell::new(HashMap::new()),
+            hung: RefCell::new(Vec::new()),
+            aborted: Rc::new(Cell::new(0)),
+            tasks: RefCell::new(Vec::new()),
+            fetched: RefCell::new(Vec::new()),
+            sent_headers: RefCell::new(Vec::new()),
+            slept: RefCell::new(Vec::new()),
+            clock: Rc::new(Cell::new(0)),
+            logs: RefCell::new(Vec::new()),
+            notified: Cell::new(0),
+        }
+    }
+}
+
+impl MockPlatform {
+    /// `random` returns these in turn, cycling; `0.0` when there are none.
+    pub fn with_randoms(mut self, randoms: Vec<f64>) -> Self {
+        self.randoms = randoms;
+        self
+    }
+
+    /// Answers GETs of `url` with a 200 and `body`.
+    pub fn respond(&self, url: &str, body: &str) {
+        self.respond_with(url, Ok(Response { status: 200, body: body.to_string(), ..Default::default() }));
+    }
+
+    /// Answers GETs of `url` with `response`. A request carrying the
+    /// response's `ETag` or `Last-Modified` back gets a 304 instead.
+    pub fn respond_with(&self, url: &str, response: Result<Response, FetchError>) {
+        self.responses.borrow_mut().insert(url.to_string(), response);
+    }
+
+    /// Answers the next GET of `url` with `response`, before those set up
+    /// with [`respond_with`](Self::respond_with).
+    pub fn respond_once(&self, url: &str, response: Result<Response, FetchError>) {
+        self.queued.borrow_mut().entry(url.to_string()).or_default().push_back(response);
+    }
+
+    /// Ends every sleep started so far; the jobs waiting on them go on once
+    /// [`run_jobs`](Self::run_jobs) polls them.
+    pub fn pass_time(&self) {
+        self.clock.set(self.clock.get() + 1);
+    }
+
+    /// Leaves GETs of `url` in flight until they are cancelled.
+    pub fn hang(&self, url: &str) {
+        self.hung.borrow_mut().push(url.to_string());
+    }
+
+    /// Polls the running jobs once. Returns how many are still running.
+    pub fn run_jobs(&self) -> usize {
+        let tasks = std::mem::take(&mut *self.tasks.borrow_mut());
+        let mut cx = Context::from_waker(Waker::noop());
+        let running: Vec<Task> = tasks.into_iter()
+            .filter_map(|mut task| task.as_mut().poll(&mut cx).is_pending().then_some(task))
+            .collect();
+        // Jobs may have been spawned meanwhile
+        let mut tasks = self.tasks.borrow_mut();
+        tasks.splice(0..0, running);
+        tasks.len()
+    }
+}
+
+/// Counts itself as aborted when dropped.
+struct Abort(Rc<Cell<usize>>);
+
+impl Drop for Abort {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+impl Platform for MockPlatform {
+    fn now(&self) -> String {
+        self.now.clone()
+    }
+
+    fn random(&self) -> f64 {
+        let i = self.next_random.get();
+        self.next_random.set(i + 1);
+        if self.randoms.is_empty() { 0.0 } else { self.randoms[i % self.randoms.len()] }
+    }
+
+    fn log(&self, message: &str) {
+        self.logs.borrow_mut().push(message.to_string());
+    }
+
+    fn error(&self, message: &str) {
+        self.logs.borrow_mut().push(format!("error: {}", message));
+    }
+
+    /// Unscripted URLs answer 404.
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Fetch {
+        self.fetched.borrow_mut().push(url.to_string());
+        self.sent_headers.borrow_mut().push(headers.to_vec());
+        if self.hung.borrow().iter().any(|hung| hung == url) {
+            let abort = Abort(self.aborted.clone());
+            return Box::pin(async move {
+                let _abort = abort;
+                std::future::pending().await
+            });
+        }
+        let queued = self.queued.borrow_mut().get_mut(url).and_then(VecDeque::pop_front);
+        let response = queued.or_else(|| self.responses.borrow().get(url).cloned())
+            .unwrap_or(Ok(Response { status: 404, ..Default::default() }))
+            .map(|response| {
+                let sent = |name: &str| headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str());
+                let unchanged = |request: &str, validator: &str| {
+                    response.header(validator).is_some_and(|value| sent(request) == Some(value))
+                };
+                if unchanged("If-None-Match", "ETag") || unchanged("If-Modified-Since", "Last-Modified") {
+                    Response { status: 304, headers: response.headers, body: String::new() }
+                } else {
+                    response
+                }
+            });
+        Box::pin(async move { response })
+    }
+
+    /// Lasts until the test calls [`pass_time`](Self::pass_time).
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.slept.borrow_mut().push(duration);
+        let clock = self.clock.clone();
+        let start = clock.get();
+        Box::pin(poll_fn(move |_| if clock.get() > start { Poll::Ready(()) } else { Poll::Pending }))
+    }
+
+    /// Polls the running jobs once, `task` included; [`run_jobs`](Self::run_jobs)
+    /// polls them again.
+    fn spawn(&self, task: Task) {
+        self.tasks.borrow_mut().push(task);
+        self.run_jobs();
+    }
+
+    fn notify(&self) {
+        self.notified.set(self.notified.get() + 1);
+    }
+}
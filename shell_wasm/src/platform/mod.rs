@@ -0,0 +1,101 @@
+//! Everything the shell needs from its host: the clock, randomness, logging,
+//! HTTP, a way to run jobs and a nudge when output arrives after a command
+//! returned.
+//!
+//! Commands reach the host through [`current`] instead of calling browser
+//! APIs, so the same core runs in the page ([`web::WebPlatform`]), natively
+//! ([`native::NativePlatform`]) and under test ([`mock::MockPlatform`]).
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Duration;
+
+pub mod web;
+pub mod native;
+pub mod mock;
+
+/// An HTTP response that arrived, whatever its status.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Response {
+    pub status: u16,
+    /// Names and values; platforms may keep only those the [`http`](crate::http) client reads.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The value of header `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Why no response arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchError {
+    InvalidUrl,
+    /// The request never completed (offline, refused, blocked by CORS...).
+    Network(String),
+}
+
+/// Dropping it before it resolves cancels the request.
+pub type Fetch = Pin<Box<dyn Future<Output = Result<Response, FetchError>>>>;
+
+/// The work of a [job](crate::jobs).
+pub type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+pub type Sleep = Pin<Box<dyn Future<Output = ()>>>;
+
+pub trait Platform {
+    /// The local date and time, as `date` prints it.
+    fn now(&self) -> String;
+
+    /// A random number in `[0, 1)`.
+    fn random(&self) -> f64;
+
+    /// Diagnostics for the developer, not the user.
+    fn log(&self, message: &str);
+
+    fn error(&self, message: &str);
+
+    /// GETs `url`, which may be relative to the site root, sending `headers`
+    /// along. Commands go through the [`http`](crate::http) client instead.
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Fetch;
+
+    /// Resolves once `duration` has passed.
+    fn sleep(&self, duration: Duration) -> Sleep;
+
+    /// Runs `task` to its end, alongside the shell where the host allows it.
+    fn spawn(&self, task: Task);
+
+    /// A background job queued events; the host should [`drain`](crate::events::drain) them.
+    fn notify(&self);
+}
+
+fn default_platform() -> Rc<dyn Platform> {
+    if cfg!(target_arch = "wasm32") {
+        Rc::new(web::WebPlatform)
+    } else {
+        Rc::new(native::NativePlatform::default())
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Rc<dyn Platform>> = RefCell::new(default_platform());
+}
+
+/// The platform the shell is running on.
+pub fn current() -> Rc<dyn Platform> {
+    CURRENT.with(|platform| platform.borrow().clone())
+}
+
+/// Replaces the platform for this thread, e.g. with a [`mock::MockPlatform`] in tests.
+pub fn install(platform: Rc<dyn Platform>) {
+    CURRENT.with(|current| *current.borrow_mut() = platform);
+}
@@ -0,0 +1,303 @@
+//! A native host: the system clock, a seeded PRNG, stderr logging, site files
+//! read from disk and plain `http://` requests over a socket. Output that
+//! arrives in the background is kept for the host to show where it belongs.
+
+use std::cell::Cell;
+use std::future::{poll_fn, Future};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::pin::pin;
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use super::{Fetch, FetchError, Platform, Response, Sleep, Task};
+use crate::http;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+pub struct NativePlatform {
+    /// Directory holding the built site, for URLs such as `/posts/x/index.json`.
+    pub site_root: Option<PathBuf>,
+    /// Whether logs go to stderr.
+    pub verbose: bool,
+    /// Whether requests may only reach public addresses, so that strangers
+    /// on a telnet or SSH host cannot probe the network behind it.
+    pub public_only: bool,
+    rng: Cell<u64>,
+}
+
+impl Default for NativePlatform {
+    fn default() -> Self {
+        Self::new(None, false)
+    }
+}
+
+impl NativePlatform {
+    pub fn new(site_root: Option<PathBuf>, verbose: bool) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        Self { site_root, verbose, public_only: false, rng: Cell::new(seed | 1) }
+    }
+
+    fn read_site_file(&self, url: &str) -> Result<Response, FetchError> {
+        let root = self.site_root.as_ref()
+            .ok_or_else(|| FetchError::Network("no site directory".to_string()))?;
+        let path = url.split(['?', '#']).next().unwrap_or(url).trim_start_matches('/');
+        if path.split('/').any(|part| part == "..") {
+            return Err(FetchError::InvalidUrl);
+        }
+        Ok(match std::fs::read_to_string(root.join(path)) {
+            Ok(body) => Response { status: 200, body, ..Default::default() },
+            Err(_) => Response { status: 404, ..Default::default() },
+        })
+    }
+}
+
+/// Days since 1970-01-01 as (year, month 1-12, day 1-31).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats a Unix time the way JavaScript's `Date.toString()` does, in UTC.
+pub fn format_time(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{} {} {:02} {} {:02}:{:02}:{:02} GMT+0000 (Coordinated Universal Time)",
+        WEEKDAYS[(days + 4).rem_euclid(7) as usize], MONTHS[month as usize - 1], day, year,
+        rem / 3600, rem / 60 % 60, rem % 60
+    )
+}
+
+/// Whether `ip` is reachable from anywhere, rather than this machine or its
+/// private network.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+                || ip.is_broadcast() || ip.is_multicast() || ip.is_documentation()
+                || a == 0 || (a == 100 && (64..128).contains(&b)) || a >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast()
+                || ip.is_unique_local() || ip.is_unicast_link_local()),
+        },
+    }
+}
+
+/// The addresses `authority` names, without those `public_only` rules out.
+fn resolve(authority: &str, public_only: bool) -> Result<Vec<SocketAddr>, FetchError> {
+    let with_port = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+    let addresses: Vec<SocketAddr> = with_port.to_socket_addrs()
+        .map_err(|e| FetchError::Network(e.to_string()))?
+        .filter(|address| !public_only || is_public(address.ip()))
+        .collect();
+    if addresses.is_empty() {
+        return Err(FetchError::Network("address not allowed".to_string()));
+    }
+    Ok(addresses)
+}
+
+/// The first of `addresses` that accepts a connection within `timeout`.
+fn connect(addresses: &[SocketAddr], timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = Some(e),
+        }
+    }
+    Err(last.unwrap_or_else(|| std::io::ErrorKind::NotFound.into()))
+}
+
+/// A bare HTTP/1.0 GET, enough for `curl` against plain-text endpoints.
+/// Connecting, sending and each read give up after `timeout`.
+fn http_get(url: &str, headers: &[(String, String)], timeout: Duration, public_only: bool) -> Result<Response, FetchError> {
+    let rest = url.strip_prefix("http://").ok_or(FetchError::InvalidUrl)?;
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err(FetchError::InvalidUrl);
+    }
+    let host = authority.split(':').next().unwrap_or(authority);
+    let addresses = resolve(authority, public_only)?;
+
+    let network = |e: std::io::Error| FetchError::Network(e.to_string());
+    let mut stream = connect(&addresses, timeout).map_err(network)?;
+    stream.set_read_timeout(Some(timeout)).map_err(network)?;
+    stream.set_write_timeout(Some(timeout)).map_err(network)?;
+    let extra: String = headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: ttsh\r\n{}Connection: close\r\n\r\n", path, host, extra)
+        .map_err(network)?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).map_err(network)?;
+    let raw = String::from_utf8_lossy(&raw);
+    let (head, body) = raw.split_once("\r\n\r\n").unwrap_or((&raw, ""));
+    let mut lines = head.split("\r\n");
+    let status = lines.next().and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| FetchError::Network("malformed response".to_string()))?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    Ok(Response { status, headers, body: body.to_string() })
+}
+
+impl Platform for NativePlatform {
+    fn now(&self) -> String {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        format_time(secs)
+    }
+
+    fn random(&self) -> f64 {
+        // xorshift64*
+        let mut x = self.rng.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng.set(x);
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn log(&self, message: &str) {
+        if self.verbose {
+            eprintln!("{}", message);
+        }
+    }
+
+    fn error(&self, message: &str) {
+        if self.verbose {
+            eprintln!("error: {}", message);
+        }
+    }
+
+    /// Site files are read as they are, without headers. Requests go out on
+    /// a thread of their own, so a timeout racing one can end it.
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Fetch {
+        let result = if url.starts_with('/') {
+            self.read_site_file(url)
+        } else if url.starts_with("http://") {
+            let (url, headers) = (url.to_string(), headers.to_vec());
+            let (timeout, public_only) = (http::client().config().timeout, self.public_only);
+            return Box::pin(off_thread(move || http_get(&url, &headers, timeout, public_only)));
+        } else if url.starts_with("https://") {
+            Err(FetchError::Network("https is not supported here".to_string()))
+        } else {
+            Err(FetchError::InvalidUrl)
+        };
+        Box::pin(async move { result })
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(off_thread(move || thread::sleep(duration)))
+    }
+
+    /// Jobs run to their end before the line returns; a background job is
+    /// only reported later.
+    fn spawn(&self, task: Task) {
+        block_on(task);
+    }
+
+    /// Native hosts drain the events right after running a line.
+    fn notify(&self) {}
+}
+
+/// Resolves to what `work` returns, running it on another thread. Dropping
+/// the future leaves the work to finish unheard.
+fn off_thread<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> impl Future<Output = T> {
+    let (sender, receiver) = mpsc::channel();
+    let waker: Arc<Mutex<Option<Waker>>> = Arc::default();
+    let wake = waker.clone();
+    thread::spawn(move || {
+        // The receiver is gone when nobody waits anymore.
+        let _ = sender.send(work());
+        if let Some(waker) = wake.lock().unwrap().take() {
+            waker.wake();
+        }
+    });
+    poll_fn(move |cx| {
+        // Registered before looking, so a result sent in between still wakes us.
+        *waker.lock().unwrap() = Some(cx.waker().clone());
+        match receiver.try_recv() {
+            Ok(output) => Poll::Ready(output),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => panic!("native worker thread panicked"),
+        }
+    })
+}
+
+/// Unparks the thread blocked on a future.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs `future` to completion on this thread, parked while it waits.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700::1111", "::ffff:1.1.1.1"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn public_only_refuses_names_of_this_machine() {
+        let get = |url| http_get(url, &[], Duration::from_secs(1), true);
+        assert_eq!(get("http://localhost:1/"), Err(FetchError::Network("address not allowed".to_string())));
+        assert_eq!(get("http://169.254.169.254/latest/meta-data"), Err(FetchError::Network("address not allowed".to_string())));
+    }
+
+    #[test]
+    fn a_timeout_can_outrun_a_slow_request() {
+        let slow = off_thread(|| thread::sleep(Duration::from_secs(5)));
+        let quick = off_thread(|| thread::sleep(Duration::from_millis(10)));
+        let (mut slow, mut quick) = (pin!(slow), pin!(quick));
+        let first = block_on(poll_fn(|cx| match (slow.as_mut().poll(cx), quick.as_mut().poll(cx)) {
+            (Poll::Ready(()), _) => Poll::Ready("slow"),
+            (_, Poll::Ready(())) => Poll::Ready("quick"),
+            _ => Poll::Pending,
+        }));
+        assert_eq!(first, "quick");
+    }
+}
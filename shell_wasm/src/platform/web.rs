@@ -0,0 +1,110 @@
+//! The browser: `js_sys` for time and randomness, the console, `fetch`, and
+//! the terminal UI exposed by the page's scripts.
+
+use wasm_bindgen::prelude::*;
+use std::time::Duration;
+use web_sys::{AbortController, Headers, Request, RequestInit, RequestMode};
+use super::{Fetch, FetchError, Platform, Response, Sleep, Task};
+
+/// Response headers the `http` client reads; the others are not copied over.
+const HEADERS: [&str; 2] = ["etag", "last-modified"];
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "terminalSystem"], js_name = onEvents)]
+    fn ui_on_events();
+}
+
+pub struct WebPlatform;
+
+/// Aborts the request it belongs to when dropped, e.g. because its job was
+/// killed; aborting a finished request does nothing.
+struct AbortOnDrop(Option<AbortController>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if let Some(controller) = &self.0 {
+            controller.abort();
+        }
+    }
+}
+
+impl Platform for WebPlatform {
+    fn now(&self) -> String {
+        js_sys::Date::new_0().to_string().as_string().unwrap_or_else(|| "Error getting date".to_string())
+    }
+
+    fn random(&self) -> f64 {
+        js_sys::Math::random()
+    }
+
+    fn log(&self, message: &str) {
+        web_sys::console::log_1(&message.into());
+    }
+
+    fn error(&self, message: &str) {
+        web_sys::console::error_1(&message.into());
+    }
+
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Fetch {
+        let url = url.to_string();
+        // Other origins would want a CORS preflight for them; the browser's
+        // own cache revalidates those requests anyway
+        let headers = if url.starts_with('/') { headers.to_vec() } else { Vec::new() };
+        Box::pin(async move {
+            let opts = RequestInit::new();
+            opts.set_method("GET");
+            opts.set_mode(RequestMode::Cors);
+            if !headers.is_empty() {
+                let request_headers = Headers::new().map_err(|_| FetchError::InvalidUrl)?;
+                for (name, value) in &headers {
+                    request_headers.set(name, value).map_err(|_| FetchError::InvalidUrl)?;
+                }
+                opts.set_headers(&request_headers);
+            }
+            let abort = AbortOnDrop(AbortController::new().ok());
+            if let Some(controller) = &abort.0 {
+                opts.set_signal(Some(&controller.signal()));
+            }
+
+            let request = Request::new_with_str_and_init(&url, &opts)
+                .map_err(|_| FetchError::InvalidUrl)?;
+
+            let window = web_sys::window().ok_or_else(|| FetchError::Network("no window".to_string()))?;
+            let resp_value = wasm_bindgen_futures::JsFuture::from(window.fetch_with_request(&request)).await
+                .map_err(|e| FetchError::Network(format!("{:?}", e)))?;
+            let resp: web_sys::Response = resp_value.dyn_into()
+                .map_err(|_| FetchError::Network("not a response".to_string()))?;
+
+            let text = resp.text().map_err(|e| FetchError::Network(format!("{:?}", e)))?;
+            let body = wasm_bindgen_futures::JsFuture::from(text).await
+                .map_err(|e| FetchError::Network(format!("{:?}", e)))?
+                .as_string()
+                .unwrap_or_default();
+            let headers = HEADERS.iter()
+                .filter_map(|name| Some((name.to_string(), resp.headers().get(name).ok()??)))
+                .collect();
+            Ok(Response { status: resp.status(), headers, body })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let ms = duration.as_millis().min(i32::MAX as u128) as i32;
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            if let Some(window) = web_sys::window() {
+                let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+            }
+        });
+        Box::pin(async move {
+            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+        })
+    }
+
+    fn spawn(&self, task: Task) {
+        wasm_bindgen_futures::spawn_local(task);
+    }
+
+    fn notify(&self) {
+        ui_on_events();
+    }
+}
@@ -0,0 +1,276 @@
+//! Full-text search over the site. Hugo publishes the plain text of every
+//! page as `/search/corpus.json` (`themes/tecnoterio/layouts/index.searchindex.json`);
+//! `ttsh-index` turns it offline into an inverted index, `/search/index.json`,
+//! with the positions of every term and the lengths BM25 needs. The `search`
+//! command loads that index once and ranks pages against it.
+//!
+//! Indexing and queries split text into terms with the same `terms`, so the
+//! two always agree.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+
+/// Where `ttsh-index` writes the index, next to the catalog.
+pub const INDEX_URL: &str = "/search/index.json";
+
+/// BM25 term frequency saturation and length normalisation, the usual values.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Terms shown around the first match of a snippet.
+const BEFORE: usize = 6;
+const AFTER: usize = 18;
+
+/// The corpus Hugo publishes for `ttsh-index`.
+#[derive(Deserialize, Default)]
+pub struct Corpus {
+    #[serde(default)]
+    pub documents: Vec<Document>,
+}
+
+/// A page as Hugo publishes it for indexing.
+#[derive(Deserialize, Default)]
+pub struct Document {
+    pub title: String,
+    pub slug: String,
+    pub url: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// An indexed page: what a result shows, and its length for BM25.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Doc {
+    pub title: String,
+    pub slug: String,
+    pub url: String,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    /// The plain text, for snippets.
+    pub text: String,
+    /// Terms in the title, which come before those of the text.
+    pub title_length: usize,
+    /// Terms in the title and the text.
+    pub length: usize,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub docs: Vec<Doc>,
+    /// The mean `length` of the documents.
+    pub average_length: f64,
+    /// For every term, a posting per document it appears in:
+    /// `[doc, position, position...]`, by document and then position.
+    pub terms: BTreeMap<String, Vec<Vec<usize>>>,
+}
+
+/// What `search` looks for: pages holding every phrase (a single word being
+/// a phrase of one term) and filed under every tag and category given.
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    pub phrases: Vec<Vec<String>>,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+}
+
+/// A page that matched, best first.
+pub struct Hit<'a> {
+    pub doc: &'a Doc,
+    pub score: f64,
+    /// Where the phrases start in the document.
+    positions: Vec<usize>,
+}
+
+/// The terms of `text` with where they are in it: runs of letters and
+/// digits, in lower case.
+pub fn terms(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut terms = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                terms.push((from..i, text[from..i].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    terms
+}
+
+impl Query {
+    /// `rust "web assembly" tag:wasm`: words, phrases (an argument of more
+    /// than one term) and filters.
+    pub fn parse(args: &[String]) -> Self {
+        let mut query = Query::default();
+        for arg in args {
+            if let Some(tag) = arg.strip_prefix("tag:") {
+                query.tags.push(tag.to_lowercase());
+            } else if let Some(category) = arg.strip_prefix("category:") {
+                query.categories.push(category.to_lowercase());
+            } else {
+                let phrase: Vec<String> = terms(arg).into_iter().map(|(_, term)| term).collect();
+                if !phrase.is_empty() {
+                    query.phrases.push(phrase);
+                }
+            }
+        }
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty() && self.tags.is_empty() && self.categories.is_empty()
+    }
+}
+
+thread_local! {
+    static LOADED: RefCell<Option<Rc<Index>>> = const { RefCell::new(None) };
+}
+
+/// The index `search` loaded, if it did.
+pub fn loaded() -> Option<Rc<Index>> {
+    LOADED.with(|loaded| loaded.borrow().clone())
+}
+
+/// Keeps `index` for the rest of the session.
+pub fn load(index: Index) -> Rc<Index> {
+    let index = Rc::new(index);
+    LOADED.with(|loaded| *loaded.borrow_mut() = Some(index.clone()));
+    index
+}
+
+impl Index {
+    pub fn build(documents: Vec<Document>) -> Self {
+        let mut index = Index::default();
+        for (id, document) in documents.into_iter().enumerate() {
+            let title = terms(&document.title);
+            let text = terms(&document.content);
+            for (position, (_, term)) in title.iter().chain(&text).enumerate() {
+                let postings = index.terms.entry(term.clone()).or_default();
+                match postings.last_mut() {
+                    Some(posting) if posting[0] == id => posting.push(position),
+                    _ => postings.push(vec![id, position]),
+                }
+            }
+            index.docs.push(Doc {
+                title: document.title,
+                slug: document.slug,
+                url: document.url,
+                tags: document.tags,
+                categories: document.categories,
+                text: document.content,
+                title_length: title.len(),
+                length: title.len() + text.len(),
+            });
+        }
+        let total: usize = index.docs.iter().map(|doc| doc.length).sum();
+        index.average_length = if index.docs.is_empty() { 0.0 } else { total as f64 / index.docs.len() as f64 };
+        index
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| format!("invalid search index: {}", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// The positions of `term` in document `id`.
+    fn positions(&self, term: &str, id: usize) -> &[usize] {
+        self.terms.get(term)
+            .and_then(|postings| postings.binary_search_by_key(&id, |posting| posting[0]).ok().map(|i| &postings[i][1..]))
+            .unwrap_or(&[])
+    }
+
+    /// Where `phrase` starts in document `id`.
+    fn phrase_positions(&self, phrase: &[String], id: usize) -> Vec<usize> {
+        let Some((first, rest)) = phrase.split_first() else { return Vec::new() };
+        self.positions(first, id).iter().copied()
+            .filter(|start| rest.iter().enumerate().all(|(i, term)| self.positions(term, id).binary_search(&(start + i + 1)).is_ok()))
+            .collect()
+    }
+
+    /// The BM25 score of `term` for document `id`.
+    fn score(&self, term: &str, id: usize) -> f64 {
+        let frequency = self.positions(term, id).len() as f64;
+        let containing = self.terms.get(term).map_or(0, Vec::len) as f64;
+        let total = self.docs.len() as f64;
+        let idf = (1.0 + (total - containing + 0.5) / (containing + 0.5)).ln();
+        let length = self.docs[id].length as f64 / self.average_length.max(1.0);
+        idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length))
+    }
+
+    /// The pages matching `query`, best first.
+    pub fn search(&self, query: &Query) -> Vec<Hit<'_>> {
+        let filed = |names: &[String], wanted: &[String]| {
+            wanted.iter().all(|want| names.iter().any(|name| name.to_lowercase() == *want))
+        };
+        let words: BTreeSet<&str> = query.phrases.iter().flatten().map(String::as_str).collect();
+
+        let mut hits = Vec::new();
+        for (id, doc) in self.docs.iter().enumerate() {
+            if !filed(&doc.tags, &query.tags) || !filed(&doc.categories, &query.categories) {
+                continue;
+            }
+            let mut positions = Vec::new();
+            let mut missing = false;
+            for phrase in &query.phrases {
+                let found = self.phrase_positions(phrase, id);
+                missing |= found.is_empty();
+                positions.extend(found);
+            }
+            if missing {
+                continue;
+            }
+            positions.sort_unstable();
+            let score = words.iter().map(|word| self.score(word, id)).sum();
+            hits.push(Hit { doc, score, positions });
+        }
+        // Ties keep the order of the corpus
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+}
+
+impl Hit<'_> {
+    /// A few lines' worth of the text around the first match, on one line,
+    /// with where the query terms are in it.
+    pub fn snippet(&self, query: &Query) -> (String, Vec<Range<usize>>) {
+        let text = terms(&self.doc.text);
+        if text.is_empty() {
+            return (String::new(), Vec::new());
+        }
+        let first = self.positions.iter()
+            .find(|position| **position >= self.doc.title_length)
+            .map_or(0, |position| position - self.doc.title_length);
+        let from = first.saturating_sub(BEFORE);
+        let to = (first + AFTER).min(text.len() - 1);
+
+        let start = if from == 0 { 0 } else { text[from].0.start };
+        let end = if to == text.len() - 1 { self.doc.text.len() } else { text[to].0.end };
+        let prefix = if from == 0 { "" } else { "..." };
+        let suffix = if end == self.doc.text.len() { "" } else { "..." };
+        let snippet = format!("{}{}{}", prefix, self.doc.text[start..end].trim_end(), suffix);
+
+        let words: BTreeSet<&str> = query.phrases.iter().flatten().map(String::as_str).collect();
+        let marks = text[from..=to].iter()
+            .filter(|(_, term)| words.contains(term.as_str()))
+            .map(|(range, _)| range.start - start + prefix.len()..range.end - start + prefix.len())
+            .filter(|range| range.end <= snippet.len())
+            .collect();
+        // One line, without changing where the marks are
+        let snippet = snippet.replace(['\n', '\r', '\t'], " ");
+        (snippet, marks)
+    }
+}
@@ -1,44 +1,182 @@
-use crate::state::SystemState;
-use crate::commands;
+use crate::state::{LoginState, SystemState};
+use crate::commands::{self, Deferred, Stdio, Stdout};
 use crate::bbs;
+use crate::fs;
+use crate::expand;
+use crate::history;
+use crate::events::{self, Event, EventKind};
+use crate::jobs;
+use crate::platform;
+use crate::parser::{self, AndOrList, Connector, Pipeline, Redirect, RedirectKind, SimpleCommand};
 use crate::WasmLine;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Output that reaches the host after `process_input` has returned.
+pub enum Completion {
+    /// Text for the terminal.
+    Stdout(String),
+    /// An error message for the terminal; never fed into a pipe.
+    Stderr(String),
+    /// New contents of a user file written by a background `>` or `>>`.
+    Write { path: String, text: String },
+}
+
+/// What background work left behind once it resolved.
+pub struct Outcome {
+    pub completions: Vec<Completion>,
+    /// Exit status of the last pipeline it finished.
+    pub status: i32,
+}
+
+/// Background work of a command line, resolving to what the host should do with it.
+pub type Pending = Pin<Box<dyn Future<Output = Outcome>>>;
+
+/// Line type of error messages. They reach the terminal even when the rest
+/// of a command's output is piped or redirected.
+pub const STDERR: &str = "stderr";
 
 pub struct ProcessResult {
     pub lines: Vec<WasmLine>,
     pub next_state: SystemState,
     pub handled: bool,
+    /// Output still being produced in the background, handled once it resolves.
+    pub pending: Option<Pending>,
+    /// Exit status: 0 on success, 1 on failure, 2 on syntax errors, 127 for unknown commands.
+    pub status: i32,
 }
 
 impl ProcessResult {
+    /// A single line of regular output; empty output prints nothing.
     pub fn simple(text: String, next_state: SystemState) -> Self {
+        let lines = if text.is_empty() { vec![] } else { vec![WasmLine { text, line_type: "regular".to_string() }] };
         Self {
-            lines: vec![WasmLine { text, line_type: "regular".to_string() }],
+            lines,
             next_state,
             handled: true,
+            pending: None,
+            status: 0,
         }
     }
+
+    /// An error message, with exit status 1.
+    pub fn failure(message: String, next_state: SystemState) -> Self {
+        Self::with_errors(String::new(), vec![message], 1, next_state)
+    }
+
+    /// Output that went partly wrong: `errors` for the terminal, then `stdout`.
+    pub fn with_errors(stdout: String, errors: Vec<String>, status: i32, next_state: SystemState) -> Self {
+        let mut lines: Vec<WasmLine> = errors.into_iter()
+            .filter(|text| !text.is_empty())
+            .map(|text| WasmLine { text, line_type: STDERR.to_string() })
+            .collect();
+        lines.extend(Self::simple(stdout, next_state.clone()).lines);
+        Self { lines, status, ..Self::simple(String::new(), next_state) }
+    }
+
+    /// Wraps what a command returned: its output, or the message it failed with.
+    pub fn from_result(result: Result<String, String>, next_state: SystemState) -> Self {
+        match result {
+            Ok(text) => Self::simple(text, next_state),
+            Err(message) => Self::failure(message, next_state),
+        }
+    }
+
+    /// Wraps a command's standard output, showing the notice of deferred output right away.
+    pub fn from_stdout(stdout: Result<Stdout, String>, next_state: SystemState) -> Self {
+        match stdout {
+            Ok(Stdout::Text(text)) => Self::simple(text, next_state),
+            Ok(Stdout::Deferred { notice, body }) => Self {
+                lines: vec![WasmLine { text: notice, line_type: "regular".to_string() }],
+                next_state,
+                handled: true,
+                pending: Some(complete(body)),
+                status: 0,
+            },
+            Err(message) => Self::failure(message, next_state),
+        }
+    }
+
+    /// Puts `lines`, printed by earlier stages of a pipeline, before our own.
+    fn after(mut self, mut lines: Vec<WasmLine>) -> Self {
+        lines.append(&mut self.lines);
+        self.lines = lines;
+        self
+    }
+
+    /// Reported by the shell itself when no command matched.
+    fn not_found(name: &str, next_state: SystemState) -> Self {
+        Self { status: 127, ..Self::failure(format!("command not found: {}", name), next_state) }
+    }
+}
+
+/// Starts the background work of `command` as a job on the platform, in the
+/// foreground unless `background`. Returns the job's number. The work queues
+/// what it produces as [`events`] tagged with the job, and tells the platform
+/// when it ends, even when the job was killed.
+pub fn start_job(command: &str, pending: Pending, background: bool) -> u32 {
+    let job = jobs::start(command, background);
+    let command = command.to_string();
+    platform::current().spawn(Box::pin(async move {
+        let event = |kind| Event { job, command: command.clone(), kind };
+        let status = match jobs::run(job, pending).await {
+            Ok(outcome) => {
+                for completion in outcome.completions {
+                    match completion {
+                        Completion::Stdout(text) | Completion::Stderr(text) if text.is_empty() => {}
+                        Completion::Stdout(text) => events::push(event(EventKind::Stdout { text })),
+                        Completion::Stderr(text) => events::push(event(EventKind::Stderr { text })),
+                        Completion::Write { path, text } => events::push(event(EventKind::Write { path, text })),
+                    }
+                }
+                jobs::finish(job, outcome.status);
+                outcome.status
+            }
+            Err(status) => status,
+        };
+        events::push(event(EventKind::Done { status }));
+        platform::current().notify();
+    }));
+    job
+}
+
+/// Turns a command's deferred output into background work for the host.
+fn complete(body: Deferred) -> Pending {
+    Box::pin(async move {
+        match body.await {
+            Ok(text) => Outcome { completions: vec![Completion::Stdout(text)], status: 0 },
+            Err(e) => Outcome { completions: vec![Completion::Stderr(e)], status: 1 },
+        }
+    })
+}
+
+/// Joins output lines for the terminal.
+fn join_lines(lines: &[WasmLine]) -> String {
+    lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n")
+}
+
+/// Joins output lines back into a text stream for the next command in a
+/// pipeline or a file, ending with a newline like any POSIX text.
+fn lines_to_text(lines: &[WasmLine]) -> String {
+    as_file_text(&join_lines(lines))
 }
 
 pub fn process_input(mut state: SystemState, input: &str) -> ProcessResult {
-    let cmd_parts: Vec<&str> = input.split_whitespace().collect();
-    
-    // If in BBS_PAUSE, any input returns to BBS_MAIN or PROMPT
-    if state.login_state == "BBS_PAUSE" {
-        state.login_state = state.return_state.clone();
-        if state.login_state == "BBS_MAIN" {
-            return ProcessResult { lines: bbs::render_main_menu(&state), next_state: state, handled: true };
-        }
-        if state.login_state == "BBS_POSTS" {
-            return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true };
-        }
-        return ProcessResult { lines: vec![], next_state: state, handled: true };
+    // If in BBS_PAUSE, any input returns to the screen the pause was opened from
+    if state.login_state == LoginState::BbsPause {
+        if let Err(e) = state.back() {
+            return ProcessResult::failure(e.to_string(), state);
+        }
+        return ProcessResult { lines: bbs::render_screen(&state), next_state: state, handled: true, pending: None, status: 0 };
     }
 
-    if state.login_state == "PASSWORD" {
+    if state.login_state == LoginState::Password {
         // Simple fixed password for demo
         if input == "admin" || input == "password" || input == "tecnoter" {
             state.is_authenticated = true;
-            state.login_state = "PROMPT".to_string();
+            if let Err(e) = state.go_to(LoginState::Prompt) {
+                return ProcessResult::failure(e.to_string(), state);
+            }
             return ProcessResult {
                 lines: vec![
                     WasmLine { text: "********".to_string(), line_type: "regular".to_string() },
@@ -46,9 +184,13 @@ pub fn process_input(mut state: SystemState, input: &str) -> ProcessResult {
                 ],
                 next_state: state,
                 handled: true,
+                pending: None,
+                status: 0,
             };
         } else {
-            state.login_state = "LOGIN".to_string();
+            if let Err(e) = state.go_to(LoginState::Login) {
+                return ProcessResult::failure(e.to_string(), state);
+            }
             return ProcessResult {
                 lines: vec![
                     WasmLine { text: "********".to_string(), line_type: "regular".to_string() },
@@ -56,211 +198,544 @@ pub fn process_input(mut state: SystemState, input: &str) -> ProcessResult {
                 ],
                 next_state: state,
                 handled: true,
+                pending: None,
+                status: 1,
             };
         }
     }
 
-    if state.login_state == "MAIL" {
-        state.login_state = "PROMPT".to_string();
+    if state.login_state == LoginState::Mail {
+        if let Err(e) = state.go_to(LoginState::Prompt) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
         state.mail_recipient = None;
         return ProcessResult::simple("Mail sent.".to_string(), state);
     }
 
-    if state.login_state == "MESSAGE" {
-        state.login_state = "PROMPT".to_string();
+    if state.login_state == LoginState::Message {
+        if let Err(e) = state.go_to(LoginState::Prompt) {
+            return ProcessResult::failure(e.to_string(), state);
+        }
         return ProcessResult::simple("Message sent.".to_string(), state);
     }
 
-    if cmd_parts.is_empty() {
-        return ProcessResult {
-            lines: vec![],
-            next_state: state,
-            handled: false,
-        };
+    // Completion helpers receive the raw, possibly half-typed line
+    match input.split_whitespace().next() {
+        Some("_suggest") => return suggest(state, input.strip_prefix("_suggest ").unwrap_or("")),
+        Some("_autocomplete") => {
+            let text = input.strip_prefix("_autocomplete ").unwrap_or("");
+            let matches = crate::completer::get_completions(&state, text);
+
+            return ProcessResult {
+                lines: vec![WasmLine { text: matches.join(" "), line_type: "autocomplete-list".to_string() }],
+                next_state: state,
+                handled: true,
+                pending: None,
+                status: 0,
+            };
+        },
+        Some("_history_search") => {
+            let query = input.strip_prefix("_history_search ").unwrap_or("");
+            let lines = history::search(&state.history, query).into_iter()
+                .map(|line| WasmLine { text: line.to_string(), line_type: "history-search".to_string() })
+                .collect();
+            return ProcessResult { lines, next_state: state, handled: true, pending: None, status: 0 };
+        },
+        _ => {}
     }
 
-    let cmd = cmd_parts[0].to_lowercase();
+    if state.login_state == LoginState::Prompt {
+        // Background jobs that ended meanwhile are reported once the line has run
+        let ended = jobs::finished();
+        let mut result = run_at_prompt(state, input);
+        result.lines.extend(ended.into_iter().map(|text| WasmLine { text, line_type: "regular".to_string() }));
+        return result;
+    }
 
-    // Internal commands
-    if cmd.starts_with('_') {
-        match cmd.as_str() {
-            "_boot" => {
-                state.login_state = "BOOT".to_string();
-                return ProcessResult {
-                    lines: vec![
-                        WasmLine { text: "TECNOTER.IO(TM) CORE SYSTEM".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "LOADING SYSTEM MODULES...".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "NET_STACK: TCP/IP v6 READY".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "SSH_DAEMON: LISTENING ON PORT 22".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "HTTP_DAEMON: READY".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "CONNECTING TO TECNOTER NETWORK...".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "CARRIER 14400 / ARQ / V.32bis".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "CONNECT 14400/REL - CD 1".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "PROTOCOL: LAP-M".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "COMPRESSION: V.42bis".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "*** WELCOME TO THE TECNOTER.IO NODE ***".to_string(), line_type: "regular".to_string() },
-                        WasmLine { text: "".to_string(), line_type: "regular".to_string() },
-                    ],
-                    next_state: state,
-                    handled: true,
-                };
-            },
-            "_start_login" => {
-                state.login_state = "LOGIN".to_string();
-                return ProcessResult {
-                    lines: vec![],
-                    next_state: state,
-                    handled: true,
-                };
-            },
-            "_login" => {
-                let username = if cmd_parts.len() > 1 { cmd_parts[1].to_lowercase() } else { "".to_string() };
-                if username == "guest" || username == "bbs" || username == "admin" {
-                    state.current_user = username.clone();
-                    if username == "admin" {
-                         state.login_state = "PASSWORD".to_string();
-                    } else if username == "bbs" {
-                         state.is_authenticated = true;
-                         state.login_state = "BBS_MAIN".to_string();
-                    } else {
-                         state.is_authenticated = true;
-                         state.login_state = "PROMPT".to_string();
+    run_line(state, input)
+}
+
+/// Runs a line typed at the prompt, which goes through history expansion and
+/// into the history.
+fn run_at_prompt(mut state: SystemState, input: &str) -> ProcessResult {
+    let line = match history::expand(&state.history, input) {
+        Ok(Some(line)) => line,
+        Ok(None) => {
+            history::record(&mut state, input);
+            return run_line(state, input);
+        }
+        Err(e) => {
+            state.last_status = 1;
+            return ProcessResult::failure(e, state);
+        }
+    };
+    history::record(&mut state, &line);
+
+    // Show the command that actually runs, as bash does
+    let echo = WasmLine { text: line.clone(), line_type: "regular".to_string() };
+    let mut result = run_line(state, &line);
+    if !result.handled {
+        // The host would retry the unexpanded line, so report it here
+        let name = line.split_whitespace().next().unwrap_or_default().to_string();
+        result = ProcessResult::not_found(&name, result.next_state);
+    }
+    result.after(vec![echo])
+}
+
+/// Parses and runs a command line.
+fn run_line(mut state: SystemState, input: &str) -> ProcessResult {
+    let script = match parser::parse(input) {
+        Ok(script) => script,
+        Err(e) => {
+            state.last_status = 2;
+            return ProcessResult { status: 2, ..ProcessResult::simple(e.to_string(), state) };
+        }
+    };
+
+    if let [list] = script.lists.as_slice() {
+        if list.rest.is_empty() && !list.background {
+            // A lone command the shell doesn't know is left to the host's fallbacks
+            let mut result = run_pipeline(state, &list.first.commands, None);
+            result.status = if result.handled { result.status } else { 127 };
+            result.next_state.last_status = result.status;
+            return result;
+        }
+    }
+    if script.lists.is_empty() {
+        return ProcessResult { lines: vec![], next_state: state, handled: false, pending: None, status: 0 };
+    }
+
+    run_lists(state, &script.lists)
+}
+
+/// Runs `lists` one after the other. Once one leaves background work, the
+/// lists after it wait for that work to finish, as `;` promises.
+fn run_lists(mut state: SystemState, lists: &[AndOrList]) -> ProcessResult {
+    let mut lines = Vec::new();
+    let mut status = 0;
+    for (i, list) in lists.iter().enumerate() {
+        if list.background {
+            // What runs right away prints now; the rest belongs to the job
+            let result = run_and_or(state, list);
+            let ended = result.status;
+            let pending = result.pending.unwrap_or_else(|| Box::pin(async move { Outcome { completions: vec![], status: ended } }));
+            let job = start_job(&list.to_string(), pending, true);
+            lines.push(WasmLine { text: format!("[{}]", job), line_type: "regular".to_string() });
+            lines.extend(result.lines);
+            status = 0;
+            state = result.next_state;
+            state.last_status = 0;
+            continue;
+        }
+        let result = run_and_or(state, list);
+        lines.extend(result.lines);
+        status = result.status;
+        state = result.next_state;
+        if let Some(pending) = result.pending {
+            let rest = &lists[i + 1..];
+            let pending = if rest.is_empty() { pending } else { continue_lists(state.clone(), pending, rest.to_vec()) };
+            return ProcessResult { lines, next_state: state, handled: true, pending: Some(pending), status };
+        }
+    }
+    ProcessResult { lines, next_state: state, handled: true, pending: None, status }
+}
+
+/// Runs the lists after `;` in the background once `pending` has resolved,
+/// against a snapshot of the state with its writes and exit status.
+fn continue_lists(mut state: SystemState, pending: Pending, rest: Vec<AndOrList>) -> Pending {
+    Box::pin(async move {
+        let Outcome { mut completions, status } = pending.await;
+        apply_writes(&mut state, &completions);
+        state.last_status = status;
+        let cwd = state.cwd.clone();
+        let result = run_lists(state, &rest);
+        for list in &rest {
+            for pipeline in std::iter::once(&list.first).chain(list.rest.iter().map(|(_, pipeline)| pipeline)) {
+                completions.extend(written_files(&cwd, &pipeline.commands, &result.next_state));
+            }
+        }
+        if !result.lines.is_empty() {
+            completions.push(Completion::Stdout(join_lines(&result.lines)));
+        }
+        let mut status = result.status;
+        if let Some(pending) = result.pending {
+            let outcome = pending.await;
+            completions.extend(outcome.completions);
+            status = outcome.status;
+        }
+        Outcome { completions, status }
+    })
+}
+
+/// Stores in the snapshot `state` the files background work wrote, which
+/// the host only gets once that work is done.
+fn apply_writes(state: &mut SystemState, completions: &[Completion]) {
+    for completion in completions {
+        if let Completion::Write { path, text } = completion {
+            state.files.insert(path.clone(), text.clone());
+        }
+    }
+}
+
+/// Whether the pipeline after `connector` runs, given the status of the one before.
+fn should_run(connector: Connector, status: i32) -> bool {
+    match connector {
+        Connector::And => status == 0,
+        Connector::Or => status != 0,
+    }
+}
+
+/// Runs a pipeline that is part of a longer command line, so unknown commands
+/// are reported here rather than by the host.
+fn run_reported(state: SystemState, pipeline: &Pipeline) -> ProcessResult {
+    let mut result = run_pipeline(state, &pipeline.commands, None);
+    if !result.handled {
+        result = ProcessResult::not_found(&pipeline.commands[0].argv()[0], result.next_state);
+    }
+    result.next_state.last_status = result.status;
+    result
+}
+
+/// Runs an `&&`/`||` list, skipping pipelines as their connectors dictate.
+///
+/// When a pipeline's outcome is only known once background work finishes
+/// (e.g. `cat bio && echo ok`), the rest of the list waits for it.
+fn run_and_or(state: SystemState, list: &AndOrList) -> ProcessResult {
+    let mut result = run_reported(state, &list.first);
+    for (i, (connector, pipeline)) in list.rest.iter().enumerate() {
+        if let Some(pending) = result.pending.take() {
+            let rest = list.rest[i..].to_vec();
+            result.pending = Some(continue_list(result.next_state.clone(), pending, rest));
+            return result;
+        }
+        if !should_run(*connector, result.status) {
+            continue;
+        }
+        let next = run_reported(result.next_state, pipeline);
+        result.lines.extend(next.lines);
+        result = ProcessResult { lines: result.lines, ..next };
+    }
+    result
+}
+
+/// Finishes an `&&`/`||` list in the background once `pending` has resolved,
+/// against a snapshot of the state.
+fn continue_list(mut state: SystemState, pending: Pending, rest: Vec<(Connector, Pipeline)>) -> Pending {
+    Box::pin(async move {
+        let Outcome { mut completions, mut status } = pending.await;
+        apply_writes(&mut state, &completions);
+        for (connector, pipeline) in rest {
+            if !should_run(connector, status) {
+                continue;
+            }
+            state.last_status = status;
+            let cwd = state.cwd.clone();
+            let result = run_reported(state, &pipeline);
+            completions.extend(written_files(&cwd, &pipeline.commands, &result.next_state));
+            if !result.lines.is_empty() {
+                completions.push(Completion::Stdout(join_lines(&result.lines)));
+            }
+            status = result.status;
+            state = result.next_state;
+            if let Some(pending) = result.pending {
+                let outcome = pending.await;
+                apply_writes(&mut state, &outcome.completions);
+                completions.extend(outcome.completions);
+                status = outcome.status;
+            }
+        }
+        Outcome { completions, status }
+    })
+}
+
+/// Contents of the files `commands` redirected into, which only exist in the
+/// snapshot a background continuation ran against, for the host to store.
+fn written_files(cwd: &str, commands: &[SimpleCommand], state: &SystemState) -> Vec<Completion> {
+    let mut writes = Vec::new();
+    for command in commands {
+        for redirect in command.redirects.iter().filter(|r| r.kind != RedirectKind::Input) {
+            let Ok(target) = redirect_target(state, redirect) else { continue };
+            let path = fs::resolve_path(cwd, &target);
+            if let Some(contents) = fs::read_file(state, &path) {
+                writes.push(Completion::Write { path, text: contents.to_string() });
+            }
+        }
+    }
+    writes
+}
+
+/// Where a `<` redirection reads from.
+enum Input {
+    Text(String),
+    /// A catalog page, fetched like `cat` does.
+    Deferred(Pending),
+}
+
+fn open_input(state: &SystemState, target: &str) -> Result<Input, String> {
+    let path = fs::resolve_path(&state.cwd, target);
+    if let Some(text) = fs::read_file(state, &path) {
+        return Ok(Input::Text(text.to_string()));
+    }
+    if fs::tree(state).is_dir(&path) {
+        return Err(format!("ttsh: {}: Is a directory", target));
+    }
+    let stdio = Stdio { stdin: None, piped: true };
+    match commands::cat::handle(state, &[target.to_string()], &stdio) {
+        Ok(Stdout::Deferred { body, .. }) => Ok(Input::Deferred(complete(body))),
+        _ => Err(format!("ttsh: {}: No such file or directory", target)),
+    }
+}
+
+/// Expands the target of a redirection, which must name exactly one file.
+fn redirect_target(state: &SystemState, redirect: &Redirect) -> Result<String, String> {
+    let mut fields = expand::expand_word(state, &redirect.target);
+    if fields.len() != 1 {
+        return Err(format!("ttsh: {}: ambiguous redirect", redirect.target.text()));
+    }
+    Ok(fields.remove(0))
+}
+
+/// Adds the newline that terminates the last line of a file.
+pub fn as_file_text(text: &str) -> String {
+    if text.is_empty() || text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// Runs `commands` connected by pipes, feeding `stdin` to the first one.
+///
+/// Redirections are set up before each command runs, as in POSIX shells.
+/// When a stage produces deferred output (e.g. `cat` fetching a page), the
+/// remaining stages run once it arrives, against a snapshot of the state.
+/// Error messages go to the terminal instead of the pipe. The pipeline's
+/// status is that of its last command.
+fn run_pipeline(mut state: SystemState, commands: &[SimpleCommand], mut stdin: Option<String>) -> ProcessResult {
+    let mut errors = Vec::new();
+
+    for (i, command) in commands.iter().enumerate() {
+        let piped = i + 1 < commands.len();
+        let mut output: Option<String> = None;
+
+        for redirect in &command.redirects {
+            let target = match redirect_target(&state, redirect) {
+                Ok(target) => target,
+                Err(e) => return ProcessResult::failure(e, state).after(errors),
+            };
+            let path = fs::resolve_path(&state.cwd, &target);
+            match redirect.kind {
+                RedirectKind::Input => match open_input(&state, &target) {
+                    Ok(Input::Text(text)) => stdin = Some(text),
+                    Ok(Input::Deferred(pending)) => {
+                        // Rerun this stage once the page has been fetched
+                        let mut rest = commands[i..].to_vec();
+                        rest[0].redirects.retain(|r| r.kind != RedirectKind::Input);
+                        let continuation = resume(state.clone(), pending, None, rest);
+                        return ProcessResult { lines: errors, next_state: state, handled: true, pending: Some(continuation), status: 0 };
                     }
-                    return ProcessResult {
-                        lines: vec![
-                            WasmLine { text: "\n--- ACCESS GRANTED ---".to_string(), line_type: "regular".to_string() },
-                        ],
-                        next_state: state,
-                        handled: true,
-                    };
-                } else {
-                    return ProcessResult {
-                        lines: vec![
-                            WasmLine { text: "Login incorrect.".to_string(), line_type: "regular".to_string() },
-                        ],
-                        next_state: state,
-                        handled: true,
-                    };
-                }
-            },
-            "_read_internal" => {
-                let slug = if cmd_parts.len() > 1 { cmd_parts[1] } else { "" };
-                let mut lines = vec![];
-                if let Some(post) = state.posts.iter().find(|p| p.slug == slug) {
-                    lines.push(WasmLine { text: format!("Reading: {}", post.title.to_uppercase()), line_type: "bbs-title".to_string() });
-                    lines.push(WasmLine { text: "-".repeat(40), line_type: "bbs-border".to_string() });
-                }
-                return ProcessResult {
-                    lines,
-                    next_state: state,
-                    handled: true,
-                };
-            },
-            "_suggest" => {
-                let text = input.strip_prefix("_suggest ").unwrap_or("");
-                if text.is_empty() {
-                    return ProcessResult { lines: vec![], next_state: state, handled: true };
+                    Err(e) => return ProcessResult::failure(e, state).after(errors),
+                },
+                RedirectKind::Output | RedirectKind::Append => {
+                    // `>` truncates right away; output is then appended as it comes
+                    let append = redirect.kind == RedirectKind::Append;
+                    if let Err(e) = fs::write_file(&mut state, &path, "", append) {
+                        return ProcessResult::failure(format!("ttsh: {}", e), state).after(errors);
+                    }
+                    output = Some(path);
                 }
+            }
+        }
 
-                let mut suggestion = String::new();
-                let commands = vec!["help", "ls", "whoami", "fortune", "cowsay", "uptime", "weather", "bbs", "cat", "mail", "msg", "message", "clear", "matrix", "ansi", "exit", "man", "top", "who", "ping", "social", "date", "motd", "curl", "cd"];
-                
-                // 1. Check commands
-                for cmd in commands {
-                    if cmd.starts_with(text) && cmd != text {
-                        suggestion = cmd.to_string();
-                        break;
-                    }
+        // Leading NAME=value words are assignments, not arguments
+        let assignments: Vec<(String, String)> = command.words.iter()
+            .map_while(expand::split_assignment)
+            .map(|(name, value)| (name, expand::expand_value(&state, &value)))
+            .collect();
+        let args = expand::expand_words(&state, &command.words[assignments.len()..]);
+
+        if args.is_empty() {
+            // Only assignments and redirections, e.g. `GREETING=hi` or `> /tmp/empty`
+            for (name, value) in assignments {
+                if let Err(e) = expand::assign(&mut state, &name, Some(value), false) {
+                    return ProcessResult::failure(format!("ttsh: {}", e), state).after(errors);
                 }
+            }
+            if piped {
+                stdin = Some(String::new());
+                continue;
+            }
+            return ProcessResult { lines: errors, next_state: state, handled: true, pending: None, status: 0 };
+        }
 
-                // 2. Check files if it's "cat " or "ls "
-                if suggestion.is_empty() {
-                    if text.starts_with("cat ") || text.starts_with("ls ") {
-                        let parts: Vec<&str> = text.split_whitespace().collect();
-                        if parts.len() == 2 || (parts.len() == 1 && text.ends_with(' ')) {
-                            let prefix = if parts.len() == 2 { parts[1] } else { "" };
-                            let dir = crate::fs::get_directory_contents(&state.cwd).unwrap_or_default();
-                            for entry in dir {
-                                if entry.starts_with(prefix) {
-                                    suggestion = format!("{} {}", parts[0], entry);
-                                    break;
-                                }
-                            }
-                            // Also check posts if in /posts or target starts with posts/
-                            if suggestion.is_empty() {
-                                for post in &state.posts {
-                                    if post.slug.starts_with(prefix) {
-                                        suggestion = format!("{} {}", parts[0], post.slug);
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // `NAME=value cmd` exports NAME to that command only
+        let saved: Vec<(String, Option<crate::state::ShellVar>)> = assignments.iter()
+            .map(|(name, _)| (name.clone(), state.variables.get(name).cloned()))
+            .collect();
+        for (name, value) in assignments {
+            if let Err(e) = expand::assign(&mut state, &name, Some(value), true) {
+                return ProcessResult::failure(format!("ttsh: {}", e), state).after(errors);
+            }
+        }
+
+        let mut result = run_command(state, &args, Stdio { stdin: stdin.take(), piped: piped || output.is_some() });
+        for (name, previous) in saved {
+            match previous {
+                Some(var) => result.next_state.variables.insert(name, var),
+                None => result.next_state.variables.remove(&name),
+            };
+        }
+        if !result.handled {
+            if commands.len() == 1 {
+                // Let the host try its own fallbacks
+                return result;
+            }
+            result = ProcessResult::not_found(&args[0], result.next_state);
+        }
+
+        let ProcessResult { lines, next_state, pending, status, .. } = result;
+        state = next_state;
+        // On the terminal both streams keep their order
+        let (stderr, lines): (Vec<WasmLine>, Vec<WasmLine>) = if piped || output.is_some() {
+            lines.into_iter().partition(|line| line.line_type == STDERR)
+        } else {
+            (Vec::new(), lines)
+        };
+        errors.extend(stderr);
+
+        if let Some(pending) = pending {
+            // The notice only makes sense when this stage talks to the terminal
+            if !piped {
+                errors.extend(lines);
+            }
+            let rest = commands[i + 1..].to_vec();
+            let continuation = resume(state.clone(), pending, output, rest);
+            return ProcessResult { lines: errors, next_state: state, handled: true, pending: Some(continuation), status: 0 };
+        }
+
+        let text = match output {
+            Some(path) => {
+                if let Err(e) = fs::write_file(&mut state, &path, &lines_to_text(&lines), true) {
+                    return ProcessResult::failure(format!("ttsh: {}", e), state).after(errors);
                 }
+                String::new()
+            }
+            None if !piped => {
+                errors.extend(lines);
+                return ProcessResult { lines: errors, next_state: state, handled: true, pending: None, status };
+            }
+            None => lines_to_text(&lines),
+        };
+        if !piped {
+            return ProcessResult { lines: errors, next_state: state, handled: true, pending: None, status };
+        }
+        stdin = Some(text);
+    }
 
-                return ProcessResult {
-                    lines: vec![WasmLine { text: suggestion, line_type: "suggestion".to_string() }],
-                    next_state: state,
-                    handled: true,
-                };
-            },
-            "_autocomplete" => {
-                let text = input.strip_prefix("_autocomplete ").unwrap_or("");
-                let matches = crate::completer::get_completions(&state, text);
-
-                return ProcessResult {
-                    lines: vec![WasmLine { text: matches.join(" "), line_type: "autocomplete-list".to_string() }],
-                    next_state: state,
-                    handled: true,
-                };
-            },
-            _ => {}
+    ProcessResult { lines: errors, next_state: state, handled: true, pending: None, status: 0 }
+}
+
+/// Finishes a pipeline in the background once `pending` has produced the output
+/// of the stage before `rest`. That output goes to `output` if it was redirected.
+fn resume(mut state: SystemState, pending: Pending, output: Option<String>, rest: Vec<SimpleCommand>) -> Pending {
+    Box::pin(async move {
+        let mut completions = Vec::new();
+        let mut text = Vec::new();
+        let outcome = pending.await;
+        for completion in outcome.completions {
+            match completion {
+                Completion::Stdout(t) => text.push(t),
+                other => completions.push(other),
+            }
+        }
+        let mut text = as_file_text(&text.join("\n"));
+
+        if let Some(path) = output {
+            if let Err(e) = fs::write_file(&mut state, &path, &text, true) {
+                completions.push(Completion::Stderr(format!("ttsh: {}", e)));
+                return Outcome { completions, status: 1 };
+            }
+            let contents = fs::read_file(&state, &path).unwrap_or_default().to_string();
+            completions.push(Completion::Write { path, text: contents });
+            text = String::new();
+        } else if rest.is_empty() {
+            completions.push(Completion::Stdout(text.strip_suffix('\n').unwrap_or(&text).to_string()));
+            return Outcome { completions, status: outcome.status };
+        }
+        if rest.is_empty() {
+            return Outcome { completions, status: outcome.status };
+        }
+
+        let cwd = state.cwd.clone();
+        let result = run_pipeline(state, &rest, Some(text));
+        completions.extend(written_files(&cwd, &rest, &result.next_state));
+        if !result.lines.is_empty() {
+            completions.push(Completion::Stdout(join_lines(&result.lines)));
         }
+        let mut status = result.status;
+        if let Some(pending) = result.pending {
+            let outcome = pending.await;
+            completions.extend(outcome.completions);
+            status = outcome.status;
+        }
+        Outcome { completions, status }
+    })
+}
+
+/// Ghost text for the prompt: the input completed with the first candidate.
+fn suggest(state: SystemState, text: &str) -> ProcessResult {
+    let partial = if text.ends_with(' ') { String::new() } else { parser::split_partial(text).pop().unwrap_or_default() };
+
+    let suggestion = match text.strip_suffix(partial.as_str()) {
+        Some(head) if !text.is_empty() => crate::completer::get_completions(&state, text).into_iter()
+            .find(|candidate| *candidate != partial)
+            .map(|candidate| format!("{}{}", head, candidate))
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    ProcessResult {
+        lines: vec![WasmLine { text: suggestion, line_type: "suggestion".to_string() }],
+        next_state: state,
+        handled: true,
+        pending: None,
+        status: 0,
     }
+}
+
+/// Runs a single parsed command. `cmd_parts[0]` is the command name.
+fn run_command(mut state: SystemState, cmd_parts: &[String], stdio: Stdio) -> ProcessResult {
+    let cmd = cmd_parts[0].to_lowercase();
 
     // BBS State Overrides
-    if state.login_state.starts_with("BBS") {
+    if state.login_state.is_bbs() {
          // Check for numeric choice first
          if let Ok(num) = cmd.parse::<usize>() {
             if num > 0 {
-                if state.login_state == "BBS_MAIN" {
+                if state.login_state == LoginState::BbsMain {
                     if num <= state.pages.len() {
                         let slug = &state.pages[num - 1].slug;
-                        let output = commands::cat::handle(&state, vec![slug]);
-                        state.return_state = "BBS_MAIN".to_string();
-                        state.login_state = "BBS_PAUSE".to_string();
-                        return ProcessResult::simple(output, state);
+                        let output = commands::cat::handle(&state, std::slice::from_ref(slug), &stdio);
+                        state.pause();
+                        return ProcessResult::from_stdout(output, state);
                     }
-                } else if state.login_state == "BBS_POSTS" {
-                    let filtered_posts: Vec<_> = if state.cwd.starts_with("/categories/") {
-                        let cat = &state.cwd[12..];
-                        state.posts.iter().filter(|p| p.categories.contains(&cat.to_string())).collect()
-                    } else if state.cwd.starts_with("/tags/") {
-                        let tag = &state.cwd[6..];
-                        state.posts.iter().filter(|p| p.tags.contains(&tag.to_string())).collect()
-                    } else {
-                        state.posts.iter().collect()
-                    };
+                } else if state.login_state == LoginState::BbsPosts {
+                    let filtered_posts = bbs::area_posts(&state);
 
                     if num <= filtered_posts.len() {
                         let post = filtered_posts[num - 1];
-                        let output = commands::cat::handle(&state, vec![&post.slug]);
-                        state.return_state = "BBS_MAIN".to_string();
-                        state.login_state = "BBS_PAUSE".to_string();
-                        return ProcessResult::simple(output, state);
+                        let output = commands::cat::handle(&state, std::slice::from_ref(&post.slug), &stdio);
+                        state.pause();
+                        return ProcessResult::from_stdout(output, state);
                     }
-                } else if state.login_state == "BBS_CATEGORIES" {
-                    let mut cats: Vec<String> = state.posts.iter().flat_map(|p| p.categories.clone()).collect();
-                    cats.sort();
-                    cats.dedup();
+                } else if state.login_state == LoginState::BbsCategories {
+                    let cats = bbs::areas(&state);
                     if num <= cats.len() {
                         state.cwd = format!("/categories/{}", cats[num - 1]);
-                        state.login_state = "BBS_POSTS".to_string();
-                        return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true };
+                        if let Err(e) = state.open(LoginState::BbsPosts) {
+                            return ProcessResult::failure(e.to_string(), state);
+                        }
+                        return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true, pending: None, status: 0 };
                     }
                 }
             }
@@ -268,185 +743,59 @@ pub fn process_input(mut state: SystemState, input: &str) -> ProcessResult {
 
          match cmd.as_str() {
             "q" => {
-                state.login_state = "PROMPT".to_string();
+                if let Err(e) = state.go_to(LoginState::Prompt) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
                 return ProcessResult::simple("Returned to system shell.".to_string(), state);
             },
             "m" => {
-                state.login_state = "BBS_MAIN".to_string();
-                return ProcessResult { lines: bbs::render_main_menu(&state), next_state: state, handled: true };
+                state.nav_stack.clear();
+                if let Err(e) = state.go_to(LoginState::BbsMain) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                return ProcessResult { lines: bbs::render_main_menu(&state), next_state: state, handled: true, pending: None, status: 0 };
+            },
+            "b" => {
+                if let Err(e) = state.back() {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                return ProcessResult { lines: bbs::render_screen(&state), next_state: state, handled: true, pending: None, status: 0 };
             },
             "r" | "l" => {
-                state.login_state = "BBS_POSTS".to_string();
-                return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true };
+                if let Err(e) = state.open(LoginState::BbsPosts) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true, pending: None, status: 0 };
             },
             "s" => {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-                return ProcessResult { lines: bbs::render_system_stats(&state), next_state: state, handled: true };
+                state.pause();
+                return ProcessResult { lines: bbs::render_system_stats(&state), next_state: state, handled: true, pending: None, status: 0 };
             },
             "c" => {
-                state.login_state = "BBS_CATEGORIES".to_string();
-                return ProcessResult { lines: bbs::render_category_list(&state), next_state: state, handled: true };
+                if let Err(e) = state.open(LoginState::BbsCategories) {
+                    return ProcessResult::failure(e.to_string(), state);
+                }
+                return ProcessResult { lines: bbs::render_category_list(&state), next_state: state, handled: true, pending: None, status: 0 };
             },
             "u" => {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-                return ProcessResult { lines: bbs::render_user_list(), next_state: state, handled: true };
+                state.pause();
+                return ProcessResult { lines: bbs::render_user_list(), next_state: state, handled: true, pending: None, status: 0 };
             },
             "?" | "h" | "help" => {
-                return ProcessResult { lines: bbs::render_help(), next_state: state, handled: true };
+                return ProcessResult { lines: bbs::render_help(), next_state: state, handled: true, pending: None, status: 0 };
             },
             _ => {}
          }
     }
 
-    // Standard Shell Commands (also work in BBS sometimes)
-    match cmd.as_str() {
-        "ping" => {
-            ProcessResult::simple("PONG (WASM Core Online)".to_string(), state)
-        },
-        "help" => ProcessResult::simple(commands::help::handle(), state),
-        "curl" => ProcessResult::simple(commands::curl::handle(&state, cmd_parts[1..].to_vec()), state),
-        "ls" => ProcessResult::simple(commands::ls::handle(&state, cmd_parts[1..].to_vec()), state),
-        "cd" => {
-            let res = commands::cd::handle(&state, cmd_parts[1..].to_vec());
-            state.cwd = res.new_path;
-            ProcessResult::simple(res.message, state)
-        },
-        "whoami" => ProcessResult::simple(commands::whoami::handle(&state), state),
-        "fortune" => {
-            if state.login_state.starts_with("BBS") {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-            }
-            ProcessResult::simple(commands::fortune::handle(&state), state)
-        },
-        "cowsay" => {
-            if state.login_state.starts_with("BBS") {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-            }
-            ProcessResult::simple(commands::cowsay::handle(), state)
-        },
-        "uptime" => {
-            if state.login_state.starts_with("BBS") {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-            }
-            ProcessResult::simple(commands::uptime::handle(&state), state)
-        },
-        "weather" | "climate" => {
-            if state.login_state.starts_with("BBS") {
-                state.return_state = "BBS_MAIN".to_string();
-                state.login_state = "BBS_PAUSE".to_string();
-            }
-            ProcessResult::simple(commands::weather::handle(), state)
-        },
-        "bbs" => {
-            if cmd_parts.len() > 1 {
-                let sub_cmd = cmd_parts[1].to_lowercase();
-                match sub_cmd.as_str() {
-                    "r" | "l" => {
-                        state.login_state = "BBS_POSTS".to_string();
-                        return ProcessResult { lines: bbs::render_post_list(&state), next_state: state, handled: true };
-                    },
-                    "c" => {
-                        state.login_state = "BBS_CATEGORIES".to_string();
-                        return ProcessResult { lines: bbs::render_category_list(&state), next_state: state, handled: true };
-                    },
-                    "s" => {
-                        state.return_state = "BBS_MAIN".to_string();
-                        state.login_state = "BBS_PAUSE".to_string();
-                        return ProcessResult { lines: bbs::render_system_stats(&state), next_state: state, handled: true };
-                    },
-                    "u" => {
-                        state.return_state = "BBS_MAIN".to_string();
-                        state.login_state = "BBS_PAUSE".to_string();
-                        return ProcessResult { lines: bbs::render_user_list(), next_state: state, handled: true };
-                    },
-                    _ => {
-                        state.login_state = "BBS_MAIN".to_string();
-                        return ProcessResult { lines: bbs::render_main_menu(&state), next_state: state, handled: true };
-                    }
-                }
-            }
-            state.login_state = "BBS_MAIN".to_string();
-            return ProcessResult { lines: bbs::render_main_menu(&state), next_state: state, handled: true };
-        },
-        "cat" => ProcessResult::simple(commands::cat::handle(&state, cmd_parts[1..].to_vec()), state),
-        "mail" => {
-            if cmd_parts.len() < 2 {
-                return ProcessResult::simple("Usage: mail [username]".to_string(), state);
-            }
-            state.mail_recipient = Some(cmd_parts[1].to_string());
-            state.login_state = "MAIL".to_string();
-            ProcessResult {
-                lines: vec![],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "msg" | "message" => {
-            state.login_state = "MESSAGE".to_string();
-            ProcessResult {
-                lines: vec![],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "clear" => {
-            ProcessResult {
-                lines: vec![WasmLine { text: "".to_string(), line_type: "clearScreen".to_string() }],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "matrix" => {
-            let mode = if cmd_parts.len() > 1 { cmd_parts[1] } else { "binary" };
-            ProcessResult {
-                lines: vec![WasmLine { text: format!("_MATRIX_{}", mode), line_type: "internalInstruction".to_string() }],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "ansi" => {
-            ProcessResult {
-                lines: vec![WasmLine { text: "ansi".to_string(), line_type: "internalInstruction".to_string() }],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "exit" => {
-            ProcessResult {
-                lines: vec![WasmLine { text: "exit".to_string(), line_type: "internalInstruction".to_string() }],
-                next_state: state,
-                handled: true,
-            }
-        },
-        "man" => ProcessResult::simple(commands::man::handle(cmd_parts[1..].to_vec()), state),
-        "top" => ProcessResult::simple(commands::top::handle(&state), state),
-        "who" => ProcessResult::simple(commands::who::handle(&state), state),
-        "date" => ProcessResult::simple(commands::date::handle(&state), state),
-        "motd" => ProcessResult::simple(commands::motd::handle(&state), state),
-        "social" => {
-            let output = commands::social::handle(&state, cmd_parts[1..].to_vec());
-            if output.starts_with("_OPEN_URL_") {
-                let url = output.replace("_OPEN_URL_", "");
-                return ProcessResult {
-                    lines: vec![
-                        WasmLine { text: format!("Opening uplink to {}...", url), line_type: "regular".to_string() },
-                        WasmLine { text: output, line_type: "internalInstruction".to_string() }
-                    ],
-                    next_state: state,
-                    handled: true,
-                };
-            }
-            ProcessResult::simple(output, state)
-        },
+    match commands::find(&cmd) {
+        Some(command) if command.allowed(state.login_state) => command.run(state, &cmd_parts[1..], stdio),
         _ => ProcessResult {
             lines: vec![],
             next_state: state,
             handled: false,
+            pending: None,
+            status: 127,
         }
     }
 }
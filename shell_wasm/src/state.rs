@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -10,11 +12,11 @@ pub struct Post {
     #[serde(default)]
     pub url: String,
     #[serde(default)]
-    pub date: String,
-    #[serde(default)]
     pub tags: Vec<String>,
     #[serde(default)]
     pub categories: Vec<String>,
+    #[serde(flatten)]
+    pub meta: Metadata,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -27,11 +29,49 @@ pub struct Page {
     #[serde(default)]
     pub url: String,
     #[serde(default)]
-    pub date: String,
-    #[serde(default)]
     pub tags: Vec<String>,
     #[serde(default)]
     pub categories: Vec<String>,
+    #[serde(flatten)]
+    pub meta: Metadata,
+}
+
+/// What Hugo knows about a post or a page besides where it lives.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    #[serde(default)]
+    pub date: String,
+    /// Hugo's summary, as plain text.
+    #[serde(default)]
+    pub summary: String,
+    /// Bytes of plain text, what `cat` writes down a pipe.
+    #[serde(default)]
+    pub size: usize,
+    #[serde(default)]
+    pub word_count: usize,
+    /// Minutes, as Hugo estimates them.
+    #[serde(default)]
+    pub reading_time: usize,
+    /// Last modification, e.g. `2026-10-01`; empty in older catalogs.
+    #[serde(default)]
+    pub lastmod: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub series: Vec<String>,
+    /// Hugo's ordering weight, 0 when unset.
+    #[serde(default)]
+    pub weight: i64,
+}
+
+impl Metadata {
+    /// When it last changed: `lastmod`, or `date` when the catalog has none.
+    pub fn modified(&self) -> &str {
+        if self.lastmod.is_empty() { &self.date } else { &self.lastmod }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -72,12 +112,73 @@ impl Default for SystemInfo {
     }
 }
 
+/// A variable defined with `NAME=value` or `export`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellVar {
+    #[serde(default)]
+    pub value: String,
+    /// Exported variables are listed by `env`.
+    #[serde(default)]
+    pub exported: bool,
+}
+
+/// Which prompt or screen the next line of input goes to. Serialized as the
+/// host's `SCREAMING_SNAKE_CASE` names, e.g. `BBS_PAUSE`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LoginState {
+    #[default]
+    Uninitialized,
+    Boot,
+    Login,
+    /// Set by the host while it checks the name typed at the login prompt.
+    Authenticating,
+    Password,
+    Prompt,
+    /// Waiting for the body of a `mail`.
+    Mail,
+    /// Waiting for the body of a `msg`.
+    Message,
+    BbsMain,
+    BbsPosts,
+    BbsCategories,
+    /// A BBS screen waiting for any key before going back to the previous one.
+    BbsPause,
+}
+
+impl LoginState {
+    pub fn is_bbs(self) -> bool {
+        matches!(self, LoginState::BbsMain | LoginState::BbsPosts | LoginState::BbsCategories | LoginState::BbsPause)
+    }
+
+    /// Whether typed lines are command lines, at the prompt or in the BBS.
+    pub fn is_shell(self) -> bool {
+        self == LoginState::Prompt || self.is_bbs()
+    }
+
+    /// The transitions the session may make.
+    pub fn can_go_to(self, next: LoginState) -> bool {
+        use LoginState::*;
+        match (self, next) {
+            // The terminal can be rebooted at any time
+            (_, Boot) => true,
+            (Boot, Login) => true,
+            (Login | Authenticating, Login | Authenticating | Password | Prompt | BbsMain) => true,
+            (Password, Prompt | Login) => true,
+            (Prompt, Mail | Message) | (Mail | Message, Prompt) => true,
+            (from, to) if from.is_shell() && to.is_shell() => true,
+            (from, to) => from == to,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemState {
     pub cwd: String,
     pub current_user: String,
-    pub login_state: String,
+    pub login_state: LoginState,
     #[serde(default)]
     pub posts: Vec<Post>,
     #[serde(default)]
@@ -89,7 +190,9 @@ pub struct SystemState {
     #[serde(default)]
     pub system_info: SystemInfo,
     pub version: String,
-    pub return_state: String,
+    /// BBS screens to go back to, innermost last.
+    #[serde(default)]
+    pub nav_stack: Vec<LoginState>,
     #[serde(default)]
     pub booted: bool,
     #[serde(default)]
@@ -97,6 +200,24 @@ pub struct SystemState {
     #[serde(default)]
     pub debug_mode: bool,
     pub mail_recipient: Option<String>,
+    /// Files written by the user under `/tmp` and `/home/<user>`, keyed by absolute path.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// Directories made by the user under `/tmp` and `/home/<user>`, by absolute path.
+    #[serde(default)]
+    pub dirs: BTreeSet<String>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, ShellVar>,
+    /// Command lines run at the prompt, oldest first.
+    #[serde(default)]
+    pub history: Vec<String>,
+    /// Exit status of the last command line, exposed as `$?`.
+    #[serde(default)]
+    pub last_status: i32,
+    /// Directories saved by `pushd`, most recent first. The working directory
+    /// is the top of the stack and is not kept here.
+    #[serde(default)]
+    pub dir_stack: Vec<String>,
 }
 
 impl Default for SystemState {
@@ -104,18 +225,105 @@ impl Default for SystemState {
         Self {
             cwd: "/".to_string(),
             current_user: "guest".to_string(),
-            login_state: "UNINITIALIZED".to_string(),
+            login_state: LoginState::Uninitialized,
             posts: Vec::new(),
             pages: Vec::new(),
             socials: Vec::new(),
             fortunes: Vec::new(),
             system_info: SystemInfo::default(),
             version: "2.0.26-LNX".to_string(),
-            return_state: "PROMPT".to_string(),
+            nav_stack: Vec::new(),
             booted: false,
             is_authenticated: false,
             debug_mode: false,
             mail_recipient: None,
+            files: BTreeMap::new(),
+            dirs: BTreeSet::new(),
+            variables: BTreeMap::new(),
+            history: Vec::new(),
+            last_status: 0,
+            dir_stack: Vec::new(),
+        }
+    }
+}
+
+/// What hosts keep of the user's files between sessions: everything under
+/// `/home`. `/tmp` starts empty every time.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Home {
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub dirs: BTreeSet<String>,
+}
+
+impl SystemState {
+    /// The files and directories under `/home`, for the host to save.
+    pub fn home(&self) -> Home {
+        let kept = |path: &&String| path.starts_with("/home/");
+        Home {
+            files: self.files.iter().filter(|(path, _)| kept(path)).map(|(path, text)| (path.clone(), text.clone())).collect(),
+            dirs: self.dirs.iter().filter(kept).cloned().collect(),
+        }
+    }
+
+    /// Puts back a home saved by [`home`](Self::home).
+    pub fn restore_home(&mut self, home: Home) {
+        self.files.extend(home.files);
+        self.dirs.extend(home.dirs);
+    }
+
+    /// Moves the session to `next`, if it can get there from where it is.
+    /// Leaving the BBS forgets its screens.
+    pub fn go_to(&mut self, next: LoginState) -> Result<(), InvalidTransition> {
+        if !self.login_state.can_go_to(next) {
+            return Err(InvalidTransition { from: self.login_state, to: next });
+        }
+        if !next.is_bbs() {
+            self.nav_stack.clear();
+        }
+        self.login_state = next;
+        Ok(())
+    }
+
+    /// Opens a BBS screen on top of the current one, so that [`back`](Self::back) returns here.
+    pub fn open(&mut self, screen: LoginState) -> Result<(), InvalidTransition> {
+        let from = self.login_state;
+        self.go_to(screen)?;
+        if from.is_bbs() && from != screen {
+            self.nav_stack.push(from);
+        }
+        Ok(())
+    }
+
+    /// Shows output that waits for a key before the current screen comes back.
+    /// Outside the BBS there is nothing to come back to, so this does nothing.
+    pub fn pause(&mut self) {
+        if self.login_state.is_bbs() && self.login_state != LoginState::BbsPause {
+            // One BBS screen can always open another
+            self.nav_stack.push(self.login_state);
+            self.login_state = LoginState::BbsPause;
         }
     }
+
+    /// Returns to the previous BBS screen, or the main menu when there is none.
+    pub fn back(&mut self) -> Result<LoginState, InvalidTransition> {
+        let previous = self.nav_stack.last().copied().unwrap_or(LoginState::BbsMain);
+        self.go_to(previous)?;
+        self.nav_stack.pop();
+        Ok(previous)
+    }
+}
+
+/// A move [`LoginState::can_go_to`] does not allow, e.g. `_login` typed at the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: LoginState,
+    pub to: LoginState,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid transition {:?} -> {:?}", self.from, self.to)
+    }
 }
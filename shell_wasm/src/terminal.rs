@@ -5,7 +5,7 @@ pub struct TerminalState {
 }
 
 impl TerminalState {
-    pub fn _new() -> Self {
+    pub fn new() -> Self {
         Self {
             width: 80,
             height: 24,
@@ -13,8 +13,14 @@ impl TerminalState {
         }
     }
 
-    pub fn _resize(&mut self, w: u32, h: u32) {
+    pub fn resize(&mut self, w: u32, h: u32) {
         self.width = w;
         self.height = h;
     }
 }
+
+impl Default for TerminalState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
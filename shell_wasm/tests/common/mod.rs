@@ -0,0 +1,41 @@
+//! A node daemon serving `tests/fixtures/site` on a local port.
+
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A running daemon, killed when dropped.
+pub struct Daemon {
+    child: Child,
+    pub address: String,
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Starts the binary at `path` with `args` and waits until it takes connections.
+pub fn start(path: &str, args: &[&str]) -> Daemon {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let address = format!("127.0.0.1:{}", port);
+    let site = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site");
+    let child = Command::new(path)
+        .args(["--site", site, "--listen", &address])
+        .args(args)
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let daemon = Daemon { child, address };
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        match TcpStream::connect(&daemon.address) {
+            Ok(_) => return daemon,
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Err(e) => panic!("{} did not listen on {}: {}", path, daemon.address, e),
+        }
+    }
+}
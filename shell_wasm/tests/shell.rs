@@ -0,0 +1,633 @@
+//! The shell core driven natively, with the host replaced by a `MockPlatform`.
+
+use std::future::{poll_fn, Future};
+use std::rc::Rc;
+use std::task::Poll;
+use std::time::Duration;
+use tecnoter_shell::bbs;
+use tecnoter_shell::commands;
+use tecnoter_shell::completer;
+use tecnoter_shell::events::{self, Event, EventKind};
+use tecnoter_shell::http::{self, Config};
+use tecnoter_shell::jobs;
+use tecnoter_shell::platform::{self, mock::MockPlatform, native, FetchError, Response};
+use tecnoter_shell::search::{self, Document, Index};
+use tecnoter_shell::shell::{self, ProcessResult};
+use tecnoter_shell::state::{LoginState, Page, Post, SystemState};
+
+fn mock() -> Rc<MockPlatform> {
+    let platform = Rc::new(MockPlatform::default());
+    platform::install(platform.clone());
+    platform
+}
+
+fn session() -> SystemState {
+    SystemState {
+        login_state: LoginState::Prompt,
+        current_user: "guest".to_string(),
+        cwd: "/".to_string(),
+        posts: vec![
+            Post { title: "Node Alpha".into(), slug: "node-alpha".into(), url: "/posts/node-alpha/".into(), tags: vec!["rust".into()], ..Default::default() },
+            Post { title: "Node Beta".into(), slug: "node-beta".into(), url: "/posts/node-beta/".into(), tags: vec!["web".into()], ..Default::default() },
+        ],
+        pages: vec![Page { title: "Bio".into(), slug: "bio".into(), url: "/pages/bio/".into(), ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+fn text(result: &ProcessResult) -> String {
+    result.lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n")
+}
+
+/// `text` without the colours commands put in it.
+fn plain(text: String) -> String {
+    regex_lite::Regex::new("\x1b\\[[0-9;]*m").unwrap().replace_all(&text, "").into_owned()
+}
+
+/// Runs `input`, starting its foreground job as the page does.
+fn run(state: SystemState, input: &str) -> ProcessResult {
+    let mut result = shell::process_input(state, input);
+    if let Some(pending) = result.pending.take() {
+        shell::start_job(input, pending, false);
+    }
+    result
+}
+
+/// Lets time pass until every job has ended.
+fn settle(host: &MockPlatform) {
+    while host.run_jobs() > 0 {
+        host.pass_time();
+    }
+}
+
+/// Resolves on its `n`th poll.
+fn after_polls(n: usize) -> impl Future<Output = ()> {
+    let mut polls = 0;
+    poll_fn(move |_| {
+        polls += 1;
+        if polls >= n { Poll::Ready(()) } else { Poll::Pending }
+    })
+}
+
+/// What background jobs printed since the last call.
+fn printed() -> Vec<String> {
+    events::drain().into_iter().filter_map(|event| match event.kind {
+        EventKind::Stdout { text } | EventKind::Stderr { text } => Some(text),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn lists_and_pipes_without_a_browser() {
+    let host = mock();
+    let result = run(session(), "ls /posts/node-* | wc -l");
+    assert_eq!(text(&result), "2");
+    assert_eq!(result.status, 0);
+    assert!(host.logs.borrow().iter().any(|log| log.starts_with("fs: ")));
+
+    // Pipes carry the final newline files have
+    assert_eq!(text(&run(session(), "echo hi | wc -c")), "3");
+    let state = run(session(), "echo hi > /tmp/a").next_state;
+    assert_eq!(text(&run(state.clone(), "wc -c < /tmp/a")), "3");
+    assert_eq!(text(&run(state, "cat /tmp/a | cat")), "hi");
+}
+
+#[test]
+fn long_listings_show_the_catalog_metadata() {
+    mock();
+    let mut state = session();
+    state.posts[0].meta.date = "2026-03-01".into();
+    state.posts[0].meta.lastmod = "2026-09-30".into();
+    state.posts[0].meta.size = 5120;
+    state.posts[0].meta.reading_time = 4;
+    state.posts[1].meta.date = "2026-02-14".into();
+
+    assert_eq!(text(&run(state.clone(), "ls -l /posts")), "\
+        -rw-r--r-- tecnoter staff  5120 Sep 30 2026 node-alpha (4 min read) [rust]\n\
+        -rw-r--r-- tecnoter staff     0 Feb 14 2026 node-beta [web]");
+
+    state.login_state = LoginState::BbsPosts;
+    let rows: Vec<String> = bbs::render_post_list(&state).into_iter().map(|line| line.text).collect();
+    assert!(rows[3].contains(" 1 2026-09-30 Node Alpha   5.0K  4m  │   2 2026-02-14 Node Beta       0"), "{}", rows[3]);
+
+    state.posts[1].meta.weight = 1;
+    let rows: Vec<String> = bbs::render_post_list(&state).into_iter().map(|line| line.text).collect();
+    assert!(rows[3].contains(" 1 2026-02-14 Node Beta       0      │   2 2026-09-30 Node Alpha"), "{}", rows[3]);
+}
+
+#[test]
+fn stat_file_and_tree_describe_the_catalog() {
+    mock();
+    let mut state = session();
+    state.posts[0].meta.word_count = 820;
+    state.posts[0].meta.reading_time = 4;
+    state.posts[0].meta.size = 5120;
+    state.posts[0].meta.summary = "A first node.".into();
+
+    assert_eq!(text(&run(state.clone(), "file /posts/node-alpha /posts /tags /tags/rust /home/guest")), "\
+        /posts/node-alpha: post\n\
+        /posts: section directory\n\
+        /tags: taxonomy directory\n\
+        /tags/rust: tag directory\n\
+        /home/guest: directory");
+
+    let stat = text(&run(state.clone(), "stat /posts/node-alpha"));
+    assert!(stat.contains("      Type: post\n      Size: 5120 bytes\n     Words: 820\n   Reading: 4 min"), "{}", stat);
+    assert!(stat.contains("      Tags: rust"), "{}", stat);
+    assert!(stat.contains("   Summary: A first node."), "{}", stat);
+
+    let tree = run(state.clone(), "tree /tags");
+    assert_eq!(text(&tree), "/tags\n├── rust\n│   └── node-alpha\n└── web\n    └── node-beta\n\n2 directories, 2 files");
+    assert_eq!(tree.lines[1].line_type, "ls-dir");
+    assert_eq!(tree.lines[2].line_type, "ls-file");
+    assert_eq!(text(&run(state.clone(), "tree -L 1 /tags")), "/tags\n├── rust\n└── web\n\n2 directories, 0 files");
+    assert_eq!(run(state.clone(), "stat /nowhere").status, 1);
+
+    // What was found still goes down the pipe, the errors to the terminal
+    let result = run(state.clone(), "stat /posts/node-alpha /nowhere | head -1");
+    assert_eq!(text(&result), "stat: cannot stat '/nowhere': No such file or directory\n      File: /posts/node-alpha");
+    let result = run(state, "file /nowhere /tags | wc -l");
+    assert_eq!(text(&result), "file: cannot open '/nowhere': No such file or directory\n1");
+}
+
+#[test]
+fn the_home_directory_is_writable_and_the_catalog_is_not() {
+    let host = mock();
+    host.respond("/posts/node-alpha/index.json", r#"{"title": "Node Alpha", "content": "Rust everywhere"}"#);
+
+    let state = run(session(), "mkdir -p ~/notes/rust && touch ~/notes/todo && echo hi > ~/notes/rust/a").next_state;
+    let state = run(state, "cp -r ~/notes /tmp/copy && mv ~/notes/todo ~/notes/rust").next_state;
+    assert_eq!(plain(text(&run(state.clone(), "ls -R ~/notes /tmp/copy"))), "\
+        /home/guest/notes:\nrust\n\n/home/guest/notes/rust:\na  todo\n\n\
+        /tmp/copy:\nrust  todo\n\n/tmp/copy/rust:\na");
+
+    let denied = run(state.clone(), "rm /posts/node-alpha; touch /pages/x; mkdir /tags/new; mv /pages/bio ~; rm ~/notes");
+    assert_eq!(text(&denied), "\
+        rm: cannot remove '/posts/node-alpha': Permission denied\n\
+        touch: cannot touch '/pages/x': Permission denied\n\
+        mkdir: cannot create directory '/tags/new': Permission denied\n\
+        mv: cannot move '/pages/bio' to '/home/guest/bio': Permission denied\n\
+        rm: cannot remove '/home/guest/notes': Is a directory");
+    let lost = run(denied.next_state.clone(), "rm -r ~/notes > ~/notes/log");
+    assert_eq!(text(&lost), "ttsh: /home/guest/notes/log: No such file or directory");
+    assert_eq!(lost.status, 1);
+    let state = run(denied.next_state, "rm -r ~/notes").next_state;
+    assert_eq!(state.home().files.len(), 0);
+    assert!(state.home().dirs.is_empty());
+
+    assert_eq!(text(&run(state, "cp /posts/node-alpha ~/")), "Copying 1 file...");
+    settle(&host);
+    let writes: Vec<EventKind> = events::drain().into_iter().map(|event| event.kind).filter(|kind| matches!(kind, EventKind::Write { .. })).collect();
+    assert_eq!(writes, [EventKind::Write { path: "/home/guest/node-alpha".into(), text: "Rust everywhere\n".into() }]);
+}
+
+#[test]
+fn commands_that_write_build_the_tree_once() {
+    let host = mock();
+    let state = run(session(), "ls ~").next_state;
+    let builds = || host.logs.borrow().iter().filter(|log| log.starts_with("fs: built")).count();
+    let before = builds();
+
+    // Each operand sees what the ones before it made; mkdir reuses the tree
+    // ls built, and every later command builds one for all its operands
+    let state = run(state, "mkdir ~/d ~/d/e ~/d/e/f").next_state;
+    let state = run(state, "touch ~/d/e/f/1 ~/d/e/f/2 ~/d/e/f/3").next_state;
+    let state = run(state, "mv ~/d ~/m").next_state;
+    let state = run(state, "mv ~/m/e/f/1 ~/m/e/f/2 ~/").next_state;
+    assert_eq!(builds() - before, 3);
+    assert_eq!(plain(text(&run(state.clone(), "ls -R ~"))), "\
+        /home/guest:\n1  2  m\n\n/home/guest/m:\ne\n\n\
+        /home/guest/m/e:\nf\n\n/home/guest/m/e/f:\n3");
+    assert_eq!(text(&run(state, "mv ~/1 ~/gone ~/2 ~/m")), "mv: cannot stat '/home/guest/gone': No such file or directory");
+}
+
+#[test]
+fn ls_sorts_recurses_and_types_its_lines() {
+    mock();
+    let mut state = session();
+    state.posts[0].meta.lastmod = "2026-01-05".into();
+    state.posts[0].meta.size = 300;
+    state.posts[1].meta.lastmod = "2026-06-01".into();
+    state.posts[1].meta.size = 2900;
+    state.files.insert("/home/guest/.profile".into(), "x".into());
+
+    assert_eq!(text(&run(state.clone(), "ls -t /posts")), "node-beta  node-alpha");
+    assert_eq!(text(&run(state.clone(), "ls -1Sr /posts")), "node-alpha\nnode-beta");
+    assert_eq!(text(&run(state.clone(), "ls -lh /posts/node-beta")), "-rw-r--r-- tecnoter staff  2.9K Jun 01 2026 /posts/node-beta [web]");
+    assert_eq!(text(&run(state.clone(), "ls /home/guest")), "");
+    assert_eq!(text(&run(state.clone(), "ls -a /home/guest")), "\x1b[1;34m.\x1b[0m  \x1b[1;34m..\x1b[0m  .profile");
+    assert_eq!(plain(text(&run(state.clone(), "ls -R /tags"))), "/tags:\nrust  web\n\n/tags/rust:\nnode-alpha\n\n/tags/web:\nnode-beta");
+
+    let types: Vec<String> = run(state.clone(), "ls -1 /").lines.into_iter().map(|line| line.line_type).collect();
+    assert_eq!(types, ["ls-dir"; 6]);
+    let types: Vec<String> = run(state.clone(), "ls -l /tags/rust").lines.into_iter().map(|line| line.line_type).collect();
+    assert_eq!(types, ["ls-file"]);
+    assert_eq!(run(state, "ls -x").status, 1);
+}
+
+#[test]
+fn paths_are_normalised_and_directories_remembered() {
+    mock();
+    let result = run(session(), "cd /posts/../tags//./rust");
+    assert_eq!(result.next_state.cwd, "/tags/rust");
+    assert_eq!(plain(text(&run(result.next_state.clone(), "ls ../.."))), "categories  home  pages  posts  tags  tmp");
+
+    let home = run(result.next_state, "cd");
+    assert_eq!(home.next_state.cwd, "/home/guest");
+    let back = run(home.next_state, "cd -");
+    assert_eq!(text(&back), "/tags/rust");
+    assert_eq!(text(&run(back.next_state.clone(), "echo ~ ~-")), "/home/guest /home/guest");
+
+    let pushed = run(back.next_state, "pushd ~");
+    assert_eq!(text(&pushed), "~ /tags/rust");
+    let popped = run(pushed.next_state, "popd");
+    assert_eq!(popped.next_state.cwd, "/tags/rust");
+    assert_eq!(text(&run(popped.next_state, "popd")), "popd: directory stack empty");
+}
+
+#[test]
+fn find_filters_the_tree() {
+    mock();
+    let mut state = session();
+    state.posts[0].meta.date = "2026-03-01".into();
+    state.posts[1].meta.date = "2025-12-31".into();
+    state.posts[1].tags.push("rust".into());
+
+    assert_eq!(text(&run(state.clone(), "find / -tag rust -newer 2026")), "/posts/node-alpha");
+    assert_eq!(text(&run(state.clone(), "find /tags -type d")), "/tags\n/tags/rust\n/tags/web");
+    assert_eq!(text(&run(state.clone(), "find posts -name '*beta' -maxdepth 1")), "posts/node-beta");
+
+    assert_eq!(text(&run(state.clone(), "find / -bogus")), "find: unknown predicate `-bogus'");
+    assert_eq!(text(&run(state.clone(), "find / -bogus x")), "find: unknown predicate `-bogus'");
+    assert_eq!(text(&run(state.clone(), "find / -name")), "find: missing argument to `-name'");
+    let result = run(state.clone(), "find /tags/rust /nope /tags/web");
+    assert_eq!(text(&result), "\
+        /tags/rust\n/tags/rust/node-alpha\n/tags/rust/node-beta\n\
+        find: '/nope': No such file or directory\n\
+        /tags/web\n/tags/web/node-beta");
+    assert_eq!(result.status, 1);
+    assert_eq!(text(&run(state, "find /nope /tags/web | wc -l")), "find: '/nope': No such file or directory\n2");
+}
+
+#[test]
+fn grep_searches_the_fetched_text() {
+    let host = mock();
+    host.respond("/posts/node-alpha/index.json", r#"{"title": "Node Alpha", "content": "Rust everywhere\nplain line"}"#);
+    host.respond("/posts/node-beta/index.json", r#"{"title": "Node Beta", "content": "a web (rusty) post"}"#);
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "Hello there"}"#);
+
+    let result = run(session(), "grep -i -n -r rust /posts");
+    assert_eq!(text(&result), "Searching 2 files...");
+    let events = events::drain();
+    let progress: Vec<&EventKind> = events.iter().filter(|event| matches!(event.kind, EventKind::Progress { .. })).map(|event| &event.kind).collect();
+    assert_eq!(progress.last(), Some(&&EventKind::Progress { text: "grep: searched 2 of 2 files".to_string() }));
+    assert_eq!(events[events.len() - 2].kind, EventKind::Stdout {
+        text: "\x1b[35mnode-alpha\x1b[0m:\x1b[32m1\x1b[0m:\x1b[1;31mRust\x1b[0m everywhere\n\
+               \x1b[35mnode-beta\x1b[0m:\x1b[32m1\x1b[0m:a web (\x1b[1;31mrust\x1b[0my) post".to_string(),
+    });
+
+    run(session(), "grep -l -F -r (rusty) / | cat");
+    assert_eq!(printed(), ["node-beta"]);
+    run(session(), "grep -c e /pages/bio /nowhere | cat");
+    assert_eq!(printed(), ["grep: /nowhere: No such file or directory", "bio:1"]);
+
+    // Only the matches go down the pipe; errors exit with 2, no match with 1
+    run(session(), "grep -r rust /nowhere /posts | wc -l");
+    assert_eq!(printed(), ["grep: /nowhere: No such file or directory", "1"]);
+    let state = run(session(), "echo one > /tmp/a").next_state;
+    let result = run(state.clone(), "grep o /nowhere /tmp/a");
+    assert_eq!((plain(text(&result)), result.status), ("grep: /nowhere: No such file or directory\na:one".to_string(), 2));
+    let result = run(state.clone(), "grep -c x /tmp/a");
+    assert_eq!((text(&result), result.status), ("0".to_string(), 1));
+    assert_eq!(run(state, "grep x /tmp/a").status, 1);
+}
+
+#[test]
+fn search_ranks_the_prebuilt_index() {
+    let host = mock();
+    let index = Index::build(vec![
+        Document { title: "Node Alpha".into(), slug: "node-alpha".into(), url: "/posts/node-alpha/".into(), tags: vec!["rust".into()], content: "Rust compiled to web assembly.".into(), ..Default::default() },
+        Document { title: "Bio".into(), slug: "bio".into(), url: "/pages/bio/".into(), content: "I write rust for the web, and rust only.".into(), ..Default::default() },
+    ]);
+    host.respond(search::INDEX_URL, &index.to_json());
+
+    run(session(), "search rust | cat");
+    assert_eq!(printed(), ["bio  Bio\n    I write rust for the web, and rust only.\nnode-alpha  Node Alpha\n    Rust compiled to web assembly."]);
+
+    // Loaded once, then searched without a fetch
+    let result = run(session(), "search 'web assembly' | cat");
+    assert_eq!(text(&result), "node-alpha  Node Alpha\n    Rust compiled to web assembly.");
+    assert_eq!(text(&run(session(), "search -n 1 web tag:rust | cat")), "node-alpha  Node Alpha\n    Rust compiled to web assembly.");
+    assert_eq!(host.fetched.borrow().len(), 1);
+    assert_eq!(run(session(), "search cobol").status, 1);
+}
+
+#[test]
+fn cat_prints_the_fetched_page() {
+    let host = mock();
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "Hello there"}"#);
+
+    let result = run(session(), "cat bio");
+    assert_eq!(text(&result), "Reading Bio...");
+    assert_eq!(*host.fetched.borrow(), ["/pages/bio/index.json"]);
+    assert_eq!(printed(), ["\n# Bio\n\nHello there"]);
+    assert_eq!(host.notified.get(), 1);
+}
+
+#[test]
+fn pipelines_resume_after_a_fetch() {
+    let host = mock();
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "one two three"}"#);
+
+    run(session(), "cat bio | wc -w");
+    assert_eq!(printed(), ["3"]);
+}
+
+#[test]
+fn semicolon_lists_wait_for_a_fetch() {
+    let host = mock();
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "Hello there"}"#);
+
+    let result = run(session(), "cat bio; echo after");
+    assert_eq!(text(&result), "Reading Bio...");
+    settle(&host);
+    assert_eq!(printed(), ["\n# Bio\n\nHello there", "after"]);
+
+    run(session(), "cat bio > /tmp/b; cat /tmp/b");
+    settle(&host);
+    assert_eq!(printed(), ["Hello there"]);
+
+    run(session(), "grep -r zzz /pages; echo $?");
+    settle(&host);
+    assert_eq!(printed(), ["1"]);
+}
+
+#[test]
+fn help_man_and_completion_cover_every_command() {
+    mock();
+    let state = session();
+    let help = text(&run(state.clone(), "help"));
+    for command in commands::registry() {
+        let name = command.name();
+        let man = run(state.clone(), &format!("man {}", name));
+        let completions = completer::get_completions(&state, name);
+        if name.starts_with('_') {
+            assert!(!help.lines().any(|line| line.trim_start().starts_with(&format!("{} ", name))), "{}", name);
+            assert_eq!(text(&man), format!("No manual entry for {}", name));
+            assert!(completions.is_empty(), "{}: {:?}", name, completions);
+            continue;
+        }
+        assert!(text(&man).starts_with(&format!("NAME\n    {} - {}", name, command.summary())), "{}", text(&man));
+        if command.allowed(state.login_state) {
+            assert!(help.lines().any(|line| line.trim_start().starts_with(&format!("{} ", name)) && line.ends_with(&format!(" - {}", command.summary()))), "{} is not in help", name);
+            assert!(completions.iter().any(|c| c == name), "{}: {:?}", name, completions);
+        } else {
+            assert!(!completions.iter().any(|c| c == name), "{}: {:?}", name, completions);
+        }
+    }
+}
+
+#[test]
+fn completion_restarts_after_an_operator() {
+    mock();
+    let state = session();
+    assert_eq!(completer::get_completions(&state, "cat bio | gr"), ["grep"]);
+    assert_eq!(completer::get_completions(&state, "ls /tags && cd /po"), ["/posts"]);
+    assert_eq!(text(&run(state, "_suggest ls; ech")), "ls; echo");
+}
+
+#[test]
+fn bbs_pauses_go_back_to_the_screen_they_were_opened_from() {
+    let host = mock();
+    host.respond("/posts/node-alpha/index.json", r#"{"title": "Node Alpha", "content": "Hello"}"#);
+    let mut state = session();
+    state.posts[0].categories = vec!["dev".into()];
+
+    let state = run(state, "bbs c").next_state;
+    assert_eq!(state.login_state, LoginState::BbsCategories);
+    let state = run(state, "1").next_state;
+    assert_eq!((state.login_state, state.cwd.as_str()), (LoginState::BbsPosts, "/categories/dev"));
+    let state = run(state, "1").next_state;
+    settle(&host);
+    assert_eq!(state.login_state, LoginState::BbsPause);
+    assert_eq!(state.nav_stack, [LoginState::BbsMain, LoginState::BbsCategories, LoginState::BbsPosts]);
+
+    // Any key ends the pause, then B walks back one screen at a time
+    let result = run(state, "");
+    assert_eq!(result.next_state.login_state, LoginState::BbsPosts);
+    assert!(text(&result).contains("Node Alpha"));
+    let result = run(result.next_state, "b");
+    assert_eq!(result.next_state.login_state, LoginState::BbsCategories);
+    assert!(text(&result).contains("dev"));
+    let state = run(result.next_state, "b").next_state;
+    assert_eq!(state.login_state, LoginState::BbsMain);
+    assert!(state.nav_stack.is_empty());
+    assert_eq!(run(state, "b").next_state.login_state, LoginState::BbsMain);
+}
+
+#[test]
+fn leaving_the_bbs_forgets_its_screens() {
+    mock();
+    let state = run(session(), "bbs c").next_state;
+    let state = run(state, "r").next_state;
+    assert_eq!(state.nav_stack, [LoginState::BbsMain, LoginState::BbsCategories]);
+
+    let state = run(state, "q").next_state;
+    assert_eq!(state.login_state, LoginState::Prompt);
+    assert!(state.nav_stack.is_empty());
+    let state = run(state, "bbs").next_state;
+    assert_eq!(run(state, "b").next_state.login_state, LoginState::BbsMain);
+}
+
+#[test]
+fn curl_reports_http_and_network_errors() {
+    let host = mock();
+    host.respond_with("http://down.example", Err(FetchError::Network("refused".into())));
+    host.respond_with("http://gone.example", Ok(Response { status: 410, ..Default::default() }));
+
+    let mut result = shell::process_input(session(), "curl http://gone.example");
+    let outcome = native::block_on(result.pending.take().unwrap());
+    assert_eq!(outcome.status, 1);
+
+    run(session(), "curl http://down.example");
+    settle(&host);
+    assert_eq!(printed().last().unwrap(), "curl: network error (CORS?)");
+    assert_eq!(host.fetched.borrow().len(), 4);
+}
+
+#[test]
+fn pages_are_cached_and_revalidated() {
+    let host = mock();
+    let page = Response {
+        status: 200,
+        headers: vec![("ETag".into(), "\"v1\"".into())],
+        body: r#"{"title": "Bio", "content": "Hello"}"#.into(),
+    };
+    host.respond_with("/pages/bio/index.json", Ok(page));
+
+    run(session(), "cat bio");
+    run(session(), "cat bio");
+    assert_eq!(printed(), ["\n# Bio\n\nHello", "\n# Bio\n\nHello"]);
+    let sent = host.sent_headers.borrow();
+    assert_eq!(sent[0], []);
+    assert_eq!(sent[1], [("If-None-Match".to_string(), "\"v1\"".to_string())]);
+}
+
+#[test]
+fn transient_errors_are_retried_with_backoff() {
+    let host = mock();
+    let url = "/pages/bio/index.json";
+    host.respond_once(url, Err(FetchError::Network("reset".into())));
+    host.respond_once(url, Ok(Response { status: 503, ..Default::default() }));
+    host.respond(url, r#"{"title": "Bio", "content": "Hello"}"#);
+
+    run(session(), "cat bio");
+    settle(&host);
+    assert_eq!(printed(), ["\n# Bio\n\nHello"]);
+    assert_eq!(host.fetched.borrow().len(), 3);
+    let backoff: Vec<Duration> = host.slept.borrow().iter().copied().filter(|d| *d < Duration::from_secs(1)).collect();
+    assert_eq!(backoff, [Duration::from_millis(250), Duration::from_millis(500)]);
+}
+
+#[test]
+fn slow_requests_time_out() {
+    let host = mock();
+    http::configure(Config { timeout: Duration::from_secs(2), retries: 1, ..Config::default() });
+    host.hang("http://slow.example");
+
+    run(session(), "curl http://slow.example");
+    settle(&host);
+    assert_eq!(printed(), ["curl: operation timed out"]);
+    assert_eq!(host.aborted.get(), 2);
+}
+
+#[test]
+fn background_writes_reach_the_host() {
+    let host = mock();
+    host.respond("http://example.com", "<html></html>");
+
+    run(session(), "curl http://example.com > /tmp/page.html");
+    let write = EventKind::Write { path: "/tmp/page.html".to_string(), text: "<html></html>\n".to_string() };
+    assert!(events::drain().iter().any(|event| event.kind == write));
+}
+
+#[test]
+fn background_output_is_tagged_with_its_job() {
+    let host = mock();
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "Hello"}"#);
+
+    let first = shell::process_input(session(), "cat bio").pending.unwrap();
+    let second = shell::process_input(session(), "cat bio | wc -w").pending.unwrap();
+    // The first job is still running when the second one finishes
+    let first = Box::pin(async move {
+        after_polls(3).await;
+        first.await
+    });
+    let a = shell::start_job("cat bio", first, true);
+    let b = shell::start_job("cat bio | wc -w", second, true);
+    assert_eq!(b, a + 1);
+    assert_eq!(host.run_jobs(), 0);
+
+    // The second job's events come first and keep its tag
+    let events = events::drain();
+    let jobs: Vec<u32> = events.iter().map(|event| event.job).collect();
+    assert_eq!(jobs, [b, b, a, a]);
+    assert_eq!(events[0].kind, EventKind::Stdout { text: "1".to_string() });
+    assert_eq!(events[1], Event { job: b, command: "cat bio | wc -w".to_string(), kind: EventKind::Done { status: 0 } });
+    assert_eq!(events[3].kind, EventKind::Done { status: 0 });
+}
+
+#[test]
+fn background_jobs_are_reported_once_they_end() {
+    let host = mock();
+    host.respond("/pages/bio/index.json", r#"{"title": "Bio", "content": "Hello"}"#);
+    host.hang("/posts/node-alpha/index.json");
+
+    let result = run(session(), "cat bio & false &");
+    assert_eq!(text(&result), "[1]\nReading Bio...\n[2]");
+    assert_eq!(result.status, 0);
+    assert_eq!(jobs::foreground(), None);
+    assert_eq!(printed(), ["\n# Bio\n\nHello"]);
+
+    let result = run(result.next_state, "cat node-alpha &");
+    assert_eq!(text(&result), "[1]\nReading Node Alpha...\n[1]-  Done                    cat bio\n[2]+  Exit 1                  false");
+    let result = run(result.next_state, "echo hi");
+    assert_eq!(text(&result), "hi");
+    assert_eq!(text(&run(result.next_state, "jobs")), "[1]+  Running                 cat node-alpha &");
+}
+
+#[test]
+fn kill_cancels_the_requests_of_a_job() {
+    let host = mock();
+    host.hang("/pages/bio/index.json");
+
+    let result = run(session(), "cat bio | wc -w &");
+    assert_eq!(text(&result), "[1]");
+    let result = run(result.next_state, "jobs");
+    assert_eq!(text(&result), "[1]+  Running                 cat bio | wc -w &");
+
+    let result = run(result.next_state, "kill %1");
+    assert_eq!(text(&result), "[1]+  Terminated              cat bio | wc -w");
+    assert_eq!(host.run_jobs(), 0);
+    assert_eq!(host.aborted.get(), 1);
+    assert_eq!(events::drain().last().unwrap().kind, EventKind::Done { status: jobs::TERMINATED });
+
+    let result = run(result.next_state, "kill %1");
+    assert_eq!(text(&result), "kill: %1: no such job");
+    assert_eq!(result.status, 1);
+}
+
+#[test]
+fn interrupt_stops_the_foreground_job() {
+    let host = mock();
+    host.hang("/pages/bio/index.json");
+
+    let result = run(session(), "cat bio");
+    assert_eq!(jobs::foreground(), Some(1));
+    let result = run(result.next_state, "_interrupt");
+    assert_eq!(text(&result), "[1]+  Terminated              cat bio");
+    assert_eq!(result.next_state.last_status, jobs::INTERRUPTED);
+    assert_eq!(jobs::foreground(), None);
+    host.run_jobs();
+    assert_eq!(host.aborted.get(), 1);
+
+    // A background job brought to the foreground stops the same way
+    let result = run(result.next_state, "cat bio &");
+    let result = run(result.next_state, "fg %1");
+    assert_eq!(text(&result), "cat bio");
+    assert_eq!(jobs::foreground(), Some(1));
+    run(result.next_state, "_interrupt");
+    host.run_jobs();
+    assert_eq!(host.aborted.get(), 2);
+}
+
+#[test]
+fn interrupting_wait_leaves_the_jobs_running() {
+    let host = mock();
+    host.hang("/pages/bio/index.json");
+
+    let result = run(session(), "cat bio &");
+    let result = run(result.next_state, "wait");
+    assert_eq!(jobs::foreground(), Some(2));
+    let result = run(result.next_state, "_interrupt");
+    assert_eq!(text(&result), "[2]+  Terminated              wait");
+    assert_eq!(text(&run(result.next_state, "jobs")), "[1]+  Running                 cat bio &");
+    assert_eq!(host.aborted.get(), 0);
+}
+
+#[test]
+fn clock_and_randomness_come_from_the_platform() {
+    let host = Rc::new(MockPlatform::default().with_randoms(vec![0.9]));
+    platform::install(host.clone());
+    let mut state = session();
+    state.fortunes = vec!["first".into(), "second".into()];
+
+    assert_eq!(text(&run(state.clone(), "date")), host.now);
+    assert_eq!(text(&run(state, "fortune")), "\nNODE WISDOM: second");
+}
+
+#[test]
+fn native_clock_formats_like_javascript() {
+    assert_eq!(native::format_time(0), "Thu Jan 01 1970 00:00:00 GMT+0000 (Coordinated Universal Time)");
+    assert_eq!(native::format_time(1_792_328_645), "Sun Oct 18 2026 13:04:05 GMT+0000 (Coordinated Universal Time)");
+}
@@ -0,0 +1,126 @@
+//! The `ttsh-sshd` binary serving `tests/fixtures/site`, driven by an SSH client.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use russh::client::{self, KeyboardInteractiveAuthResponse as Auth};
+use russh::keys::PublicKeyOrCertificate;
+use russh::ChannelMsg;
+
+/// Trusts any host key, as a first connection does.
+struct Client;
+
+impl client::Handler for Client {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, _key: &PublicKeyOrCertificate) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// A host key file the daemon creates, removed when dropped.
+struct HostKey(PathBuf);
+
+impl Drop for HostKey {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// The daemon, with a host key of its own.
+fn daemon(args: &[&str]) -> (common::Daemon, HostKey) {
+    static KEYS: AtomicUsize = AtomicUsize::new(0);
+    let name = format!("ttsh-sshd-test-{}-{}", std::process::id(), KEYS.fetch_add(1, Ordering::Relaxed));
+    let key = HostKey(std::env::temp_dir().join(name));
+    let mut all = vec!["--host-key", key.0.to_str().unwrap()];
+    all.extend(args);
+    (common::start(env!("CARGO_BIN_EXE_ttsh-sshd"), &all), key)
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(future)
+}
+
+/// A connection to `daemon`, and how logging in as `user` went.
+async fn connect(daemon: &common::Daemon, user: &str) -> (client::Handle<Client>, Auth) {
+    let mut handle = client::connect(Arc::new(client::Config::default()), daemon.address.as_str(), Client).await.unwrap();
+    let auth = handle.authenticate_keyboard_interactive_start(user, None).await.unwrap();
+    (handle, auth)
+}
+
+/// What the channel printed until it closed, and the exit status it reported.
+async fn output(channel: &mut russh::Channel<client::Msg>) -> (String, Option<u32>) {
+    let mut text = Vec::new();
+    let mut status = None;
+    while let Some(message) = channel.wait().await {
+        match message {
+            ChannelMsg::Data { data } => text.extend_from_slice(&data),
+            ChannelMsg::ExitStatus { exit_status } => status = Some(exit_status),
+            _ => {}
+        }
+    }
+    (String::from_utf8(text).unwrap(), status)
+}
+
+#[test]
+fn exec_runs_one_command() {
+    let (daemon, _key) = daemon(&[]);
+    block_on(async {
+        let (handle, auth) = connect(&daemon, "guest").await;
+        assert!(matches!(auth, Auth::Success));
+        let mut channel = handle.channel_open_session().await.unwrap();
+        channel.exec(true, "ls -1 /posts; cat /nope").await.unwrap();
+        assert_eq!(output(&mut channel).await, ("hello\ncat: /nope: No such file or directory\n".to_string(), Some(1)));
+    });
+}
+
+#[test]
+fn a_shell_without_a_terminal_reads_lines_until_eof() {
+    let (daemon, _key) = daemon(&[]);
+    block_on(async {
+        let (handle, _) = connect(&daemon, "guest").await;
+        let mut channel = handle.channel_open_session().await.unwrap();
+        channel.request_shell(true).await.unwrap();
+        channel.data_bytes(&b"echo hi\nfalse\n"[..]).await.unwrap();
+        channel.eof().await.unwrap();
+        let (text, status) = output(&mut channel).await;
+        assert!(text.contains("Welcome to tecnoter.io, guest!\n"), "{}", text);
+        assert!(text.ends_with("$ echo hi\nhi\nguest@tecnoter.io:/$ false\nguest@tecnoter.io:/$ "), "{}", text);
+        assert!(!text.contains('\r'));
+        assert_eq!(status, Some(1));
+    });
+}
+
+#[test]
+fn logins_go_through_the_node() {
+    let (daemon, _key) = daemon(&[]);
+    block_on(async {
+        let (_, auth) = connect(&daemon, "nobody").await;
+        assert!(matches!(auth, Auth::Failure { .. }));
+
+        let (mut handle, auth) = connect(&daemon, "admin").await;
+        let Auth::InfoRequest { prompts, .. } = auth else { panic!("no password prompt") };
+        assert_eq!((prompts[0].prompt.as_str(), prompts[0].echo), ("Password: ", false));
+        let auth = handle.authenticate_keyboard_interactive_respond(vec!["wrong".to_string()]).await.unwrap();
+        assert!(matches!(auth, Auth::Failure { .. }));
+    });
+}
+
+#[test]
+fn idle_sessions_are_told_before_the_channel_closes() {
+    let (daemon, _key) = daemon(&["--idle-timeout", "1"]);
+    block_on(async {
+        let (handle, _) = connect(&daemon, "guest").await;
+        let mut channel = handle.channel_open_session().await.unwrap();
+        channel.request_pty(true, "xterm", 80, 24, 0, 0, &[]).await.unwrap();
+        channel.request_shell(true).await.unwrap();
+        let started = Instant::now();
+        let (text, status) = output(&mut channel).await;
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(text.ends_with("$ \r\nIdle timeout, closing connection.\r\n"), "{}", text);
+        assert_eq!(status, Some(0));
+    });
+}
@@ -0,0 +1,22 @@
+//! The `ttsh-telnetd` binary serving `tests/fixtures/site` on a local port.
+
+mod common;
+
+use std::io::Read;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+#[test]
+fn idle_connections_are_closed() {
+    let daemon = common::start(env!("CARGO_BIN_EXE_ttsh-telnetd"), &["--idle-timeout", "1"]);
+    let mut stream = TcpStream::connect(&daemon.address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let started = Instant::now();
+    let mut output = Vec::new();
+    stream.read_to_end(&mut output).unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("tecnoter login: "), "{}", output);
+    assert!(output.ends_with("\r\nIdle timeout, closing connection.\r\n"), "{}", output);
+}
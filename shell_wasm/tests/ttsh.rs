@@ -0,0 +1,47 @@
+//! The `ttsh` binary run against the small Hugo build in `tests/fixtures/site`.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// What `ttsh --batch` printed for `input`, and its exit status.
+fn batch(input: &str) -> (String, i32) {
+    let site = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/site");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ttsh"))
+        .args(["--site", site, "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (String::from_utf8(output.stdout).unwrap(), output.status.code().unwrap())
+}
+
+#[test]
+fn batch_runs_lines_from_stdin_against_the_site() {
+    let (output, status) = batch("ls -1 /posts\ncat /posts/hello | wc -w\nfind / -tag rust\n");
+    assert_eq!(output, "hello\n4\n/posts/hello\n");
+    assert_eq!(status, 0);
+}
+
+#[test]
+fn the_exit_status_is_that_of_the_last_line() {
+    let (output, status) = batch("cat /nope\n");
+    assert_eq!(output, "cat: /nope: No such file or directory\n");
+    assert_eq!(status, 1);
+    assert_eq!(batch("cat /nope\ntrue\n").1, 0);
+}
+
+#[test]
+fn logout_ends_the_session() {
+    let (output, status) = batch("echo before\nlogout\necho after\n");
+    assert_eq!(output, "before\n");
+    assert_eq!(status, 0);
+}
+
+#[test]
+fn login_commands_are_refused_at_the_prompt() {
+    let (output, status) = batch("_login admin\n_start_login\necho still here\n");
+    assert_eq!(output, "command not found: _login\ncommand not found: _start_login\nstill here\n");
+    assert_eq!(status, 0);
+}
@@ -1,8 +1,10 @@
-import { state, fs, resolvePath } from '/js/system.js';
+import { state, fs, resolvePath, foregroundJob } from '/js/system.js';
 import { input, output, print, getPS1, updatePrompt, suggestionElement } from '/js/ui.js';
 import { renderHubContent, populateHubPosts } from '/js/fbui.js';
 
 let run, man;
+// Ctrl-R reverse search while active: { query, original, matches, index }
+let search = null;
 
 export function handleAutocomplete() {
   const val = input.value;
@@ -90,6 +92,24 @@ export function initListeners(handlers) {
     }
   }
 
+  // The input holds the search query while the suggestion area shows the match
+  function updateSearch() {
+    const resp = window.terminalSystem?.wasm?.process_input(state, `_history_search ${search.query}`);
+    search.matches = (resp?.lines || []).map(l => l.text);
+    search.index = Math.min(search.index, Math.max(0, search.matches.length - 1));
+    if (suggestionElement) {
+      suggestionElement.textContent = `  (reverse-i-search)\`${search.query}': ${search.matches[search.index] || ""}`;
+    }
+  }
+
+  function endSearch(accept) {
+    const match = search.matches[search.index];
+    input.value = accept && match ? match : search.original;
+    input.style.width = Math.max(1, input.value.length) + "ch";
+    search = null;
+    clearSuggestion();
+  }
+
   function setSystemMode(mode) {
     state.systemMode = mode;
     sessionStorage.setItem('tecnoter_mode', mode);
@@ -219,6 +239,12 @@ export function initListeners(handlers) {
   if (input) {
     input.addEventListener("input", () => {
       input.style.width = input.value.length + "ch";
+      if (search) {
+        search.query = input.value;
+        search.index = 0;
+        updateSearch();
+        return;
+      }
       triggerSuggestion();
     });
 
@@ -227,10 +253,47 @@ export function initListeners(handlers) {
       const isBBS = state.loginState.startsWith("BBS");
       const isEmpty = input.value === "";
 
+      if (foregroundJob !== null) {
+        // Only Ctrl-C gets through while a command is running
+        if (e.ctrlKey && k === "c") {
+          print("^C");
+          run("_interrupt");
+        }
+        if (e.key === "Enter" || e.ctrlKey) e.preventDefault();
+        return;
+      }
+
       if (state.loginState === "BBS_PAUSE") {
+        // Any key: the core returns to the screen the pause was opened from
+        e.preventDefault();
+        import('/js/bbs.js').then(m => m.handleBBSInput(""));
+        return;
+      }
+
+      if (search) {
+        if (e.ctrlKey && k === "r") {
+          // Again: the next older match
+          e.preventDefault();
+          search.index++;
+          updateSearch();
+          return;
+        }
+        if (e.key === "Escape" || (e.ctrlKey && k === "g")) {
+          e.preventDefault();
+          endSearch(false);
+          return;
+        }
+        if (e.key === "Enter") {
+          endSearch(true);
+        } else if (e.key === "ArrowRight" || e.key === "Tab") {
+          e.preventDefault();
+          endSearch(true);
+          return;
+        }
+      } else if (e.ctrlKey && k === "r" && state.loginState === "PROMPT") {
         e.preventDefault();
-        state.loginState = "BBS_MAIN";
-        import('/js/bbs.js').then(m => m.showBBSMainMenu());
+        search = { query: input.value, original: input.value, matches: [], index: 0 };
+        updateSearch();
         return;
       }
 
@@ -264,15 +327,16 @@ export function initListeners(handlers) {
         clearSuggestion();
         if (state.loginState === "LOGIN") { onLogin(val); return; }
         if (isBBS || state.loginState === "PROMPT" || state.loginState === "PASSWORD") {
-          if (state.loginState === "PROMPT" && val) {
+          const atPrompt = state.loginState === "PROMPT";
+          if (atPrompt && val) {
             print(`${getPS1()} ${val}`);
-            // Save to history
-            state.history.push(val);
-            if (state.history.length > 100) state.history.shift();
             state.histIndex = -1;
-            localStorage.setItem('tecnoter_history', JSON.stringify(state.history));
           }
           run(val);
+          // The core records the line (after `!!` and friends are expanded); we only persist it
+          if (atPrompt && val) {
+            localStorage.setItem('tecnoter_history', JSON.stringify(state.history));
+          }
         }
       }
 
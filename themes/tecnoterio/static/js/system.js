@@ -18,6 +18,8 @@ const defaultHistory = [
   "curl https://jsonplaceholder.typicode.com/posts/1"
 ];
 const savedHistory = JSON.parse(localStorage.getItem('tecnoter_history') || JSON.stringify(defaultHistory));
+// Files and directories under /home, kept between visits; /tmp starts empty
+const savedHome = JSON.parse(localStorage.getItem('tecnoter_home') || '{"files": {}, "dirs": []}');
 
 window.getSystemDate = () => {
   return new Date().toString();
@@ -26,7 +28,7 @@ window.getSystemDate = () => {
 export const state = {
   cwd: "/",
   currentUser: "guest",
-  loginState: "UNINITIALIZED", // BOOT, LOGIN, PASSWORD, PROMPT, MESSAGE
+  loginState: "UNINITIALIZED", // see LoginState in shell_wasm/src/state.rs
   posts: [],
   pages: [],
   socials: [],
@@ -46,11 +48,13 @@ export const state = {
   loginTicker: null,
   currentPostIndex: -1,
   mailRecipient: null,
-  returnState: "PROMPT",
+  navStack: [],
   systemMode: savedMode || (getIsInternal() ? "HUB" : "TERMINAL"), // Persistence > Context > Default
   booted: false,
   isAuthenticated: false,
-  debugMode: false
+  debugMode: false,
+  files: savedHome.files, // User files under /tmp and /home/<user>, owned by the Rust core
+  dirs: savedHome.dirs // Directories made by the user there
 };
 
 export const VALID_USERS = ["guest", "bbs", "admin"];
@@ -113,6 +117,70 @@ export function syncState(newState) {
   Object.keys(newState).forEach(key => {
     state[key] = newState[key];
   });
+  saveHome();
+}
+
+// Stores a user file written by a background > or >>, or a fetched cp
+export function writeFile(path, text) {
+  state.files = state.files || {};
+  state.files[path] = text;
+  saveHome();
+}
+
+// Persists what is under /home, as SystemState::home does for native hosts
+function saveHome() {
+  const inHome = path => path.startsWith("/home/");
+  const files = Object.fromEntries(Object.entries(state.files || {}).filter(([path]) => inHome(path)));
+  const dirs = (state.dirs || []).filter(inHome);
+  try {
+    localStorage.setItem('tecnoter_home', JSON.stringify({ files, dirs }));
+  } catch (e) {
+    console.warn("Could not save the home directory:", e);
+  }
+}
+
+// The job the prompt waits for (a fetch and the pipeline waiting on it), or
+// null; Ctrl-C sends `_interrupt` to stop it. See shell_wasm/src/jobs.rs
+export let foregroundJob = null;
+// The progress line each running job shows, by job id
+const progressLines = new Map();
+
+// Called by the Rust core when jobs have queued output; see shell_wasm/src/events.rs
+export function onEvents() {
+  if (!wasm) return;
+  const { print } = window.terminalUI || {};
+  for (const event of wasm.drain_events()) {
+    if (window.DEBUG_MODE) console.log("WASM Event:", event);
+    switch (event.type) {
+      case "stdout":
+      case "stderr":
+        if (print) print(event.text, "regular");
+        break;
+      case "write":
+        writeFile(event.path, event.text);
+        break;
+      case "progress":
+        if (progressLines.has(event.job)) {
+          progressLines.get(event.job).textContent = event.text;
+        } else if (print) {
+          const line = print("", "progress");
+          if (line) {
+            line.textContent = event.text;
+            progressLines.set(event.job, line);
+          }
+        }
+        break;
+      case "done":
+        progressLines.get(event.job)?.remove();
+        progressLines.delete(event.job);
+        state.lastStatus = event.status;
+        if (event.job === foregroundJob) {
+          foregroundJob = null;
+          if (window.terminalUI?.updatePrompt) window.terminalUI.updatePrompt();
+        }
+        break;
+    }
+  }
 }
 
 export function processWithWasm(line) {
@@ -142,6 +210,7 @@ export function processWithWasm(line) {
         });
      }
      syncState(result.state);
+     foregroundJob = result.job ?? null;
      if (window.terminalUI?.updateUplinkStatus) window.terminalUI.updateUplinkStatus();
      if (updatePrompt) updatePrompt();
      return true;
@@ -157,7 +226,10 @@ window.terminalSystem = {
   resolvePath, 
   initWasm,
   syncState,
+  writeFile,
+  onEvents,
   processWithWasm,
+  get foregroundJob() { return foregroundJob; },
   get wasm() { return wasm; },
   ping: () => {
     if (!wasm) return "WASM not initialized";
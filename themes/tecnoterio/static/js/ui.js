@@ -1,4 +1,4 @@
-import { state } from '/js/system.js';
+import { state, foregroundJob } from '/js/system.js';
 
 export let output, input, promptElement, suggestionElement;
 
@@ -25,12 +25,26 @@ function initCheck() {
   return true;
 }
 
+// ANSI colour codes (ESC[1;31m ... ESC[0m) as spans styled by terminal.css
+function sgrToHtml(line) {
+  let open = 0;
+  const html = line.replace(/\x1b\[([\d;]*)m/g, (_, codes) => {
+    const closing = "</span>".repeat(open);
+    open = 0;
+    const classes = codes.split(";").filter(code => code && code !== "0");
+    if (classes.length === 0) return closing;
+    open = 1;
+    return closing + `<span class="${classes.map(code => "sgr-" + code).join(" ")}">`;
+  });
+  return html + "</span>".repeat(open);
+}
+
 export function print(line = "", type = "") {
   if (!initCheck()) return null;
   
   const p = document.createElement("div");
   if (type) p.classList.add(type);
-  p.innerHTML = line;
+  p.innerHTML = String(line).includes("\x1b") ? sgrToHtml(String(line)) : line;
   output.appendChild(p);
   
   const terminalElement = document.getElementById("terminal");
@@ -48,7 +62,10 @@ export function getPS1() {
 export function updatePrompt() {
   if (!initCheck()) return;
   
-  if (state.loginState === "PROMPT") {
+  if (foregroundJob !== null) {
+    // A command is still running
+    promptElement.innerHTML = "";
+  } else if (state.loginState === "PROMPT") {
     promptElement.innerHTML = getPS1();
   } else if (state.loginState === "LOGIN") {
     promptElement.innerHTML = "tecnoter login: ";